@@ -0,0 +1,25 @@
+-- Store transaction amounts as exact integer minor units (cents) instead of REAL.
+-- SQLite cannot change a column type in place, so the table is rebuilt.
+
+CREATE TABLE transactions_new (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  account_id   INTEGER NOT NULL
+                 REFERENCES accounts(id) ON DELETE RESTRICT,
+  date         TEXT NOT NULL,        -- 'YYYY-MM-DD'
+  description  TEXT,
+  amount       INTEGER NOT NULL,     -- cents; income > 0, expense < 0
+  category_id  INTEGER NULL
+                 REFERENCES categories(id) ON DELETE SET NULL,
+  created_at   TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT INTO transactions_new (id, account_id, date, description, amount, category_id, created_at)
+SELECT id, account_id, date, description, CAST(ROUND(amount * 100) AS INTEGER), category_id, created_at
+FROM transactions;
+
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
+
+CREATE INDEX IF NOT EXISTS idx_tx_account_date ON transactions(account_id, date, id);
+CREATE INDEX IF NOT EXISTS idx_tx_date_id      ON transactions(date, id);
+CREATE INDEX IF NOT EXISTS idx_tx_category_id  ON transactions(category_id);
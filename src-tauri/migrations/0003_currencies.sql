@@ -0,0 +1,15 @@
+-- Per-account currency (ISO 4217 code) + local exchange-rate table.
+
+ALTER TABLE accounts ADD COLUMN currency TEXT NOT NULL DEFAULT 'EUR';
+
+-- 1 unit of `currency` = `rate` units of `base`, valid from `date` on.
+CREATE TABLE IF NOT EXISTS exchange_rates (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  date        TEXT NOT NULL,        -- 'YYYY-MM-DD'
+  currency    TEXT NOT NULL,
+  base        TEXT NOT NULL,
+  rate        REAL NOT NULL CHECK (rate > 0),
+  created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+  UNIQUE (date, currency, base)
+);
+CREATE INDEX IF NOT EXISTS idx_rates_pair_date ON exchange_rates(currency, base, date);
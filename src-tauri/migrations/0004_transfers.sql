@@ -0,0 +1,39 @@
+-- First-class transfers: both legs of a transfer share a transfer_id.
+
+CREATE TABLE IF NOT EXISTS transfers (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+ALTER TABLE transactions ADD COLUMN transfer_id INTEGER NULL
+  REFERENCES transfers(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_tx_transfer_id ON transactions(transfer_id);
+
+-- Link legacy transfers: rows in the "Transfer" category on the same day with
+-- opposite amounts in different accounts become a pair (oldest rows first).
+CREATE TEMP TABLE _transfer_pairs AS
+WITH tr AS (
+  SELECT t.id, t.account_id, t.date, t.amount,
+         ROW_NUMBER() OVER (
+           PARTITION BY t.date, ABS(t.amount), t.amount < 0
+           ORDER BY t.id
+         ) AS rn
+  FROM transactions t
+  JOIN categories c ON c.id = t.category_id
+  WHERE LOWER(c.name) = 'transfer' AND t.amount <> 0
+)
+SELECT o.id AS out_id, i.id AS in_id
+FROM tr o
+JOIN tr i ON i.date = o.date AND i.amount = -o.amount AND i.rn = o.rn
+WHERE o.amount < 0 AND i.account_id <> o.account_id;
+
+INSERT INTO transfers (id) SELECT out_id FROM _transfer_pairs;
+
+UPDATE transactions
+SET transfer_id = (
+  SELECT p.out_id FROM _transfer_pairs p
+  WHERE p.out_id = transactions.id OR p.in_id = transactions.id
+)
+WHERE id IN (SELECT out_id FROM _transfer_pairs UNION SELECT in_id FROM _transfer_pairs);
+
+DROP TABLE _transfer_pairs;
@@ -0,0 +1,24 @@
+-- Split transactions: one parent row, several (category, amount, memo) lines
+-- that must sum to the parent amount (checked by the app).
+
+CREATE TABLE IF NOT EXISTS transaction_splits (
+  id              INTEGER PRIMARY KEY AUTOINCREMENT,
+  transaction_id  INTEGER NOT NULL
+                    REFERENCES transactions(id) ON DELETE CASCADE,
+  category_id     INTEGER NULL
+                    REFERENCES categories(id) ON DELETE SET NULL,
+  amount          INTEGER NOT NULL,     -- cents; same sign convention as transactions
+  memo            TEXT,
+  position        INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS idx_splits_tx       ON transaction_splits(transaction_id, position);
+CREATE INDEX IF NOT EXISTS idx_splits_category ON transaction_splits(category_id);
+
+-- Accounting lines: an unsplit transaction is one line, a split one contributes its splits.
+CREATE VIEW IF NOT EXISTS transaction_lines AS
+  SELECT t.id AS tx_id, t.category_id, t.amount, NULL AS memo, 0 AS position
+  FROM transactions t
+  WHERE NOT EXISTS (SELECT 1 FROM transaction_splits s WHERE s.transaction_id = t.id)
+  UNION ALL
+  SELECT s.transaction_id AS tx_id, s.category_id, s.amount, s.memo, s.position
+  FROM transaction_splits s;
@@ -0,0 +1,10 @@
+-- Data carried over from bank statement imports (CAMT, MT940, ...).
+-- bank_ref is the bank's own entry reference; re-importing a statement skips refs we already have.
+
+ALTER TABLE transactions ADD COLUMN counterparty      TEXT;
+ALTER TABLE transactions ADD COLUMN counterparty_iban TEXT;
+ALTER TABLE transactions ADD COLUMN bank_ref          TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_bank_ref
+  ON transactions(account_id, bank_ref)
+  WHERE bank_ref IS NOT NULL;
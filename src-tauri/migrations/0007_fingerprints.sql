@@ -0,0 +1,7 @@
+-- Duplicate detection: hash of date, amount, normalized description and bank_ref,
+-- computed by the app (existing rows are filled in when a database is opened).
+
+ALTER TABLE transactions ADD COLUMN fingerprint TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_transactions_fingerprint
+  ON transactions(account_id, fingerprint);
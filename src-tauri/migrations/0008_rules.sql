@@ -0,0 +1,39 @@
+-- Auto-categorization rules, applied on import, on add_transaction and by apply_rules.
+-- Every condition that is set must match; unset conditions match anything.
+-- Rules run in (position, id) order: the first matching rule that sets a category
+-- (or description) wins, tags from all matching rules are added.
+
+CREATE TABLE IF NOT EXISTS rules (
+  id                    INTEGER PRIMARY KEY AUTOINCREMENT,
+  name                  TEXT NOT NULL,
+  position              INTEGER NOT NULL DEFAULT 0,
+  enabled               INTEGER NOT NULL DEFAULT 1,
+  -- conditions
+  description_contains  TEXT,               -- case-insensitive, description or counterparty
+  description_regex     TEXT,
+  amount_min            INTEGER,            -- cents, inclusive, signed
+  amount_max            INTEGER,
+  account_id            INTEGER NULL
+                          REFERENCES accounts(id) ON DELETE CASCADE,
+  -- actions
+  category_id           INTEGER NULL
+                          REFERENCES categories(id) ON DELETE SET NULL,
+  set_description       TEXT,
+  add_tag               TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_rules_position ON rules(position, id);
+
+-- Free-form labels on transactions (many-to-many).
+CREATE TABLE IF NOT EXISTS tags (
+  id    INTEGER PRIMARY KEY AUTOINCREMENT,
+  name  TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+CREATE TABLE IF NOT EXISTS transaction_tags (
+  transaction_id  INTEGER NOT NULL
+                    REFERENCES transactions(id) ON DELETE CASCADE,
+  tag_id          INTEGER NOT NULL
+                    REFERENCES tags(id) ON DELETE CASCADE,
+  PRIMARY KEY (transaction_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS idx_transaction_tags_tag ON transaction_tags(tag_id);
@@ -0,0 +1,35 @@
+-- Recurring transactions (rent, salary, subscriptions).
+-- `rrule` is a subset of RFC 5545: FREQ, INTERVAL, COUNT, UNTIL, BYMONTHDAY, BYDAY.
+-- Occurrences up to `handled_through` were posted, queued or skipped when the database was opened.
+
+CREATE TABLE IF NOT EXISTS recurring_transactions (
+  id               INTEGER PRIMARY KEY AUTOINCREMENT,
+  account_id       INTEGER NOT NULL
+                     REFERENCES accounts(id),
+  category_id      INTEGER NULL
+                     REFERENCES categories(id) ON DELETE SET NULL,
+  description      TEXT,
+  amount           INTEGER NOT NULL,     -- cents, signed like transactions
+  rrule            TEXT NOT NULL,        -- e.g. FREQ=MONTHLY;BYMONTHDAY=1
+  start_date       TEXT NOT NULL,        -- YYYY-MM-DD, first possible occurrence
+  auto_post        INTEGER NOT NULL DEFAULT 1, -- 0 = queue due occurrences for confirmation
+  handled_through  TEXT                  -- YYYY-MM-DD
+);
+
+-- Single occurrences that differ from the schedule, identified by their scheduled date.
+--   planned: edited, not due yet    pending: due, waiting for confirmation
+--   posted:  booked as transaction  skipped: left out
+CREATE TABLE IF NOT EXISTS recurring_occurrences (
+  recurring_id    INTEGER NOT NULL
+                    REFERENCES recurring_transactions(id) ON DELETE CASCADE,
+  date            TEXT NOT NULL,
+  status          TEXT NOT NULL DEFAULT 'planned',
+  post_date       TEXT,                  -- overrides (NULL = as scheduled)
+  amount          INTEGER,
+  description     TEXT,
+  category_id     INTEGER NULL
+                    REFERENCES categories(id) ON DELETE SET NULL,
+  transaction_id  INTEGER NULL
+                    REFERENCES transactions(id) ON DELETE SET NULL,
+  PRIMARY KEY (recurring_id, date)
+);
@@ -0,0 +1,14 @@
+-- Spending limit per category and period ('monthly' | 'quarterly' | 'yearly').
+-- With rollover, money left over at the end of a period (counted from start_date)
+-- adds to the next period's budget.
+
+CREATE TABLE IF NOT EXISTS budgets (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  category_id  INTEGER NOT NULL UNIQUE
+                 REFERENCES categories(id) ON DELETE CASCADE,
+  period       TEXT NOT NULL DEFAULT 'monthly',
+  amount       INTEGER NOT NULL,            -- cents per period, positive
+  currency     TEXT NOT NULL DEFAULT 'EUR',
+  rollover     INTEGER NOT NULL DEFAULT 0,
+  start_date   TEXT NOT NULL                -- YYYY-MM-DD
+);
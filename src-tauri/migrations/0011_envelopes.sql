@@ -0,0 +1,36 @@
+-- Small key/value store for app-wide options (e.g. budget_mode).
+CREATE TABLE IF NOT EXISTS settings (
+  key    TEXT PRIMARY KEY,
+  value  TEXT NOT NULL
+);
+
+-- Envelope (zero-based) budgeting: income is assigned to envelopes month by month,
+-- spending in an envelope's categories draws it down. Balances carry over, negative ones too.
+CREATE TABLE IF NOT EXISTS envelopes (
+  id        INTEGER PRIMARY KEY AUTOINCREMENT,
+  name      TEXT NOT NULL UNIQUE COLLATE NOCASE,
+  position  INTEGER NOT NULL DEFAULT 0
+);
+
+ALTER TABLE categories ADD COLUMN envelope_id INTEGER NULL
+  REFERENCES envelopes(id) ON DELETE SET NULL;
+
+CREATE TABLE IF NOT EXISTS envelope_assignments (
+  envelope_id  INTEGER NOT NULL
+                 REFERENCES envelopes(id) ON DELETE CASCADE,
+  month        TEXT NOT NULL,              -- YYYY-MM
+  amount       INTEGER NOT NULL,           -- cents
+  PRIMARY KEY (envelope_id, month)
+);
+
+CREATE TABLE IF NOT EXISTS envelope_moves (
+  id                INTEGER PRIMARY KEY AUTOINCREMENT,
+  month             TEXT NOT NULL,         -- YYYY-MM
+  from_envelope_id  INTEGER NOT NULL
+                      REFERENCES envelopes(id) ON DELETE CASCADE,
+  to_envelope_id    INTEGER NOT NULL
+                      REFERENCES envelopes(id) ON DELETE CASCADE,
+  amount            INTEGER NOT NULL,      -- cents, positive
+  created_at        TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_envelope_moves_month ON envelope_moves(month);
@@ -0,0 +1,5 @@
+-- Parent/child categories ("Food > Groceries"). Names stay unique across the whole tree,
+-- so transactions, rules and budgets keep pointing at the same row when it moves.
+ALTER TABLE categories ADD COLUMN parent_id INTEGER NULL
+  REFERENCES categories(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_categories_parent ON categories(parent_id);
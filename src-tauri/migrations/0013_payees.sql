@@ -0,0 +1,20 @@
+-- Payees: one row per merchant / counterparty, however the bank spells it.
+CREATE TABLE IF NOT EXISTS payees (
+  id    INTEGER PRIMARY KEY AUTOINCREMENT,
+  name  TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+-- Raw spellings that map to a payee ("REWE SAGT DANKE", "REWE Markt GmbH").
+-- Matched as whole words against the counterparty, then the description;
+-- the longest matching alias wins. A payee's name counts as an alias too.
+CREATE TABLE IF NOT EXISTS payee_aliases (
+  id        INTEGER PRIMARY KEY AUTOINCREMENT,
+  payee_id  INTEGER NOT NULL
+              REFERENCES payees(id) ON DELETE CASCADE,
+  alias     TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+CREATE INDEX IF NOT EXISTS idx_payee_aliases_payee ON payee_aliases(payee_id);
+
+ALTER TABLE transactions ADD COLUMN payee_id INTEGER NULL
+  REFERENCES payees(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_transactions_payee ON transactions(payee_id);
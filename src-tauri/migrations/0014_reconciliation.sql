@@ -0,0 +1,18 @@
+-- Reconciliation against bank statements.
+--   uncleared: entered, not yet seen on a statement
+--   cleared:   seen on a statement
+--   reconciled: part of a finished reconciliation; locked against edits
+ALTER TABLE transactions ADD COLUMN status TEXT NOT NULL DEFAULT 'uncleared';
+ALTER TABLE transactions ADD COLUMN reconciliation_id INTEGER NULL
+  REFERENCES reconciliations(id) ON DELETE SET NULL;
+
+CREATE TABLE IF NOT EXISTS reconciliations (
+  id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+  account_id         INTEGER NOT NULL
+                       REFERENCES accounts(id) ON DELETE CASCADE,
+  statement_date     TEXT NOT NULL,      -- YYYY-MM-DD
+  statement_balance  INTEGER NOT NULL,   -- cents
+  created_at         TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_reconciliations_account ON reconciliations(account_id, statement_date);
+CREATE INDEX IF NOT EXISTS idx_transactions_reconciliation ON transactions(reconciliation_id);
@@ -0,0 +1,14 @@
+-- Receipts and other documents kept with a transaction.
+-- The file content lives in the database, so it is encrypted with the same SQLCipher key.
+CREATE TABLE IF NOT EXISTS attachments (
+  id              INTEGER PRIMARY KEY AUTOINCREMENT,
+  transaction_id  INTEGER NOT NULL
+                    REFERENCES transactions(id) ON DELETE CASCADE,
+  filename        TEXT NOT NULL,
+  mime_type       TEXT NOT NULL,
+  size            INTEGER NOT NULL,   -- bytes
+  sha256          TEXT NOT NULL,      -- hex, of the content
+  data            BLOB NOT NULL,
+  created_at      TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_attachments_transaction ON attachments(transaction_id);
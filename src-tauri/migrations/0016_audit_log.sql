@@ -0,0 +1,14 @@
+-- Every change made through the transaction, account and category commands.
+-- `before` / `after` are the full row as JSON (NULL for inserts / deletes);
+-- transactions also carry their tags and split lines. Amounts are in cents.
+CREATE TABLE IF NOT EXISTS audit_log (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  at         TEXT NOT NULL DEFAULT (datetime('now')),   -- UTC
+  entity     TEXT NOT NULL,      -- 'transaction' | 'account' | 'category'
+  entity_id  INTEGER NOT NULL,
+  action     TEXT NOT NULL,      -- 'insert' | 'update' | 'delete'
+  before     TEXT,
+  after      TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log(entity, entity_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_at ON audit_log(at);
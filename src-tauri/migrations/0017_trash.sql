@@ -0,0 +1,14 @@
+-- Soft delete: deleted transactions, accounts and categories stay in the trash
+-- (deleted_at set, UTC) until they are restored or purged.
+-- The audit log records moving a row in and out of the trash as 'trash' / 'restore';
+-- purging it is a 'delete'.
+ALTER TABLE transactions ADD COLUMN deleted_at TEXT NULL;
+ALTER TABLE accounts ADD COLUMN deleted_at TEXT NULL;
+ALTER TABLE categories ADD COLUMN deleted_at TEXT NULL;
+CREATE INDEX IF NOT EXISTS idx_transactions_deleted_at ON transactions(deleted_at);
+
+-- A trashed entry doesn't block re-importing its statement line.
+DROP INDEX IF EXISTS idx_transactions_bank_ref;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_bank_ref
+  ON transactions(account_id, bank_ref)
+  WHERE bank_ref IS NOT NULL AND deleted_at IS NULL;
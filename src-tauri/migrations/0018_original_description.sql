@@ -0,0 +1,5 @@
+-- The description a transaction was first booked with (the bank's text for imports).
+-- Fingerprints are built from it, so renaming a transaction by hand or by a rule
+-- doesn't make the next import of the same statement line look new.
+ALTER TABLE transactions ADD COLUMN original_description TEXT;
+UPDATE transactions SET original_description = description;
@@ -12,6 +12,132 @@ use std::io::Read;
 use std::path::Path;
 use tauri::{Manager, State, AppHandle};
 
+/* ---------- Money (exact minor units) ---------- */
+// Amounts are kept as integer cents everywhere (DB column, sums, exports).
+// Over the wire they stay plain decimal numbers (12.34) so the frontend is unaffected;
+// the f64 only exists at the JSON / XLSX boundary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+struct Money(i64);
+
+impl Money {
+    const ZERO: Money = Money(0);
+
+    fn cents(self) -> i64 {
+        self.0
+    }
+    fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+    fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+    fn abs(self) -> Money {
+        Money(self.0.abs())
+    }
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    // Exact decimal parse ("-1234.5", "12.345" rounds half away from zero).
+    // Only '.' is accepted as decimal separator; callers normalize locale formats first.
+    fn parse(s: &str) -> Option<Money> {
+        let s = s.trim();
+        let (neg, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int_part, frac_part) = match digits.split_once('.') {
+            Some((i, f)) => (i, f),
+            None => (digits, ""),
+        };
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        if !int_part.chars().all(|c| c.is_ascii_digit())
+            || !frac_part.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let whole: i64 = if int_part.is_empty() { 0 } else { int_part.parse().ok()? };
+        let mut frac = frac_part.bytes().map(|b| (b - b'0') as i64);
+        let d1 = frac.next().unwrap_or(0);
+        let d2 = frac.next().unwrap_or(0);
+        let round_up = frac.next().unwrap_or(0) >= 5;
+        let mut cents = whole.checked_mul(100)?.checked_add(d1 * 10 + d2)?;
+        if round_up {
+            cents = cents.checked_add(1)?;
+        }
+        Some(Money(if neg { -cents } else { cents }))
+    }
+
+    // f64 -> cents via its shortest decimal representation, so 0.1 + 0.2 style noise is dropped.
+    fn from_f64(v: f64) -> Option<Money> {
+        if !v.is_finite() {
+            return None;
+        }
+        Money::parse(&format!("{}", v))
+    }
+}
+
+impl std::ops::Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+impl std::ops::AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+impl std::ops::Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+impl std::ops::SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+impl std::ops::Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |a, b| a + b)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Money, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            I(i64),
+            F(f64),
+            S(String),
+        }
+        let m = match Raw::deserialize(d)? {
+            Raw::I(v) => v.checked_mul(100).map(Money),
+            Raw::F(v) => Money::from_f64(v),
+            Raw::S(s) => Money::parse(&s),
+        };
+        m.ok_or_else(|| serde::de::Error::custom("invalid amount"))
+    }
+}
+
 /* ---------- Accounts & Transactions ---------- */
 #[derive(Debug, Serialize, sqlx::FromRow)]
 struct AccountOut {
@@ -21,7 +147,11 @@ struct AccountOut {
     #[sqlx(rename = "account_type")]
     #[serde(rename = "type")]
     r#type: String, // "standard" | "reimbursable"
-    balance: f64,
+    currency: String,
+    balance: Money,
+    // balance converted into the requested base currency (None = no rate known)
+    #[sqlx(skip)]
+    balance_base: Option<Money>,
 }
 
 #[derive(Debug, Serialize, sqlx::FromRow, Clone)]
@@ -30,10 +160,26 @@ struct TransactionOut {
     account_id: i64,
     account_name: String,
     account_color: Option<String>,
+    currency: String,
     date: String,
     category: Option<String>,
     description: Option<String>,
-    amount: f64,
+    amount: Money,
+    transfer_id: Option<i64>, // both legs of a transfer share this id
+    #[sqlx(default)]
+    counterparty: Option<String>, // from statement imports
+    #[sqlx(default)]
+    counterparty_iban: Option<String>,
+    #[sqlx(default)]
+    payee_id: Option<i64>,
+    #[sqlx(default)]
+    payee: Option<String>,
+    #[sqlx(default)]
+    status: String, // "uncleared" | "cleared" | "reconciled"
+    #[sqlx(skip)]
+    splits: Vec<SplitOut>, // empty unless the amount is split across categories
+    #[sqlx(skip)]
+    tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,8 +187,39 @@ struct NewTransaction {
     account_id: i64,
     date: String, // YYYY-MM-DD
     description: Option<String>,
-    amount: f64,
+    amount: Money,
+    category: Option<String>,
+    splits: Option<Vec<SplitInput>>, // must add up to `amount`; replaces `category`
+    tags: Option<Vec<String>>,       // added to the tags from rules
+    payee: Option<String>,           // by name, created if new (default: matched by alias)
+}
+
+/* ---------- Split lines ---------- */
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SplitInput {
+    category: Option<String>,
+    amount: Money,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, Clone)]
+struct SplitOut {
+    id: i64,
+    transaction_id: i64,
     category: Option<String>,
+    amount: Money,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewTransfer {
+    from_account_id: i64,
+    to_account_id: i64,
+    date: String,             // YYYY-MM-DD
+    amount: Money,            // positive; leaves `from`, arrives in `to`
+    to_amount: Option<Money>, // received amount when the accounts differ in currency
+    description: Option<String>,
+    category: Option<String>, // defaults to "Transfer" (display only)
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,22 +228,31 @@ struct UpdateTransaction {
     account_id: Option<i64>,
     date: Option<String>,
     description: Option<String>,
-    amount: Option<f64>,
+    amount: Option<Money>,
     category: Option<String>,
+    splits: Option<Vec<SplitInput>>, // Some([]) turns a split transaction back into a plain one
+    tags: Option<Vec<String>>,       // replaces the tags; Some([]) removes all
+    payee: Option<String>,           // by name, created if new; Some("") removes the payee
 }
 
 /* ---------- Search / Export DTOs ---------- */
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 struct TxSearch {
     query: Option<String>,
     account_id: Option<i64>,
     date_from: Option<String>, // inclusive, YYYY-MM-DD
     date_to: Option<String>,   // inclusive, YYYY-MM-DD
-    tx_type: Option<String>,   // "all" | "income" | "expense"
+    tx_type: Option<String>,   // "all" | "income" | "expense" | "transfer"
     limit: Option<i64>,
-    offset: Option<i64>,      // if < 0 => compute last page on server
-    sort_by: Option<String>,  // "date"|"category"|"description"|"amount"|"account"|"id"
-    sort_dir: Option<String>, // "asc"|"desc"
+    offset: Option<i64>,           // if < 0 => compute last page on server
+    sort_by: Option<String>,       // "date"|"category"|"description"|"amount"|"account"|"payee"|"id"
+    sort_dir: Option<String>,      // "asc"|"desc"
+    base_currency: Option<String>, // totals are converted into this (default EUR)
+    tags: Option<Vec<String>>,     // tag names, case-insensitive
+    tag_match: Option<String>,     // "any" (default) | "all"
+    category_id: Option<i64>,      // includes subcategories
+    payee_id: Option<i64>,
+    status: Option<String>,        // "uncleared" | "cleared" | "reconciled"
 }
 
 #[derive(Debug, Serialize)]
@@ -74,14 +260,26 @@ struct TxSearchResult {
     items: Vec<TransactionOut>,
     total: i64,
     offset: i64,
-    sum_income: f64,
-    sum_expense: f64,
-    sum_income_std: f64,
-    sum_expense_std: f64,
-    sum_income_reimb: f64,
-    sum_expense_reimb: f64,
+    sum_income: Money,
+    sum_expense: Money,
+    sum_income_std: Money,
+    sum_expense_std: Money,
+    sum_income_reimb: Money,
+    sum_expense_reimb: Money,
     // NEW
-    sum_init: f64,
+    sum_init: Money,
+    // sums above are in `base_currency`; native per-currency sums below
+    base_currency: String,
+    by_currency: Vec<CurrencyTotals>,
+    missing_rates: Vec<String>, // currencies left out of the converted sums
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct CurrencyTotals {
+    currency: String,
+    sum_income: Money,
+    sum_expense: Money,
+    sum_init: Money,
 }
 
 /* ---------- Categories (DB-level unique) ---------- */
@@ -89,25 +287,322 @@ struct TxSearchResult {
 struct Category {
     id: i64,
     name: String,
+    parent_id: Option<i64>,
+    #[sqlx(skip)]
+    path: String, // "Food > Groceries"
+    #[sqlx(skip)]
+    depth: i64, // 0 = top level
 }
 
-async fn get_or_create_category_id(
-    pool: &SqlitePool,
+async fn get_or_create_category_id<'a, A: sqlx::Acquire<'a, Database = sqlx::Sqlite>>(
+    db: A,
     name_opt: Option<String>,
-) -> Result<Option<i64>, sqlx::Error> {
+) -> Result<Option<i64>, String> {
     let name = match name_opt.map(|s| s.trim().to_string()) {
         Some(s) if !s.is_empty() => s,
         _ => return Ok(None),
     };
-    sqlx::query("INSERT OR IGNORE INTO categories(name) VALUES (?)")
+    let mut conn = db.acquire().await.map_err(|e| e.to_string())?;
+    let created = sqlx::query("INSERT OR IGNORE INTO categories(name) VALUES (?)")
         .bind(&name)
-        .execute(pool)
-        .await?;
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected()
+        > 0;
     let rec = sqlx::query("SELECT id FROM categories WHERE name = ? COLLATE NOCASE")
         .bind(&name)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let id = rec.get::<i64, _>(0);
+    if created {
+        write_audit(&mut conn, "category", vec![(id, None)]).await?;
+        return Ok(Some(id));
+    }
+    // using the name of a trashed category brings it back
+    let before = audit_before(&mut conn, "category", &[id]).await?;
+    sqlx::query("UPDATE categories SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL")
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    write_audit(&mut conn, "category", before).await?;
+    Ok(Some(id))
+}
+
+/* ---------- Category tree ---------- */
+// Category ids in the subtree rooted at the bound id (UNION guards against cycles).
+const CATEGORY_SUBTREE: &str = "WITH RECURSIVE sub(id) AS (\
+       SELECT ? UNION SELECT c2.id FROM categories c2 JOIN sub ON c2.parent_id = sub.id) \
+     SELECT id FROM sub";
+
+// id -> (parent_id, name), loaded once per command
+struct CategoryTree(std::collections::HashMap<i64, (Option<i64>, String)>);
+
+impl CategoryTree {
+    async fn load(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let rows: Vec<(i64, Option<i64>, String)> =
+            sqlx::query_as("SELECT id, parent_id, name FROM categories")
+                .fetch_all(pool)
+                .await?;
+        Ok(CategoryTree(
+            rows.into_iter()
+                .map(|(id, parent, name)| (id, (parent, name)))
+                .collect(),
+        ))
+    }
+
+    // Root first, `id` last.
+    fn chain(&self, id: i64) -> Vec<i64> {
+        let mut out = vec![id];
+        let mut cur = id;
+        while let Some((Some(parent), _)) = self.0.get(&cur) {
+            if out.contains(parent) {
+                break;
+            }
+            out.push(*parent);
+            cur = *parent;
+        }
+        out.reverse();
+        out
+    }
+
+    fn path(&self, id: i64) -> String {
+        self.chain(id)
+            .iter()
+            .filter_map(|c| self.0.get(c).map(|(_, name)| name.as_str()))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+
+    // The ancestor at `level` (0 = top level), or `id` itself if it sits higher up.
+    fn at_level(&self, id: i64, level: usize) -> i64 {
+        self.chain(id).get(level).copied().unwrap_or(id)
+    }
+}
+
+/* ---------- Currencies & exchange rates ---------- */
+const DEFAULT_CURRENCY: &str = "EUR";
+
+fn normalize_currency(code: Option<&str>) -> Result<String, String> {
+    let c = code.map(|s| s.trim().to_ascii_uppercase()).unwrap_or_default();
+    if c.is_empty() {
+        return Ok(DEFAULT_CURRENCY.to_string());
+    }
+    if c.len() != 3 || !c.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return Err(format!("Invalid currency code: {c}"));
+    }
+    Ok(c)
+}
+
+// Display symbol for exports; falls back to the ISO code.
+fn currency_symbol(code: &str) -> &str {
+    match code {
+        "EUR" => "€",
+        "USD" => "$",
+        "GBP" => "£",
+        "JPY" => "¥",
+        other => other,
+    }
+}
+
+// Convert with a single rounding step (rates are inexact by nature, the cents are not).
+fn convert_money(m: Money, rate: f64) -> Money {
+    Money((m.cents() as f64 * rate).round() as i64)
+}
+
+/// Rate to turn 1 unit of `from` into `to`: latest rate on/before `as_of`
+/// (direct or inverse pair), falling back to the newest known rate.
+async fn find_rate(
+    pool: &SqlitePool,
+    from: &str,
+    to: &str,
+    as_of: Option<&str>,
+) -> Result<Option<f64>, sqlx::Error> {
+    if from == to {
+        return Ok(Some(1.0));
+    }
+    let sql = r#"
+        SELECT rate, date, 0 AS inv FROM exchange_rates
+         WHERE currency = ?1 AND base = ?2 AND (?3 IS NULL OR DATE(date) <= DATE(?3))
+        UNION ALL
+        SELECT 1.0 / rate, date, 1 AS inv FROM exchange_rates
+         WHERE currency = ?2 AND base = ?1 AND (?3 IS NULL OR DATE(date) <= DATE(?3))
+        ORDER BY date DESC, inv ASC
+        LIMIT 1
+    "#;
+    let row: Option<(f64, String, i64)> = sqlx::query_as(sql)
+        .bind(from)
+        .bind(to)
+        .bind(as_of)
+        .fetch_optional(pool)
+        .await?;
+    if row.is_some() || as_of.is_none() {
+        return Ok(row.map(|r| r.0));
+    }
+    let row: Option<(f64, String, i64)> = sqlx::query_as(sql)
+        .bind(from)
+        .bind(to)
+        .bind(None::<String>)
+        .fetch_optional(pool)
         .await?;
-    Ok(Some(rec.get::<i64, _>(0)))
+    Ok(row.map(|r| r.0))
+}
+
+/// Rates for several currencies into `base`, plus the (sorted) currencies that have none.
+async fn rates_into_base(
+    pool: &SqlitePool,
+    currencies: &[String],
+    base: &str,
+    as_of: Option<&str>,
+) -> Result<(std::collections::HashMap<String, f64>, Vec<String>), String> {
+    let mut out = std::collections::HashMap::new();
+    let mut missing = Vec::new();
+    for cur in currencies {
+        if out.contains_key(cur) || missing.contains(cur) {
+            continue;
+        }
+        match find_rate(pool, cur, base, as_of)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            Some(rate) => {
+                out.insert(cur.clone(), rate);
+            }
+            None => missing.push(cur.clone()),
+        }
+    }
+    missing.sort();
+    Ok((out, missing))
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct ExchangeRate {
+    id: i64,
+    date: String,
+    currency: String,
+    base: String,
+    rate: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewExchangeRate {
+    date: String, // YYYY-MM-DD
+    currency: String,
+    base: String,
+    rate: f64,
+}
+
+async fn upsert_rate<'e, E: sqlx::SqliteExecutor<'e>>(
+    ex: E,
+    input: &NewExchangeRate,
+) -> Result<(), String> {
+    let currency = normalize_currency(Some(&input.currency))?;
+    let base = normalize_currency(Some(&input.base))?;
+    if currency == base {
+        return Err("Currency and base currency must differ".into());
+    }
+    if !(input.rate.is_finite() && input.rate > 0.0) {
+        return Err(format!("Invalid rate: {}", input.rate));
+    }
+    chrono::NaiveDate::parse_from_str(input.date.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", input.date))?;
+
+    sqlx::query(
+        r#"
+    INSERT INTO exchange_rates (date, currency, base, rate)
+    VALUES (?1, ?2, ?3, ?4)
+    ON CONFLICT(date, currency, base) DO UPDATE SET rate = excluded.rate;
+    "#,
+    )
+    .bind(input.date.trim())
+    .bind(&currency)
+    .bind(&base)
+    .bind(input.rate)
+    .execute(ex)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_exchange_rates(
+    state: State<'_, AppState>,
+) -> Result<Vec<ExchangeRate>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, ExchangeRate>(
+        "SELECT id, date, currency, base, rate FROM exchange_rates ORDER BY date DESC, currency, base",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_exchange_rate(
+    state: State<'_, AppState>,
+    input: NewExchangeRate,
+) -> Result<(), String> {
+    let pool = current_pool(&state).await;
+    upsert_rate(&pool, &input).await
+}
+
+#[tauri::command]
+async fn delete_exchange_rate(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let res = sqlx::query("DELETE FROM exchange_rates WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Import rates from a CSV file with the columns `date,currency,base,rate`
+/// (`,` or `;` separated, optional header line). Returns the number of rates stored.
+#[tauri::command]
+async fn import_exchange_rates_csv(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<i64, String> {
+    let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let mut parsed = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let sep = if line.contains(';') { ';' } else { ',' };
+        let cells: Vec<&str> = line.split(sep).map(|c| c.trim().trim_matches('"')).collect();
+        if cells.len() < 4 {
+            return Err(format!("Line {}: expected date, currency, base, rate", idx + 1));
+        }
+        // skip a header line
+        if idx == 0 && cells[3].parse::<f64>().is_err() {
+            continue;
+        }
+        let rate = cells[3]
+            .parse::<f64>()
+            .map_err(|_| format!("Line {}: invalid rate '{}'", idx + 1, cells[3]))?;
+        parsed.push(NewExchangeRate {
+            date: cells[0].to_string(),
+            currency: cells[1].to_string(),
+            base: cells[2].to_string(),
+            rate,
+        });
+    }
+
+    // all or nothing
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (i, r) in parsed.iter().enumerate() {
+        upsert_rate(&mut *tx, r)
+            .await
+            .map_err(|e| format!("Rate #{}: {e}", i + 1))?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(parsed.len() as i64)
 }
 
 /* ---------- Finance commands ---------- */
@@ -116,487 +611,6862 @@ struct NewAccountInput {
     name: String,
     color: Option<String>,
     account_type: String, // "standard" | "reimbursable"
-    initial_balance: Option<f64>,
+    initial_balance: Option<Money>,
+    currency: Option<String>, // ISO 4217, default EUR
 }
 
 #[tauri::command]
 async fn add_account(state: State<'_, AppState>, input: NewAccountInput) -> Result<i64, String> {
     let pool = current_pool(&state).await;
+    let currency = normalize_currency(input.currency.as_deref())?;
+    let initial_balance = input.initial_balance.filter(|a| *a != Money::ZERO);
 
-    let rec = sqlx::query("INSERT INTO accounts (name, color, type) VALUES (?1, ?2, ?3);")
-        .bind(&input.name)
-        .bind(&input.color)
-        .bind(&input.account_type)
-        .execute(&pool)
-        .await
-        .map_err(|e| e.to_string())?;
-    let account_id = rec.last_insert_rowid();
+    // ensure "Init" category exists and get its id (before the write transaction starts)
+    let init_cat_id = match initial_balance {
+        Some(_) => get_or_create_category_id(&pool, Some("Init".to_string())).await?,
+        None => None,
+    };
 
-    if let Some(amount) = input.initial_balance {
-        if amount != 0.0 {
-            let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let rec = sqlx::query(
+        "INSERT INTO accounts (name, color, type, currency) VALUES (?1, ?2, ?3, ?4);",
+    )
+    .bind(&input.name)
+    .bind(&input.color)
+    .bind(&input.account_type)
+    .bind(&currency)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let account_id = rec.last_insert_rowid();
+    let mut changes = write_audit(&mut tx, "account", vec![(account_id, None)]).await?;
 
-            // ensure "Init" category exists and get its id
-            let cat_id = get_or_create_category_id(&pool, Some("Init".to_string()))
-                .await
-                .map_err(|e| e.to_string())?;
+    if let Some(amount) = initial_balance {
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
 
-            sqlx::query(
-                r#"
-          INSERT INTO transactions (account_id, date, description, amount, category_id)
-          VALUES (?1, ?2, ?3, ?4, ?5);
+        let rec = sqlx::query(
+            r#"
+          INSERT INTO transactions
+            (account_id, date, description, original_description, amount, category_id, fingerprint)
+          VALUES (?1, ?2, ?3, ?3, ?4, ?5, ?6);
         "#,
-            )
-            .bind(account_id)
-            .bind(date)
-            .bind("Initial balance")
-            .bind(amount)
-            .bind(cat_id) // <-- set category "Init"
-            .execute(&pool)
-            .await
-            .map_err(|e| e.to_string())?;
-        }
+        )
+        .bind(account_id)
+        .bind(&date)
+        .bind("Initial balance")
+        .bind(amount)
+        .bind(init_cat_id) // <-- set category "Init"
+        .bind(fingerprint(&date, amount, Some("Initial balance"), None))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        let init_id = rec.last_insert_rowid();
+        changes.extend(write_audit(&mut tx, "transaction", vec![(init_id, None)]).await?);
     }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Add account", changes).await;
     Ok(account_id)
 }
 
 #[tauri::command]
-async fn list_accounts(state: State<'_, AppState>) -> Result<Vec<AccountOut>, String> {
+async fn list_accounts(
+    state: State<'_, AppState>,
+    base_currency: Option<String>,
+) -> Result<Vec<AccountOut>, String> {
     let pool = current_pool(&state).await;
+    let base = normalize_currency(base_currency.as_deref())?;
 
-    sqlx::query_as::<_, AccountOut>(
+    let mut accounts = sqlx::query_as::<_, AccountOut>(
         r#"
     SELECT
       a.id,
       a.name,
       a.color,
       a.type AS account_type,
-      COALESCE(SUM(t.amount), 0.0) AS balance
+      a.currency,
+      COALESCE(SUM(t.amount), 0) AS balance
     FROM accounts a
-    LEFT JOIN transactions t ON t.account_id = a.id
-    GROUP BY a.id, a.name, a.color, a.type
+    LEFT JOIN transactions t ON t.account_id = a.id AND t.deleted_at IS NULL
+    WHERE a.deleted_at IS NULL
+    GROUP BY a.id, a.name, a.color, a.type, a.currency
     ORDER BY a.name COLLATE NOCASE ASC;
     "#,
     )
     .fetch_all(&pool)
     .await
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?;
+
+    // convert at the latest known rate; unknown pairs stay None
+    for acc in accounts.iter_mut() {
+        let rate = find_rate(&pool, &acc.currency, &base, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        acc.balance_base = rate.map(|r| convert_money(acc.balance, r));
+    }
+    Ok(accounts)
 }
 
-/* ---------- list_transactions (Home) ordered by newest first ---------- */
-#[tauri::command]
-async fn list_transactions(
-    state: State<'_, AppState>,
-    limit: Option<i64>,
-) -> Result<Vec<TransactionOut>, String> {
-    let lim = limit.unwrap_or(20);
-    let pool = current_pool(&state).await;
+/* ---------- Split helpers ---------- */
+type SplitLine = (Option<i64>, Money, Option<String>); // (category_id, amount, memo)
+
+// Validate split input and resolve category names (creating them like single categories do).
+async fn resolve_splits<'a, A: sqlx::Acquire<'a, Database = sqlx::Sqlite>>(
+    db: A,
+    splits: &[SplitInput],
+) -> Result<Vec<SplitLine>, String> {
+    let mut conn = db.acquire().await.map_err(|e| e.to_string())?;
+    let mut lines = Vec::with_capacity(splits.len());
+    for s in splits {
+        if s.amount == Money::ZERO {
+            return Err("Split amounts cannot be zero.".into());
+        }
+        let cat_id = get_or_create_category_id(&mut *conn, s.category.clone()).await?;
+        let memo = s
+            .memo
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string);
+        lines.push((cat_id, s.amount, memo));
+    }
+    Ok(lines)
+}
+
+async fn replace_splits(
+    conn: &mut sqlx::SqliteConnection,
+    transaction_id: i64,
+    lines: &[SplitLine],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_splits WHERE transaction_id = ?1")
+        .bind(transaction_id)
+        .execute(&mut *conn)
+        .await?;
+    for (pos, (cat_id, amount, memo)) in lines.iter().enumerate() {
+        sqlx::query(
+            r#"
+        INSERT INTO transaction_splits (transaction_id, category_id, amount, memo, position)
+        VALUES (?1, ?2, ?3, ?4, ?5);
+        "#,
+        )
+        .bind(transaction_id)
+        .bind(cat_id)
+        .bind(amount)
+        .bind(memo)
+        .bind(pos as i64)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
 
-    sqlx::query_as::<_, TransactionOut>(
+// A split transaction's lines must add up to its amount (checked after every write).
+async fn check_split_total(
+    conn: &mut sqlx::SqliteConnection,
+    transaction_id: i64,
+) -> Result<(), String> {
+    let row: Option<(Money, i64, Money)> = sqlx::query_as(
         r#"
-    SELECT
-      t.id,
-      t.account_id,
-      a.name  AS account_name,
-      a.color AS account_color,
-      t.date,
-      c.name AS category,
-      t.description,
-      t.amount
-    FROM transactions t
-    JOIN accounts a ON a.id = t.account_id
-    LEFT JOIN categories c ON c.id = t.category_id
-    ORDER BY DATE(t.date) DESC, t.id DESC
-    LIMIT ?1;
+    SELECT t.amount,
+           (SELECT COUNT(*) FROM transaction_splits s WHERE s.transaction_id = t.id),
+           (SELECT COALESCE(SUM(s.amount), 0) FROM transaction_splits s WHERE s.transaction_id = t.id)
+    FROM transactions t WHERE t.id = ?1;
     "#,
     )
-    .bind(lim)
-    .fetch_all(&pool)
+    .bind(transaction_id)
+    .fetch_optional(&mut *conn)
     .await
-    .map_err(|e| e.to_string())
+    .map_err(|e| e.to_string())?;
+
+    match row {
+        Some((amount, n, total)) if n > 0 && total != amount => Err(format!(
+            "Split lines add up to {:.2} but the transaction amount is {:.2}.",
+            total.to_f64(),
+            amount.to_f64()
+        )),
+        _ => Ok(()),
+    }
 }
 
-/* ---------- CRUD ---------- */
-#[tauri::command]
-async fn add_transaction(state: State<'_, AppState>, input: NewTransaction) -> Result<i64, String> {
-    let pool = current_pool(&state).await;
+// Fill `splits` for the given transactions with one extra query.
+async fn attach_splits(pool: &SqlitePool, items: &mut [TransactionOut]) -> Result<(), sqlx::Error> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    let placeholders = vec!["?"; items.len()].join(", ");
+    let sql = format!(
+        "SELECT s.id, s.transaction_id, c.name AS category, s.amount, s.memo \
+     FROM transaction_splits s \
+     LEFT JOIN categories c ON c.id = s.category_id \
+     WHERE s.transaction_id IN ({placeholders}) \
+     ORDER BY s.transaction_id, s.position, s.id"
+    );
+    let mut q = sqlx::query_as::<_, SplitOut>(&sql);
+    for it in items.iter() {
+        q = q.bind(it.id);
+    }
+    let splits = q.fetch_all(pool).await?;
+    for it in items.iter_mut() {
+        it.splits = splits
+            .iter()
+            .filter(|s| s.transaction_id == it.id)
+            .cloned()
+            .collect();
+    }
+    Ok(())
+}
 
-    let cat_id = get_or_create_category_id(&pool, input.category.clone())
-        .await
-        .map_err(|e| e.to_string())?;
+/* ---------- Duplicate detection ---------- */
+// Same account + same fingerprint = exact duplicate; same amount within
+// ±FUZZY_DUPLICATE_DAYS = worth a second look.
+const FUZZY_DUPLICATE_DAYS: i64 = 3;
+
+// "REWE  Markt-GmbH, Berlin" and "rewe markt gmbh berlin" compare equal.
+fn normalize_description(s: Option<&str>) -> String {
+    s.unwrap_or("")
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    let rec = sqlx::query(
-        r#"
-    INSERT INTO transactions (account_id, date, description, amount, category_id)
-    VALUES (?1, ?2, ?3, ?4, ?5);
-    "#,
-    )
-    .bind(input.account_id)
-    .bind(input.date)
-    .bind(input.description)
-    .bind(input.amount)
-    .bind(cat_id)
-    .execute(&pool)
-    .await
-    .map_err(|e| e.to_string())?;
-    Ok(rec.last_insert_rowid())
+fn fingerprint(date: &str, amount: Money, description: Option<&str>, external_id: Option<&str>) -> String {
+    use sha2::{Digest, Sha256};
+    let key = format!(
+        "{}|{}|{}|{}",
+        date.trim().get(..10).unwrap_or(date.trim()),
+        amount.cents(),
+        normalize_description(description),
+        external_id.map(str::trim).unwrap_or("")
+    );
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
 }
 
-#[tauri::command]
-async fn update_transaction(
-    state: State<'_, AppState>,
-    input: UpdateTransaction,
-) -> Result<bool, String> {
-    let pool = current_pool(&state).await;
+// (id, date, amount, description, bank_ref)
+type FingerprintSource = (i64, String, Money, Option<String>, Option<String>);
+
+async fn write_fingerprints(
+    conn: &mut sqlx::SqliteConnection,
+    rows: Vec<FingerprintSource>,
+) -> Result<(), sqlx::Error> {
+    for (id, date, amount, description, bank_ref) in rows {
+        sqlx::query("UPDATE transactions SET fingerprint = ?1 WHERE id = ?2")
+            .bind(fingerprint(&date, amount, description.as_deref(), bank_ref.as_deref()))
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
 
-    let mut sql = String::from("UPDATE transactions SET ");
-    let mut first = true;
-    let mut args = SqliteArguments::default();
+// After a date or amount edit; the text stays the one the row was first booked with.
+async fn refresh_fingerprint(
+    conn: &mut sqlx::SqliteConnection,
+    id: i64,
+) -> Result<(), sqlx::Error> {
+    let rows: Vec<FingerprintSource> = sqlx::query_as(
+        "SELECT id, date, amount, COALESCE(original_description, description), bank_ref \
+         FROM transactions WHERE id = ?1",
+    )
+    .bind(id)
+    .fetch_all(&mut *conn)
+    .await?;
+    write_fingerprints(conn, rows).await
+}
 
-    fn push_set(sql: &mut String, first: &mut bool, col: &str) {
-        if !*first {
-            sql.push_str(", ");
+// Fill in fingerprints for rows written before the column existed.
+async fn backfill_fingerprints(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let rows: Vec<FingerprintSource> = sqlx::query_as(
+        "SELECT id, date, amount, description, bank_ref FROM transactions WHERE fingerprint IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    write_fingerprints(&mut tx, rows).await?;
+    tx.commit().await
+}
+
+// Transactions in the account with the same amount within ±FUZZY_DUPLICATE_DAYS.
+async fn duplicate_candidates<'e, E: sqlx::SqliteExecutor<'e>>(
+    ex: E,
+    account_id: i64,
+    date: &str,
+    amount: Money,
+    exclude_id: Option<i64>,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+    SELECT id FROM transactions
+    WHERE account_id = ?1
+      AND deleted_at IS NULL
+      AND amount = ?2
+      AND ABS(julianday(DATE(date)) - julianday(DATE(?3))) <= ?4
+      AND id <> COALESCE(?5, -1)
+    ORDER BY ABS(julianday(DATE(date)) - julianday(DATE(?3))), id
+    "#,
+    )
+    .bind(account_id)
+    .bind(amount)
+    .bind(date)
+    .bind(FUZZY_DUPLICATE_DAYS)
+    .bind(exclude_id)
+    .fetch_all(ex)
+    .await
+}
+
+/* ---------- Tags ---------- */
+// Attach tags by name, creating them on first use.
+async fn add_tags(
+    conn: &mut sqlx::SqliteConnection,
+    transaction_id: i64,
+    names: &[String],
+) -> Result<(), sqlx::Error> {
+    for name in names {
+        sqlx::query("INSERT OR IGNORE INTO tags(name) VALUES (?1)")
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            r#"
+        INSERT OR IGNORE INTO transaction_tags (transaction_id, tag_id)
+        SELECT ?1, id FROM tags WHERE name = ?2 COLLATE NOCASE
+        "#,
+        )
+        .bind(transaction_id)
+        .bind(name)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+// Trimmed, non-empty, first spelling wins for case-insensitive repeats.
+fn clean_tag_names(names: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for n in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        if !out.iter().any(|o| o.eq_ignore_ascii_case(n)) {
+            out.push(n.to_string());
         }
-        *first = false;
-        sql.push_str(col);
-        sql.push_str(" = ?");
     }
+    out
+}
 
-    if let Some(v) = input.account_id {
-        push_set(&mut sql, &mut first, "account_id");
-        args.add(v);
+// Fill `tags` for the given transactions with one extra query.
+async fn attach_tags(pool: &SqlitePool, items: &mut [TransactionOut]) -> Result<(), sqlx::Error> {
+    let mut ids: Vec<i64> = items.iter().map(|it| it.id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(());
     }
-    if let Some(v) = input.date {
-        push_set(&mut sql, &mut first, "date");
-        args.add(v);
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let sql = format!(
+        "SELECT tt.transaction_id, g.name \
+     FROM transaction_tags tt \
+     JOIN tags g ON g.id = tt.tag_id \
+     WHERE tt.transaction_id IN ({placeholders}) \
+     ORDER BY g.name COLLATE NOCASE"
+    );
+    let mut q = sqlx::query_as::<_, (i64, String)>(&sql);
+    for id in &ids {
+        q = q.bind(*id);
     }
-    if let Some(v) = input.description {
-        push_set(&mut sql, &mut first, "description");
-        args.add(v);
+    let tags = q.fetch_all(pool).await?;
+    for it in items.iter_mut() {
+        it.tags = tags
+            .iter()
+            .filter(|(tx_id, _)| *tx_id == it.id)
+            .map(|(_, name)| name.clone())
+            .collect();
     }
-    if let Some(v) = input.amount {
-        push_set(&mut sql, &mut first, "amount");
-        args.add(v);
+    Ok(())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Tag {
+    id: i64,
+    name: String,
+    usage: i64, // number of tagged transactions
+}
+
+#[tauri::command]
+async fn list_tags(state: State<'_, AppState>) -> Result<Vec<Tag>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, Tag>(
+        r#"
+    SELECT g.id, g.name, COUNT(tt.transaction_id) AS usage
+    FROM tags g
+    LEFT JOIN transaction_tags tt ON tt.tag_id = g.id
+    GROUP BY g.id
+    ORDER BY g.name COLLATE NOCASE
+    "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Returns the id of the tag, creating it if needed.
+#[tauri::command]
+async fn add_tag(state: State<'_, AppState>, name: String) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".into());
     }
+    sqlx::query("INSERT OR IGNORE INTO tags(name) VALUES (?1)")
+        .bind(name)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query_scalar("SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE")
+        .bind(name)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    if input.category.is_some() {
-        let cat_id = get_or_create_category_id(&pool, input.category.clone())
+// Rules that add the tag follow the rename.
+#[tauri::command]
+async fn rename_tag(state: State<'_, AppState>, id: i64, name: String) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".into());
+    }
+    let taken: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE AND id <> ?2")
+            .bind(name)
+            .bind(id)
+            .fetch_optional(&pool)
             .await
             .map_err(|e| e.to_string())?;
-        match cat_id {
-            Some(id) => {
-                push_set(&mut sql, &mut first, "category_id");
-                args.add(id);
-            }
-            None => {
-                if !first {
-                    sql.push_str(", ");
-                }
-                first = false;
-                sql.push_str("category_id = NULL");
-            }
-        }
+    if taken.is_some() {
+        return Err(format!("A tag named '{name}' already exists."));
     }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query(
+        "UPDATE rules SET add_tag = ?1 \
+         WHERE add_tag = (SELECT name FROM tags WHERE id = ?2) COLLATE NOCASE",
+    )
+    .bind(name)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let res = sqlx::query("UPDATE tags SET name = ?1 WHERE id = ?2")
+        .bind(name)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+// Removes the tag from all transactions (rules that add it will create it again).
+#[tauri::command]
+async fn delete_tag(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let ids: Vec<i64> =
+        sqlx::query_scalar("SELECT transaction_id FROM transaction_tags WHERE tag_id = ?1")
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &ids).await?;
+    let before_tag = audit_before(&mut tx, "tag", &[id]).await?;
+    sqlx::query("DELETE FROM transaction_tags WHERE tag_id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let res = sqlx::query("DELETE FROM tags WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut changes = write_audit(&mut tx, "transaction", before).await?;
+    changes.extend(write_audit(&mut tx, "tag", before_tag).await?);
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Delete tag", changes).await;
+    Ok(res.rows_affected() > 0)
+}
 
-    if first {
+// Replaces the tags of one transaction.
+async fn replace_tags(
+    conn: &mut sqlx::SqliteConnection,
+    transaction_id: i64,
+    names: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_tags WHERE transaction_id = ?1")
+        .bind(transaction_id)
+        .execute(&mut *conn)
+        .await?;
+    add_tags(conn, transaction_id, &clean_tag_names(names)).await
+}
+
+#[tauri::command]
+async fn set_transaction_tags(
+    state: State<'_, AppState>,
+    transaction_id: i64,
+    tags: Vec<String>,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM transactions WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(transaction_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    if exists.is_none() {
         return Ok(false);
     }
+    let before = audit_before(&mut tx, "transaction", &[transaction_id]).await?;
+    replace_tags(&mut tx, transaction_id, &tags)
+        .await
+        .map_err(|e| e.to_string())?;
+    let changes = write_audit(&mut tx, "transaction", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Edit tags", changes).await;
+    Ok(true)
+}
 
-    sql.push_str(" WHERE id = ?");
-    args.add(input.id);
+/* ---------- Payees ---------- */
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Payee {
+    id: i64,
+    name: String,
+    transactions: i64,
+    #[sqlx(skip)]
+    aliases: Vec<String>,
+}
 
-    let res = sqlx::query_with(&sql, args)
-        .execute(&pool)
+#[derive(Debug, Deserialize)]
+struct PayeeInput {
+    name: String,
+    aliases: Option<Vec<String>>, // replaces the aliases on update
+}
+
+// (payee_id, payee name, normalized alias), longest alias first
+struct PayeeMatcher(Vec<(i64, String, String)>);
+
+impl PayeeMatcher {
+    async fn load<'e, E: sqlx::SqliteExecutor<'e>>(ex: E) -> Result<Self, sqlx::Error> {
+        let rows: Vec<(i64, String, String)> = sqlx::query_as(
+            r#"
+        SELECT p.id, p.name, p.name FROM payees p
+        UNION ALL
+        SELECT p.id, p.name, pa.alias FROM payee_aliases pa JOIN payees p ON p.id = pa.payee_id
+        "#,
+        )
+        .fetch_all(ex)
+        .await?;
+        let mut aliases: Vec<(i64, String, String)> = rows
+            .into_iter()
+            .map(|(id, name, alias)| (id, name, normalize_description(Some(&alias))))
+            .filter(|(_, _, alias)| !alias.is_empty())
+            .collect();
+        aliases.sort_by_key(|(_, _, alias)| std::cmp::Reverse(alias.len()));
+        Ok(PayeeMatcher(aliases))
+    }
+
+    // The counterparty is checked first; it is usually the cleaner text.
+    fn find(&self, counterparty: Option<&str>, description: Option<&str>) -> Option<(i64, &str)> {
+        [counterparty, description]
+            .into_iter()
+            .flatten()
+            .find_map(|text| {
+                let hay = format!(" {} ", normalize_description(Some(text)));
+                self.0
+                    .iter()
+                    .find(|(_, _, alias)| hay.contains(&format!(" {alias} ")))
+                    .map(|(id, name, _)| (*id, name.as_str()))
+            })
+    }
+}
+
+async fn get_or_create_payee_id(
+    conn: &mut sqlx::SqliteConnection,
+    name: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO payees(name) VALUES (?1)")
+        .bind(name)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query_scalar("SELECT id FROM payees WHERE name = ?1 COLLATE NOCASE")
+        .bind(name)
+        .fetch_one(&mut *conn)
         .await
-        .map_err(|e| e.to_string())?;
+}
 
-    Ok(res.rows_affected() > 0)
+// (id, payee_id, counterparty, description)
+type PayeeSource = (i64, Option<i64>, Option<String>, Option<String>);
+
+// Links transactions without a payee (all of them with `overwrite`) to the
+// payee their counterparty / description matches. Transfers are left alone.
+// Returns the audited changes; the caller records them for undo.
+async fn match_payees(pool: &SqlitePool, overwrite: bool) -> Result<Vec<AuditChange>, String> {
+    let matcher = PayeeMatcher::load(pool).await.map_err(|e| e.to_string())?;
+    let rows: Vec<PayeeSource> = sqlx::query_as(
+        "SELECT id, payee_id, counterparty, description FROM transactions \
+         WHERE transfer_id IS NULL AND deleted_at IS NULL AND (?1 OR payee_id IS NULL)",
+    )
+    .bind(overwrite)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let updates: Vec<(i64, i64)> = rows
+        .into_iter()
+        .filter_map(|(id, current, counterparty, description)| {
+            let (found, _) = matcher.find(counterparty.as_deref(), description.as_deref())?;
+            (Some(found) != current).then_some((id, found))
+        })
+        .collect();
+    if updates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let ids: Vec<i64> = updates.iter().map(|(id, _)| *id).collect();
+    let before = audit_before(&mut tx, "transaction", &ids).await?;
+    for (id, payee_id) in updates {
+        sqlx::query("UPDATE transactions SET payee_id = ?1 WHERE id = ?2")
+            .bind(payee_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let changes = write_audit(&mut tx, "transaction", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(changes)
 }
 
 #[tauri::command]
-async fn delete_transaction(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+async fn list_payees(state: State<'_, AppState>) -> Result<Vec<Payee>, String> {
     let pool = current_pool(&state).await;
+    let mut items = sqlx::query_as::<_, Payee>(
+        r#"
+    SELECT p.id, p.name,
+           (SELECT COUNT(*) FROM transactions t WHERE t.payee_id = p.id AND t.deleted_at IS NULL)
+             AS transactions
+    FROM payees p
+    ORDER BY p.name COLLATE NOCASE
+    "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let aliases: Vec<(i64, String)> =
+        sqlx::query_as("SELECT payee_id, alias FROM payee_aliases ORDER BY alias COLLATE NOCASE")
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    for it in items.iter_mut() {
+        it.aliases = aliases
+            .iter()
+            .filter(|(pid, _)| *pid == it.id)
+            .map(|(_, a)| a.clone())
+            .collect();
+    }
+    Ok(items)
+}
 
-    let res = sqlx::query("DELETE FROM transactions WHERE id = ?1")
+async fn save_payee(
+    pool: &SqlitePool,
+    id: Option<i64>,
+    input: &PayeeInput,
+) -> Result<Option<i64>, String> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err("Payee name cannot be empty".into());
+    }
+    let taken: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM payees WHERE name = ?1 COLLATE NOCASE")
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    if taken.is_some() && taken != id {
+        return Err(format!("A payee named '{name}' already exists."));
+    }
+    let aliases = clean_tag_names(input.aliases.as_deref().unwrap_or_default());
+    for alias in &aliases {
+        let owner: Option<String> = sqlx::query_scalar(
+            "SELECT p.name FROM payee_aliases pa JOIN payees p ON p.id = pa.payee_id \
+             WHERE pa.alias = ?1 AND (?2 IS NULL OR p.id <> ?2)",
+        )
+        .bind(alias)
         .bind(id)
-        .execute(&pool)
+        .fetch_optional(pool)
         .await
         .map_err(|e| e.to_string())?;
-    Ok(res.rows_affected() > 0)
+        if let Some(owner) = owner {
+            return Err(format!("'{alias}' is already an alias of {owner}."));
+        }
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let id = match id {
+        Some(id) => {
+            let res = sqlx::query("UPDATE payees SET name = ?1 WHERE id = ?2")
+                .bind(name)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            if res.rows_affected() == 0 {
+                return Ok(None);
+            }
+            if input.aliases.is_some() {
+                sqlx::query("DELETE FROM payee_aliases WHERE payee_id = ?1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            id
+        }
+        None => sqlx::query("INSERT INTO payees (name) VALUES (?1)")
+            .bind(name)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .last_insert_rowid(),
+    };
+    for alias in &aliases {
+        sqlx::query("INSERT INTO payee_aliases (payee_id, alias) VALUES (?1, ?2)")
+            .bind(id)
+            .bind(alias)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(Some(id))
 }
 
+// Unassigned transactions that match the new payee are linked right away.
 #[tauri::command]
-async fn delete_account(state: tauri::State<'_, AppState>, id: i64) -> Result<bool, String> {
+async fn add_payee(state: State<'_, AppState>, input: PayeeInput) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let id = save_payee(&pool, None, &input)
+        .await?
+        .ok_or("Payee could not be created")?;
+    let changes = match_payees(&pool, false).await?;
+    record_undo(&state, "Assign payees", changes).await;
+    Ok(id)
+}
+
+#[tauri::command]
+async fn update_payee(
+    state: State<'_, AppState>,
+    id: i64,
+    input: PayeeInput,
+) -> Result<bool, String> {
     let pool = current_pool(&state).await;
+    let updated = save_payee(&pool, Some(id), &input).await?.is_some();
+    if updated {
+        let changes = match_payees(&pool, false).await?;
+        record_undo(&state, "Assign payees", changes).await;
+    }
+    Ok(updated)
+}
 
-    // refuse if any transactions reference this account
-    let cnt: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE account_id = ?1")
+// Transactions keep their description; only the link is removed.
+#[tauri::command]
+async fn delete_payee(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM transactions WHERE payee_id = ?1")
         .bind(id)
-        .fetch_one(&pool)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &ids).await?;
+    let before_payee = audit_before(&mut tx, "payee", &[id]).await?;
+    for sql in [
+        "UPDATE transactions SET payee_id = NULL WHERE payee_id = ?1",
+        "DELETE FROM payee_aliases WHERE payee_id = ?1",
+    ] {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let res = sqlx::query("DELETE FROM payees WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
+    let mut changes = write_audit(&mut tx, "transaction", before).await?;
+    changes.extend(write_audit(&mut tx, "payee", before_payee).await?);
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Delete payee", changes).await;
+    Ok(res.rows_affected() > 0)
+}
 
-    if cnt > 0 {
-        return Err(format!(
-            "This account has {} transaction(s). Move or delete them first.",
-            cnt
-        ));
+// Re-runs alias matching over existing transactions; returns how many changed.
+#[tauri::command]
+async fn assign_payees(state: State<'_, AppState>, overwrite: Option<bool>) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let changes = match_payees(&pool, overwrite.unwrap_or(false)).await?;
+    let changed = changes.len() as i64;
+    record_undo(&state, "Assign payees", changes).await;
+    Ok(changed)
+}
+
+#[derive(Debug, Serialize)]
+struct PayeeSummary {
+    payee_id: Option<i64>, // None = transactions without a payee
+    payee: Option<String>,
+    transactions: i64,
+    spent: Money,    // expenses, negative
+    received: Money, // income, positive
+    last_date: String,
+}
+
+// (currency, payee_id, payee, count, spent, received, last date)
+type PayeeSummaryRow = (
+    String,
+    Option<i64>,
+    Option<String>,
+    i64,
+    Money,
+    Money,
+    String,
+);
+
+// Spending per payee for the filtered transactions, in `base_currency`
+// (transfers and opening balances left out), biggest spending first.
+#[tauri::command]
+async fn payee_summary(
+    state: State<'_, AppState>,
+    filters: TxSearch,
+) -> Result<Vec<PayeeSummary>, String> {
+    let mut where_sql = String::new();
+    let mut args: Vec<BindArg> = Vec::new();
+    build_where(&filters, &mut where_sql, &mut args, false);
+    where_sql.push_str(" AND t.transfer_id IS NULL AND COALESCE(LOWER(c.name), '') <> 'init' ");
+
+    let mut sql = String::from(
+        "SELECT a.currency, t.payee_id, p.name, COUNT(*), \
+            COALESCE(SUM(CASE WHEN t.amount < 0 THEN t.amount END), 0), \
+            COALESCE(SUM(CASE WHEN t.amount > 0 THEN t.amount END), 0), \
+            MAX(t.date) \
+     FROM transactions t \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = t.category_id \
+     LEFT JOIN payees p ON p.id = t.payee_id",
+    );
+    sql.push_str(&where_sql);
+    sql.push_str(" GROUP BY a.currency, t.payee_id ");
+
+    let mut q = sqlx::query_as::<_, PayeeSummaryRow>(&sql);
+    for a in &args {
+        match a {
+            BindArg::I(v) => {
+                q = q.bind(*v);
+            }
+            BindArg::S(s) => {
+                q = q.bind(s);
+            }
+        }
+    }
+    let pool = current_pool(&state).await;
+    let rows = q.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+
+    let base = normalize_currency(filters.base_currency.as_deref())?;
+    let mut rates: std::collections::HashMap<String, Option<f64>> = Default::default();
+    for (cur, ..) in &rows {
+        if let std::collections::hash_map::Entry::Vacant(slot) = rates.entry(cur.clone()) {
+            let rate = find_rate(&pool, slot.key(), &base, filters.date_to.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            slot.insert(rate);
+        }
+    }
+
+    // currencies without a known rate are left out, like in the search totals
+    let mut out: Vec<PayeeSummary> = Vec::new();
+    for (cur, payee_id, payee, count, spent, received, last_date) in rows {
+        let Some(rate) = rates.get(&cur).copied().flatten() else {
+            continue;
+        };
+        let (spent, received) = (convert_money(spent, rate), convert_money(received, rate));
+        match out.iter_mut().find(|s| s.payee_id == payee_id) {
+            Some(s) => {
+                s.transactions += count;
+                s.spent += spent;
+                s.received += received;
+                if last_date > s.last_date {
+                    s.last_date = last_date;
+                }
+            }
+            None => out.push(PayeeSummary {
+                payee_id,
+                payee,
+                transactions: count,
+                spent,
+                received,
+                last_date,
+            }),
+        }
+    }
+    out.sort_by_key(|s| (s.spent, std::cmp::Reverse(s.received)));
+    Ok(out)
+}
+
+/* ---------- Rules (auto-categorization) ---------- */
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Rule {
+    id: i64,
+    name: String,
+    position: i64,
+    enabled: bool,
+    // conditions (None = any)
+    description_contains: Option<String>, // case-insensitive, also checks the counterparty
+    description_regex: Option<String>,
+    amount_min: Option<Money>, // signed, inclusive
+    amount_max: Option<Money>,
+    account_id: Option<i64>,
+    // actions
+    category: Option<String>,
+    set_description: Option<String>,
+    add_tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleInput {
+    name: String,
+    position: Option<i64>, // default: after the last rule (kept on update)
+    enabled: Option<bool>, // default true
+    description_contains: Option<String>,
+    description_regex: Option<String>,
+    amount_min: Option<Money>,
+    amount_max: Option<Money>,
+    account_id: Option<i64>,
+    category: Option<String>,
+    set_description: Option<String>,
+    add_tag: Option<String>,
+}
+
+// What the matching rules would do to one transaction.
+#[derive(Debug, Default)]
+struct RuleOutcome {
+    rule_ids: Vec<i64>,
+    category: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+}
+
+struct CompiledRule {
+    rule: Rule,
+    regex: Option<regex::Regex>,
+}
+
+impl CompiledRule {
+    fn matches(
+        &self,
+        account_id: i64,
+        amount: Money,
+        description: Option<&str>,
+        counterparty: Option<&str>,
+    ) -> bool {
+        let r = &self.rule;
+        if r.account_id.is_some_and(|a| a != account_id)
+            || r.amount_min.is_some_and(|m| amount < m)
+            || r.amount_max.is_some_and(|m| amount > m)
+        {
+            return false;
+        }
+        if let Some(needle) = &r.description_contains {
+            let needle = needle.to_lowercase();
+            let hit = [description, counterparty]
+                .iter()
+                .flatten()
+                .any(|t| t.to_lowercase().contains(&needle));
+            if !hit {
+                return false;
+            }
+        }
+        match &self.regex {
+            Some(re) => description.is_some_and(|d| re.is_match(d)),
+            None => true,
+        }
+    }
+}
+
+fn trimmed(s: Option<String>) -> Option<String> {
+    s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+const RULE_COLUMNS: &str = r#"
+    r.id, r.name, r.position, r.enabled, r.description_contains, r.description_regex,
+    r.amount_min, r.amount_max, r.account_id, c.name AS category, r.set_description, r.add_tag
+    FROM rules r
+    LEFT JOIN categories c ON c.id = r.category_id
+"#;
+
+// Enabled rules in evaluation order.
+async fn load_rules<'e, E: sqlx::SqliteExecutor<'e>>(ex: E) -> Result<Vec<CompiledRule>, String> {
+    let rules = sqlx::query_as::<_, Rule>(&format!(
+        "SELECT {RULE_COLUMNS} WHERE r.enabled = 1 ORDER BY r.position, r.id"
+    ))
+    .fetch_all(ex)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    rules
+        .into_iter()
+        .map(|rule| {
+            let regex = match rule.description_regex.as_deref() {
+                Some(p) => Some(
+                    regex::Regex::new(p).map_err(|e| format!("Rule '{}': {e}", rule.name))?,
+                ),
+                None => None,
+            };
+            Ok(CompiledRule { rule, regex })
+        })
+        .collect()
+}
+
+fn run_rules(
+    rules: &[CompiledRule],
+    account_id: i64,
+    amount: Money,
+    description: Option<&str>,
+    counterparty: Option<&str>,
+) -> RuleOutcome {
+    let mut out = RuleOutcome::default();
+    for cr in rules {
+        if !cr.matches(account_id, amount, description, counterparty) {
+            continue;
+        }
+        let r = &cr.rule;
+        out.rule_ids.push(r.id);
+        if out.category.is_none() {
+            out.category = r.category.clone();
+        }
+        if out.description.is_none() {
+            out.description = r.set_description.clone();
+        }
+        if let Some(tag) = &r.add_tag {
+            if !out.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                out.tags.push(tag.clone());
+            }
+        }
+    }
+    out
+}
+
+// Validate a rule and return its trimmed fields; the category is resolved by the caller.
+fn clean_rule_input(mut input: RuleInput) -> Result<RuleInput, String> {
+    input.name = input.name.trim().to_string();
+    if input.name.is_empty() {
+        return Err("Rule name cannot be empty".into());
+    }
+    input.description_contains = trimmed(input.description_contains);
+    input.description_regex = trimmed(input.description_regex);
+    input.category = trimmed(input.category);
+    input.set_description = trimmed(input.set_description);
+    input.add_tag = trimmed(input.add_tag);
+
+    if let Some(p) = &input.description_regex {
+        regex::Regex::new(p).map_err(|e| format!("Invalid regular expression: {e}"))?;
+    }
+    if let (Some(min), Some(max)) = (input.amount_min, input.amount_max) {
+        if min > max {
+            return Err("Minimum amount is larger than the maximum.".into());
+        }
+    }
+    if input.category.is_none() && input.set_description.is_none() && input.add_tag.is_none() {
+        return Err("A rule needs at least one action (category, description or tag).".into());
+    }
+    Ok(input)
+}
+
+#[tauri::command]
+async fn list_rules(state: State<'_, AppState>) -> Result<Vec<Rule>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, Rule>(&format!("SELECT {RULE_COLUMNS} ORDER BY r.position, r.id"))
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn add_rule(state: State<'_, AppState>, input: RuleInput) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let input = clean_rule_input(input)?;
+    let cat_id = get_or_create_category_id(&pool, input.category.clone()).await?;
+
+    let rec = sqlx::query(
+        r#"
+    INSERT INTO rules
+      (name, position, enabled, description_contains, description_regex,
+       amount_min, amount_max, account_id, category_id, set_description, add_tag)
+    VALUES (?1, COALESCE(?2, (SELECT COALESCE(MAX(position), 0) + 1 FROM rules)),
+            ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11);
+    "#,
+    )
+    .bind(&input.name)
+    .bind(input.position)
+    .bind(input.enabled.unwrap_or(true))
+    .bind(&input.description_contains)
+    .bind(&input.description_regex)
+    .bind(input.amount_min)
+    .bind(input.amount_max)
+    .bind(input.account_id)
+    .bind(cat_id)
+    .bind(&input.set_description)
+    .bind(&input.add_tag)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(rec.last_insert_rowid())
+}
+
+#[tauri::command]
+async fn update_rule(
+    state: State<'_, AppState>,
+    id: i64,
+    input: RuleInput,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let input = clean_rule_input(input)?;
+    let cat_id = get_or_create_category_id(&pool, input.category.clone()).await?;
+
+    let res = sqlx::query(
+        r#"
+    UPDATE rules SET
+      name = ?1, position = COALESCE(?2, position), enabled = ?3,
+      description_contains = ?4, description_regex = ?5, amount_min = ?6, amount_max = ?7,
+      account_id = ?8, category_id = ?9, set_description = ?10, add_tag = ?11
+    WHERE id = ?12
+    "#,
+    )
+    .bind(&input.name)
+    .bind(input.position)
+    .bind(input.enabled.unwrap_or(true))
+    .bind(&input.description_contains)
+    .bind(&input.description_regex)
+    .bind(input.amount_min)
+    .bind(input.amount_max)
+    .bind(input.account_id)
+    .bind(cat_id)
+    .bind(&input.set_description)
+    .bind(&input.add_tag)
+    .bind(id)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+#[tauri::command]
+async fn delete_rule(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let res = sqlx::query("DELETE FROM rules WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+#[derive(Debug, Deserialize)]
+struct ApplyRules {
+    account_id: Option<i64>,
+    date_from: Option<String>, // inclusive, YYYY-MM-DD
+    date_to: Option<String>,   // inclusive, YYYY-MM-DD
+    overwrite: Option<bool>,   // also re-categorize transactions that have a category (default false)
+    dry_run: Option<bool>,     // default true: only preview
+}
+
+#[derive(Debug, Serialize)]
+struct RuleChange {
+    transaction_id: i64,
+    date: String,
+    rule_ids: Vec<i64>,
+    description: Option<String>,
+    new_description: Option<String>, // None = unchanged
+    category: Option<String>,
+    new_category: Option<String>, // None = unchanged
+    new_tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApplyRulesResult {
+    changes: Vec<RuleChange>,
+    applied: i64, // 0 for a dry run
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RuleCandidate {
+    id: i64,
+    account_id: i64,
+    date: String,
+    amount: Money,
+    description: Option<String>,
+    counterparty: Option<String>,
+    category: Option<String>,
+    is_split: bool,
+}
+
+#[tauri::command]
+async fn apply_rules(
+    state: State<'_, AppState>,
+    input: ApplyRules,
+) -> Result<ApplyRulesResult, String> {
+    let pool = current_pool(&state).await;
+    let rules = load_rules(&pool).await?;
+    let overwrite = input.overwrite.unwrap_or(false);
+
+    // transfers and opening balances are bookkeeping, not spending
+    let candidates = sqlx::query_as::<_, RuleCandidate>(
+        r#"
+    SELECT t.id, t.account_id, t.date, t.amount, t.description, t.counterparty,
+           c.name AS category,
+           EXISTS (SELECT 1 FROM transaction_splits s WHERE s.transaction_id = t.id) AS is_split
+    FROM transactions t
+    LEFT JOIN categories c ON c.id = t.category_id
+    WHERE t.transfer_id IS NULL
+      AND t.deleted_at IS NULL
+      AND COALESCE(LOWER(c.name), '') <> 'init'
+      AND (?1 IS NULL OR t.account_id = ?1)
+      AND (?2 IS NULL OR DATE(t.date) >= DATE(?2))
+      AND (?3 IS NULL OR DATE(t.date) <= DATE(?3))
+    ORDER BY t.date, t.id
+    "#,
+    )
+    .bind(input.account_id)
+    .bind(trimmed(input.date_from))
+    .bind(trimmed(input.date_to))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let existing_tags: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT tt.transaction_id, tg.name FROM transaction_tags tt JOIN tags tg ON tg.id = tt.tag_id",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut changes = Vec::new();
+    for t in candidates {
+        let out = run_rules(
+            &rules,
+            t.account_id,
+            t.amount,
+            t.description.as_deref(),
+            t.counterparty.as_deref(),
+        );
+        if out.rule_ids.is_empty() {
+            continue;
+        }
+        let new_category = out.category.filter(|new| {
+            !t.is_split
+                && (t.category.is_none() || overwrite)
+                && !t.category.as_ref().is_some_and(|cur| cur.eq_ignore_ascii_case(new))
+        });
+        let new_description = out.description.filter(|d| t.description.as_ref() != Some(d));
+        let new_tags: Vec<String> = out
+            .tags
+            .into_iter()
+            .filter(|tag| {
+                !existing_tags
+                    .iter()
+                    .any(|(id, name)| *id == t.id && name.eq_ignore_ascii_case(tag))
+            })
+            .collect();
+        if new_category.is_none() && new_description.is_none() && new_tags.is_empty() {
+            continue;
+        }
+        changes.push(RuleChange {
+            transaction_id: t.id,
+            date: t.date,
+            rule_ids: out.rule_ids,
+            description: t.description,
+            new_description,
+            category: t.category,
+            new_category,
+            new_tags,
+        });
+    }
+
+    let mut applied = 0;
+    if !input.dry_run.unwrap_or(true) && !changes.is_empty() {
+        let mut cat_ids = Vec::with_capacity(changes.len());
+        for c in &changes {
+            cat_ids.push(get_or_create_category_id(&pool, c.new_category.clone()).await?);
+        }
+
+        // the fingerprint keeps the original bank text, so re-imports are still recognized
+        let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+        let ids: Vec<i64> = changes.iter().map(|c| c.transaction_id).collect();
+        let before = audit_before(&mut tx, "transaction", &ids).await?;
+        for (c, cat_id) in changes.iter().zip(cat_ids) {
+            sqlx::query(
+                r#"
+            UPDATE transactions
+            SET category_id = COALESCE(?1, category_id),
+                description = COALESCE(?2, description)
+            WHERE id = ?3
+            "#,
+            )
+            .bind(cat_id)
+            .bind(&c.new_description)
+            .bind(c.transaction_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            add_tags(&mut tx, c.transaction_id, &c.new_tags)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let audited = write_audit(&mut tx, "transaction", before).await?;
+        tx.commit().await.map_err(|e| e.to_string())?;
+        record_undo(&state, "Apply rules", audited).await;
+        applied = changes.len() as i64;
+    }
+
+    Ok(ApplyRulesResult { changes, applied })
+}
+
+/* ---------- list_transactions (Home) ordered by newest first ---------- */
+#[tauri::command]
+async fn list_transactions(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<TransactionOut>, String> {
+    let lim = limit.unwrap_or(20);
+    let pool = current_pool(&state).await;
+
+    let mut items = sqlx::query_as::<_, TransactionOut>(
+        r#"
+    SELECT
+      t.id,
+      t.account_id,
+      a.name  AS account_name,
+      a.color AS account_color,
+      a.currency,
+      t.date,
+      c.name AS category,
+      t.description,
+      t.amount,
+      t.transfer_id,
+      t.counterparty,
+      t.counterparty_iban,
+      t.payee_id,
+      p.name AS payee,
+      t.status
+    FROM transactions t
+    JOIN accounts a ON a.id = t.account_id
+    LEFT JOIN categories c ON c.id = t.category_id
+    LEFT JOIN payees p ON p.id = t.payee_id
+    WHERE t.deleted_at IS NULL
+    ORDER BY DATE(t.date) DESC, t.id DESC
+    LIMIT ?1;
+    "#,
+    )
+    .bind(lim)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    attach_splits(&pool, &mut items)
+        .await
+        .map_err(|e| e.to_string())?;
+    attach_tags(&pool, &mut items)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(items)
+}
+
+/* ---------- CRUD ---------- */
+#[derive(Debug, Serialize)]
+struct AddedTransaction {
+    id: i64,
+    // existing entries that look like the same booking (the new one is saved anyway)
+    possible_duplicates: Vec<TransactionOut>,
+}
+
+#[tauri::command]
+async fn add_transaction(
+    state: State<'_, AppState>,
+    input: NewTransaction,
+) -> Result<AddedTransaction, String> {
+    let pool = current_pool(&state).await;
+    check_account_exists(&pool, input.account_id).await?;
+
+    let rules = load_rules(&pool).await?;
+    let ruled = run_rules(&rules, input.account_id, input.amount, input.description.as_deref(), None);
+
+    let has_splits = input.splits.as_ref().is_some_and(|s| !s.is_empty());
+    if has_splits && trimmed(input.category.clone()).is_some() {
+        return Err("A split transaction takes its categories from the split lines.".into());
+    }
+    let splits = resolve_splits(&pool, input.splits.as_deref().unwrap_or_default()).await?;
+    // a split transaction carries its categories on the lines only;
+    // rules only fill in a category the user left empty
+    let cat_id = if splits.is_empty() {
+        let category = trimmed(input.category.clone()).or(ruled.category);
+        get_or_create_category_id(&pool, category).await?
+    } else {
+        None
+    };
+    // likewise a rule's description only replaces an empty one
+    let description = match trimmed(input.description.clone()) {
+        Some(_) => input.description.clone(),
+        None => ruled.description,
+    };
+    // matched against the text as entered, before rules rewrite it
+    let matched_payee = match trimmed(input.payee.clone()) {
+        Some(_) => None,
+        None => PayeeMatcher::load(&pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .find(None, input.description.as_deref())
+            .map(|(id, _)| id),
+    };
+
+    let fp = fingerprint(&input.date, input.amount, input.description.as_deref(), None);
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let payee_id = match trimmed(input.payee.clone()) {
+        Some(name) => Some(
+            get_or_create_payee_id(&mut tx, &name)
+                .await
+                .map_err(|e| e.to_string())?,
+        ),
+        None => matched_payee,
+    };
+    let rec = sqlx::query(
+        r#"
+    INSERT INTO transactions
+      (account_id, date, description, amount, category_id, fingerprint, payee_id,
+       original_description)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);
+    "#,
+    )
+    .bind(input.account_id)
+    .bind(&input.date)
+    .bind(&description)
+    .bind(input.amount)
+    .bind(cat_id)
+    .bind(fp)
+    .bind(payee_id)
+    .bind(&input.description)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let id = rec.last_insert_rowid();
+    let mut tags = ruled.tags;
+    tags.extend(input.tags.clone().unwrap_or_default());
+    add_tags(&mut tx, id, &clean_tag_names(&tags))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !splits.is_empty() {
+        replace_splits(&mut tx, id, &splits)
+            .await
+            .map_err(|e| e.to_string())?;
+        check_split_total(&mut tx, id).await?;
+    }
+    let changes = write_audit(&mut tx, "transaction", vec![(id, None)]).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Add transaction", changes).await;
+
+    let dup_ids = duplicate_candidates(&pool, input.account_id, &input.date, input.amount, Some(id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut possible_duplicates = Vec::with_capacity(dup_ids.len());
+    for dup_id in dup_ids {
+        let row = sqlx::query_as::<_, TransactionOut>(
+            r#"
+        SELECT t.id, t.account_id, a.name AS account_name, a.color AS account_color,
+               a.currency, t.date, c.name AS category, t.description, t.amount, t.transfer_id,
+               t.counterparty, t.counterparty_iban, t.payee_id, p.name AS payee, t.status
+        FROM transactions t
+        JOIN accounts a ON a.id = t.account_id
+        LEFT JOIN categories c ON c.id = t.category_id
+        LEFT JOIN payees p ON p.id = t.payee_id
+        WHERE t.id = ?1
+        "#,
+        )
+        .bind(dup_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        possible_duplicates.push(row);
+    }
+
+    Ok(AddedTransaction {
+        id,
+        possible_duplicates,
+    })
+}
+
+#[tauri::command]
+async fn update_transaction(
+    state: State<'_, AppState>,
+    input: UpdateTransaction,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+
+    let mut sql = String::from("UPDATE transactions SET ");
+    let mut first = true;
+    let mut args = SqliteArguments::default();
+    let mut new_cat: Option<Option<i64>> = None;
+
+    fn push_set(sql: &mut String, first: &mut bool, col: &str) {
+        if !*first {
+            sql.push_str(", ");
+        }
+        *first = false;
+        sql.push_str(col);
+        sql.push_str(" = ?");
+    }
+
+    if let Some(v) = input.account_id {
+        push_set(&mut sql, &mut first, "account_id");
+        args.add(v);
+    }
+    if let Some(v) = &input.date {
+        push_set(&mut sql, &mut first, "date");
+        args.add(v.clone());
+    }
+    if let Some(v) = &input.description {
+        push_set(&mut sql, &mut first, "description");
+        args.add(v.clone());
+    }
+    if let Some(v) = input.amount {
+        push_set(&mut sql, &mut first, "amount");
+        args.add(v);
+    }
+
+    let splits = match &input.splits {
+        Some(s) => Some(resolve_splits(&pool, s).await?),
+        None => None,
+    };
+    let has_splits = splits.as_ref().is_some_and(|s| !s.is_empty());
+
+    if has_splits {
+        if input.category.is_some() {
+            return Err("A split transaction takes its categories from the split lines.".into());
+        }
+        if !first {
+            sql.push_str(", ");
+        }
+        first = false;
+        sql.push_str("category_id = NULL");
+    } else if input.category.is_some() {
+        let cat_id = get_or_create_category_id(&pool, input.category.clone()).await?;
+        match cat_id {
+            Some(id) => {
+                push_set(&mut sql, &mut first, "category_id");
+                args.add(id);
+            }
+            None => {
+                if !first {
+                    sql.push_str(", ");
+                }
+                first = false;
+                sql.push_str("category_id = NULL");
+            }
+        }
+        new_cat = Some(cat_id);
+    }
+
+    if first && splits.is_none() && input.tags.is_none() && input.payee.is_none() {
+        return Ok(false);
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    // trashed transactions can't be edited until they are restored
+    let found: Option<Option<i64>> = sqlx::query_scalar(
+        "SELECT transfer_id FROM transactions WHERE id = ?1 AND deleted_at IS NULL",
+    )
+    .bind(input.id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(transfer_id) = found else {
+        return Ok(false);
+    };
+    if let Some(account_id) = input.account_id {
+        let account: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM accounts WHERE id = ?1 AND deleted_at IS NULL")
+                .bind(account_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        if account.is_none() {
+            return Err(format!("Account #{account_id} does not exist."));
+        }
+    }
+    if has_splits && transfer_id.is_some() {
+        return Err("Transfers cannot be split.".into());
+    }
+    if input.account_id.is_some() || input.date.is_some() || input.amount.is_some() {
+        check_not_reconciled(&mut tx, input.id).await?;
+    }
+    let legs: Vec<i64> = sqlx::query_scalar(
+        "SELECT id FROM transactions WHERE id = ?1 OR (?2 IS NOT NULL AND transfer_id = ?2)",
+    )
+    .bind(input.id)
+    .bind(transfer_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &legs).await?;
+
+    let mut changed = false;
+    if !first {
+        sql.push_str(" WHERE id = ?");
+        args.add(input.id);
+        let res = sqlx::query_with(&sql, args)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        changed = res.rows_affected() > 0;
+    }
+
+    if let Some(lines) = &splits {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM transactions WHERE id = ?1")
+            .bind(input.id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists.is_some() {
+            replace_splits(&mut tx, input.id, lines)
+                .await
+                .map_err(|e| e.to_string())?;
+            changed = true;
+        }
+    }
+    check_split_total(&mut tx, input.id).await?;
+
+    if let Some(tags) = &input.tags {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM transactions WHERE id = ?1")
+            .bind(input.id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists.is_some() {
+            replace_tags(&mut tx, input.id, tags)
+                .await
+                .map_err(|e| e.to_string())?;
+            changed = true;
+        }
+    }
+
+    if input.payee.is_some() {
+        let payee_id = match trimmed(input.payee.clone()) {
+            Some(name) => Some(
+                get_or_create_payee_id(&mut tx, &name)
+                    .await
+                    .map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
+        let res = sqlx::query("UPDATE transactions SET payee_id = ?1 WHERE id = ?2")
+            .bind(payee_id)
+            .bind(input.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        changed |= res.rows_affected() > 0;
+    }
+
+    // keep the other leg of a transfer in sync
+
+    if let Some(tid) = transfer_id {
+        // amount is mirrored only between accounts of the same currency
+        sqlx::query(
+            r#"
+        UPDATE transactions
+        SET
+          date        = COALESCE(?1, date),
+          description = COALESCE(?2, description),
+          category_id = CASE WHEN ?3 THEN ?4 ELSE category_id END,
+          amount      = CASE
+                          WHEN ?5 IS NOT NULL
+                           AND (SELECT currency FROM accounts WHERE id = transactions.account_id)
+                             = (SELECT a.currency FROM transactions s
+                                JOIN accounts a ON a.id = s.account_id WHERE s.id = ?6)
+                          THEN -?5
+                          ELSE amount
+                        END
+        WHERE transfer_id = ?7 AND id <> ?6;
+        "#,
+        )
+        .bind(&input.date)
+        .bind(&input.description)
+        .bind(new_cat.is_some())
+        .bind(new_cat.flatten())
+        .bind(input.amount)
+        .bind(input.id)
+        .bind(tid)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let accounts: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT account_id) FROM transactions WHERE transfer_id = ?1",
+        )
+        .bind(tid)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        if accounts < 2 {
+            return Err("Both legs of a transfer cannot use the same account.".into());
+        }
+    }
+
+    if input.date.is_some() || input.amount.is_some() {
+        for leg in legs {
+            refresh_fingerprint(&mut tx, leg)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    let changes = write_audit(&mut tx, "transaction", before).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Edit transaction", changes).await;
+    Ok(changed)
+}
+
+#[tauri::command]
+async fn delete_transaction(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    check_not_reconciled(&mut tx, id).await?;
+
+    let transfer_id: Option<i64> =
+        sqlx::query_scalar("SELECT transfer_id FROM transactions WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .flatten();
+    let legs: Vec<i64> = sqlx::query_scalar(
+        "SELECT id FROM transactions WHERE id = ?1 OR (?2 IS NOT NULL AND transfer_id = ?2)",
+    )
+    .bind(id)
+    .bind(transfer_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &legs).await?;
+
+    // into the trash (tags, splits and attachments stay with it);
+    // a transfer leg takes its counterpart with it
+    let res = sqlx::query(
+        "UPDATE transactions SET deleted_at = datetime('now') \
+         WHERE deleted_at IS NULL AND (id = ?1 OR (?2 IS NOT NULL AND transfer_id = ?2))",
+    )
+    .bind(id)
+    .bind(transfer_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let changes = write_audit(&mut tx, "transaction", before).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Delete transaction", changes).await;
+    Ok(res.rows_affected() > 0)
+}
+
+/* ---------- Audit log ---------- */
+// (entity id, row before the change; None = didn't exist yet)
+type AuditSnapshot = (i64, Option<serde_json::Value>);
+
+fn audit_table(entity: &str) -> Result<&'static str, String> {
+    match entity {
+        "transaction" => Ok("transactions"),
+        "account" => Ok("accounts"),
+        "category" => Ok("categories"),
+        "payee" => Ok("payees"),
+        "tag" => Ok("tags"),
+        "reconciliation" => Ok("reconciliations"),
+        other => Err(format!("Unknown audit entity '{other}'")),
+    }
+}
+
+// The row as JSON, column by column, so new columns are picked up without changes here.
+// Transactions carry their tags and split lines along, which are edited with them,
+// payees their aliases.
+async fn audit_row(
+    conn: &mut sqlx::SqliteConnection,
+    entity: &str,
+    id: i64,
+) -> Result<Option<serde_json::Value>, String> {
+    use serde_json::Value;
+    use sqlx::{Column, TypeInfo, ValueRef};
+    let table = audit_table(entity)?;
+    let row = sqlx::query(&format!("SELECT * FROM {table} WHERE id = ?1"))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let mut obj = serde_json::Map::new();
+    for col in row.columns() {
+        let i = col.ordinal();
+        let raw = row.try_get_raw(i).map_err(|e| e.to_string())?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => Value::from(row.try_get::<i64, _>(i).map_err(|e| e.to_string())?),
+                "REAL" => Value::from(row.try_get::<f64, _>(i).map_err(|e| e.to_string())?),
+                "TEXT" => Value::from(row.try_get::<String, _>(i).map_err(|e| e.to_string())?),
+                _ => continue, // no blobs in the audited tables
+            }
+        };
+        obj.insert(col.name().to_string(), value);
+    }
+
+    if entity == "transaction" {
+        let tags: Vec<String> = sqlx::query_scalar(
+            "SELECT tg.name FROM transaction_tags tt JOIN tags tg ON tg.id = tt.tag_id \
+             WHERE tt.transaction_id = ?1 ORDER BY tg.name COLLATE NOCASE",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        let splits: Vec<(Option<i64>, i64, Option<String>)> = sqlx::query_as(
+            "SELECT category_id, amount, memo FROM transaction_splits \
+             WHERE transaction_id = ?1 ORDER BY position, id",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        obj.insert("tags".into(), Value::from(tags));
+        obj.insert(
+            "splits".into(),
+            splits
+                .into_iter()
+                .map(|(category_id, amount, memo)| {
+                    serde_json::json!({ "category_id": category_id, "amount": amount, "memo": memo })
+                })
+                .collect(),
+        );
+    }
+    if entity == "payee" {
+        let aliases: Vec<String> = sqlx::query_scalar(
+            "SELECT alias FROM payee_aliases WHERE payee_id = ?1 ORDER BY alias COLLATE NOCASE",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        obj.insert("aliases".into(), Value::from(aliases));
+    }
+    Ok(Some(Value::Object(obj)))
+}
+
+// Snapshots of the rows a command is about to change; hand them to `write_audit` afterwards.
+async fn audit_before(
+    conn: &mut sqlx::SqliteConnection,
+    entity: &str,
+    ids: &[i64],
+) -> Result<Vec<AuditSnapshot>, String> {
+    let mut out = Vec::with_capacity(ids.len());
+    for &id in ids {
+        out.push((id, audit_row(conn, entity, id).await?));
+    }
+    Ok(out)
+}
+
+// One row's change, as written to the audit log (and kept for undo).
+#[derive(Debug, Clone)]
+struct AuditChange {
+    entity: String,
+    id: i64,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+}
+
+// One entry per row that actually changed; the action follows from which side is missing.
+async fn write_audit(
+    conn: &mut sqlx::SqliteConnection,
+    entity: &str,
+    before: Vec<AuditSnapshot>,
+) -> Result<Vec<AuditChange>, String> {
+    let trashed = |row: &serde_json::Value| row.get("deleted_at").is_some_and(|d| !d.is_null());
+    let mut changes = Vec::new();
+    for (id, old) in before {
+        let new = audit_row(conn, entity, id).await?;
+        let action = match (&old, &new) {
+            (None, None) => continue,
+            (None, Some(_)) => "insert",
+            (Some(_), None) => "delete",
+            (Some(a), Some(b)) if a == b => continue,
+            (Some(a), Some(b)) if !trashed(a) && trashed(b) => "trash",
+            (Some(a), Some(b)) if trashed(a) && !trashed(b) => "restore",
+            (Some(_), Some(_)) => "update",
+        };
+        sqlx::query(
+            "INSERT INTO audit_log (entity, entity_id, action, before, after) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(entity)
+        .bind(id)
+        .bind(action)
+        .bind(old.as_ref().map(|v| v.to_string()))
+        .bind(new.as_ref().map(|v| v.to_string()))
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        changes.push(AuditChange {
+            entity: entity.to_string(),
+            id,
+            before: old,
+            after: new,
+        });
+    }
+    Ok(changes)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AuditFilter {
+    // "transaction" | "account" | "category" | "payee" | "tag" | "reconciliation"
+    entity: Option<String>,
+    entity_id: Option<i64>,
+    date_from: Option<String>, // YYYY-MM-DD, inclusive (UTC)
+    date_to: Option<String>,
+    limit: Option<i64>, // default 200
+    offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditEntry {
+    id: i64,
+    at: String,
+    entity: String,
+    entity_id: i64,
+    action: String,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+}
+
+// (id, at, entity, entity_id, action, before, after)
+type AuditLogRow = (
+    i64,
+    String,
+    String,
+    i64,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
+// Newest first.
+#[tauri::command]
+async fn list_audit_log(
+    state: State<'_, AppState>,
+    filters: Option<AuditFilter>,
+) -> Result<Vec<AuditEntry>, String> {
+    let f = filters.unwrap_or_default();
+    let pool = current_pool(&state).await;
+
+    let mut where_sql = String::from(" WHERE 1=1 ");
+    let mut args: Vec<BindArg> = Vec::new();
+    if let Some(entity) = trimmed(f.entity) {
+        where_sql.push_str(" AND entity = ? ");
+        args.push(BindArg::S(entity.to_ascii_lowercase()));
+    }
+    if let Some(id) = f.entity_id {
+        where_sql.push_str(" AND entity_id = ? ");
+        args.push(BindArg::I(id));
+    }
+    if let Some(d) = trimmed(f.date_from) {
+        parse_iso_date(&d)?;
+        where_sql.push_str(" AND DATE(at) >= DATE(?) ");
+        args.push(BindArg::S(d));
+    }
+    if let Some(d) = trimmed(f.date_to) {
+        parse_iso_date(&d)?;
+        where_sql.push_str(" AND DATE(at) <= DATE(?) ");
+        args.push(BindArg::S(d));
+    }
+
+    let sql = format!(
+        "SELECT id, at, entity, entity_id, action, before, after FROM audit_log {where_sql} \
+         ORDER BY id DESC LIMIT ? OFFSET ?"
+    );
+    let mut q = sqlx::query_as::<_, AuditLogRow>(&sql);
+    for a in &args {
+        match a {
+            BindArg::I(v) => {
+                q = q.bind(*v);
+            }
+            BindArg::S(s) => {
+                q = q.bind(s);
+            }
+        }
+    }
+    let rows = q
+        .bind(f.limit.unwrap_or(200).clamp(1, 10_000))
+        .bind(f.offset.unwrap_or(0).max(0))
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let parse = |s: Option<String>| s.and_then(|s| serde_json::from_str(&s).ok());
+    Ok(rows
+        .into_iter()
+        .map(
+            |(id, at, entity, entity_id, action, before, after)| AuditEntry {
+                id,
+                at,
+                entity,
+                entity_id,
+                action,
+                before: parse(before),
+                after: parse(after),
+            },
+        )
+        .collect())
+}
+
+/* ---------- Undo / redo ---------- */
+// The journal lives in memory next to the pool: it starts empty whenever a
+// database is opened and is dropped when it's closed.
+const JOURNAL_LIMIT: usize = 100;
+
+#[derive(Debug)]
+struct JournalOp {
+    label: String, // "Delete transaction"
+    changes: Vec<AuditChange>,
+}
+
+#[derive(Debug, Default)]
+struct Journal {
+    done: Vec<JournalOp>,
+    undone: Vec<JournalOp>,
+}
+
+// Called after the command's transaction committed. A new edit ends the redo history.
+async fn record_undo(state: &State<'_, AppState>, label: &str, changes: Vec<AuditChange>) {
+    if changes.is_empty() {
+        return;
+    }
+    let mut journal = state.journal.lock().await;
+    journal.undone.clear();
+    journal.done.push(JournalOp {
+        label: label.to_string(),
+        changes,
+    });
+    if journal.done.len() > JOURNAL_LIMIT {
+        journal.done.remove(0);
+    }
+}
+
+async fn delete_audited_row(
+    conn: &mut sqlx::SqliteConnection,
+    entity: &str,
+    id: i64,
+) -> Result<(), String> {
+    if entity == "transaction" {
+        // attachments aren't part of the snapshot, so a redo couldn't bring them back
+        let attachments: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM attachments WHERE transaction_id = ?1")
+                .bind(id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        if attachments > 0 {
+            return Err(format!(
+                "Transaction #{id} has {attachments} attachment(s). \
+                 Delete the transaction instead, it keeps them in the trash."
+            ));
+        }
+    }
+    let statements: &[&str] = match entity {
+        "transaction" => &[
+            "DELETE FROM transaction_splits WHERE transaction_id = ?1",
+            "DELETE FROM transaction_tags WHERE transaction_id = ?1",
+            "DELETE FROM transactions WHERE id = ?1",
+        ],
+        "account" => &["DELETE FROM accounts WHERE id = ?1"],
+        "category" => &["DELETE FROM categories WHERE id = ?1"],
+        "payee" => &[
+            "DELETE FROM payee_aliases WHERE payee_id = ?1",
+            "DELETE FROM payees WHERE id = ?1",
+        ],
+        "tag" => &[
+            "DELETE FROM transaction_tags WHERE tag_id = ?1",
+            "DELETE FROM tags WHERE id = ?1",
+        ],
+        "reconciliation" => &["DELETE FROM reconciliations WHERE id = ?1"],
+        other => return Err(format!("Unknown audit entity '{other}'")),
+    };
+    for sql in statements {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Puts a row back into the state of an `audit_row` snapshot (inserting it if it's gone).
+async fn restore_audited_row(
+    conn: &mut sqlx::SqliteConnection,
+    entity: &str,
+    id: i64,
+    snapshot: &serde_json::Value,
+) -> Result<(), String> {
+    use serde_json::Value;
+    let table = audit_table(entity)?;
+    let obj = snapshot.as_object().ok_or("Malformed journal entry.")?;
+    let cols: Vec<(&String, &Value)> = obj
+        .iter()
+        .filter(|(k, _)| {
+            !matches!(
+                (entity, k.as_str()),
+                ("transaction", "tags" | "splits") | ("payee", "aliases")
+            )
+        })
+        .collect();
+
+    let exists: Option<i64> = sqlx::query_scalar(&format!("SELECT id FROM {table} WHERE id = ?1"))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    // a restored transfer leg needs its transfers row back
+    if let Some(tid) = obj.get("transfer_id").and_then(Value::as_i64) {
+        sqlx::query("INSERT OR IGNORE INTO transfers (id) VALUES (?1)")
+            .bind(tid)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let names: Vec<String> = cols.iter().map(|(k, _)| format!("\"{k}\"")).collect();
+    let sql = if exists.is_some() {
+        let sets: Vec<String> = names.iter().map(|n| format!("{n} = ?")).collect();
+        format!("UPDATE {table} SET {} WHERE id = {id}", sets.join(", "))
+    } else {
+        format!(
+            "INSERT INTO {table} ({}) VALUES ({})",
+            names.join(", "),
+            vec!["?"; names.len()].join(", ")
+        )
+    };
+    let mut q = sqlx::query(&sql);
+    for (_, v) in &cols {
+        q = match v {
+            Value::Null => q.bind(None::<i64>),
+            Value::Bool(b) => q.bind(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => q.bind(i),
+                None => q.bind(n.as_f64()),
+            },
+            Value::String(s) => q.bind(s.as_str()),
+            other => q.bind(other.to_string()),
+        };
+    }
+    q.execute(&mut *conn).await.map_err(|e| e.to_string())?;
+
+    if entity == "transaction" {
+        let tags: Vec<String> = obj
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|a| a.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        replace_tags(conn, id, &tags)
+            .await
+            .map_err(|e| e.to_string())?;
+        sqlx::query("DELETE FROM transaction_splits WHERE transaction_id = ?1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        let splits = obj.get("splits").and_then(Value::as_array);
+        for (pos, s) in splits.into_iter().flatten().enumerate() {
+            sqlx::query(
+                "INSERT INTO transaction_splits \
+                 (transaction_id, category_id, amount, memo, position) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .bind(id)
+            .bind(s.get("category_id").and_then(Value::as_i64))
+            .bind(s.get("amount").and_then(Value::as_i64).unwrap_or(0))
+            .bind(s.get("memo").and_then(Value::as_str))
+            .bind(pos as i64)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    if entity == "payee" {
+        sqlx::query("DELETE FROM payee_aliases WHERE payee_id = ?1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        let aliases = obj.get("aliases").and_then(Value::as_array);
+        for alias in aliases.into_iter().flatten().filter_map(Value::as_str) {
+            sqlx::query("INSERT INTO payee_aliases (payee_id, alias) VALUES (?1, ?2)")
+                .bind(id)
+                .bind(alias)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// Moves each row from `from` to `to`, refusing if it no longer looks like `from`
+// (changed by an import, rules or another window in the meantime).
+async fn apply_journal_op(
+    conn: &mut sqlx::SqliteConnection,
+    op: &JournalOp,
+    undo: bool,
+) -> Result<(), String> {
+    let changes: Vec<&AuditChange> = if undo {
+        op.changes.iter().rev().collect()
+    } else {
+        op.changes.iter().collect()
+    };
+    for c in changes {
+        let (from, to) = if undo {
+            (&c.after, &c.before)
+        } else {
+            (&c.before, &c.after)
+        };
+        let current = audit_row(conn, &c.entity, c.id).await?;
+        if current.as_ref() != from.as_ref() {
+            return Err(format!(
+                "Cannot {} \"{}\": the {} #{} was changed since.",
+                if undo { "undo" } else { "redo" },
+                op.label,
+                c.entity,
+                c.id
+            ));
+        }
+        let before = audit_before(conn, &c.entity, &[c.id]).await?;
+        match to {
+            Some(snapshot) => restore_audited_row(conn, &c.entity, c.id, snapshot).await?,
+            None => delete_audited_row(conn, &c.entity, c.id).await?,
+        }
+        write_audit(conn, &c.entity, before).await?;
+    }
+    Ok(())
+}
+
+async fn step_journal(
+    state: &State<'_, AppState>,
+    steps: Option<usize>,
+    undo: bool,
+) -> Result<Vec<String>, String> {
+    let pool = current_pool(state).await;
+    let mut journal = state.journal.lock().await;
+    let available = if undo {
+        journal.done.len()
+    } else {
+        journal.undone.len()
+    };
+    let n = steps.unwrap_or(1).min(available);
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    // all steps or none
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    // rows come back one at a time, e.g. a category before its parent
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let stack = if undo { &journal.done } else { &journal.undone };
+    for op in stack.iter().rev().take(n) {
+        apply_journal_op(&mut tx, op, undo).await?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    let journal = &mut *journal;
+    let mut labels = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (from, to) = if undo {
+            (&mut journal.done, &mut journal.undone)
+        } else {
+            (&mut journal.undone, &mut journal.done)
+        };
+        let op = from.pop().expect("counted above");
+        labels.push(op.label.clone());
+        to.push(op);
+    }
+    Ok(labels)
+}
+
+// Reverts the last `steps` (default 1) edits of transactions, accounts, categories,
+// payees, tags and reconciliations.
+// Rows purged from the trash don't come back.
+// Returns the labels of the reverted edits, newest first.
+#[tauri::command]
+async fn undo(state: State<'_, AppState>, steps: Option<usize>) -> Result<Vec<String>, String> {
+    step_journal(&state, steps, true).await
+}
+
+#[tauri::command]
+async fn redo(state: State<'_, AppState>, steps: Option<usize>) -> Result<Vec<String>, String> {
+    step_journal(&state, steps, false).await
+}
+
+#[derive(Debug, Serialize)]
+struct JournalStatus {
+    undo: Vec<String>, // labels, next one first
+    redo: Vec<String>,
+}
+
+#[tauri::command]
+async fn journal_status(state: State<'_, AppState>) -> Result<JournalStatus, String> {
+    let journal = state.journal.lock().await;
+    let labels = |ops: &[JournalOp]| ops.iter().rev().map(|op| op.label.clone()).collect();
+    Ok(JournalStatus {
+        undo: labels(&journal.done),
+        redo: labels(&journal.undone),
+    })
+}
+
+/* ---------- Trash ---------- */
+// Deleted transactions, accounts and categories keep their row with `deleted_at` set
+// until they are restored or purged. Opening a database purges everything that has
+// been in the trash longer than the retention setting.
+const TRASH_RETENTION_KEY: &str = "trash_retention_days";
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct TrashItem {
+    entity: String, // "transaction" | "account" | "category"
+    id: i64,
+    name: Option<String>, // description for transactions
+    date: Option<String>, // transactions only
+    amount: Option<Money>,
+    currency: Option<String>,
+    account_name: Option<String>,
+    deleted_at: String, // UTC
+}
+
+// Most recently deleted first; both legs of a transfer are listed.
+#[tauri::command]
+async fn list_trash(state: State<'_, AppState>) -> Result<Vec<TrashItem>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, TrashItem>(
+        r#"
+    SELECT 'transaction' AS entity, t.id, t.description AS name, t.date, t.amount,
+           a.currency, a.name AS account_name, t.deleted_at
+    FROM transactions t
+    JOIN accounts a ON a.id = t.account_id
+    WHERE t.deleted_at IS NOT NULL
+    UNION ALL
+    SELECT 'account', id, name, NULL, NULL, currency, NULL, deleted_at
+    FROM accounts WHERE deleted_at IS NOT NULL
+    UNION ALL
+    SELECT 'category', id, name, NULL, NULL, NULL, NULL, deleted_at
+    FROM categories WHERE deleted_at IS NOT NULL
+    ORDER BY deleted_at DESC, entity, id
+    "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Trashed categories among `ids` and their ancestors, so a restored category
+// doesn't hang below one that's still in the trash.
+async fn trashed_category_chain(
+    conn: &mut sqlx::SqliteConnection,
+    ids: &[i64],
+) -> Result<Vec<i64>, String> {
+    let mut out: Vec<i64> = Vec::new();
+    for id in ids {
+        let chain: Vec<i64> = sqlx::query_scalar(
+            "WITH RECURSIVE up(id) AS (\
+               SELECT ?1 UNION SELECT c.parent_id FROM categories c JOIN up ON c.id = up.id \
+               WHERE c.parent_id IS NOT NULL) \
+             SELECT c.id FROM up JOIN categories c ON c.id = up.id \
+             WHERE c.deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+        for c in chain {
+            if !out.contains(&c) {
+                out.push(c);
+            }
+        }
+    }
+    Ok(out)
+}
+
+async fn untrash(
+    conn: &mut sqlx::SqliteConnection,
+    entity: &str,
+    ids: &[i64],
+) -> Result<Vec<AuditChange>, String> {
+    let table = match entity {
+        "transaction" => "transactions",
+        "account" => "accounts",
+        _ => "categories",
+    };
+    let sql = format!("UPDATE {table} SET deleted_at = NULL WHERE id = ?1");
+    let before = audit_before(conn, entity, ids).await?;
+    for id in ids {
+        sqlx::query(&sql)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    write_audit(conn, entity, before).await
+}
+
+// A transaction comes back with the other leg of its transfer and with its account
+// and categories if those are in the trash too. Subcategories that moved up when a
+// category was deleted stay where they are.
+#[tauri::command]
+async fn restore_from_trash(
+    state: State<'_, AppState>,
+    entity: String,
+    id: i64,
+) -> Result<bool, String> {
+    let entity = entity.trim().to_ascii_lowercase();
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let mut changes = Vec::new();
+    match entity.as_str() {
+        "transaction" => {
+            let transfer_id: Option<i64> = sqlx::query_scalar(
+                "SELECT transfer_id FROM transactions WHERE id = ?1 AND deleted_at IS NOT NULL",
+            )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .flatten();
+            let legs: Vec<i64> = sqlx::query_scalar(
+                "SELECT id FROM transactions WHERE deleted_at IS NOT NULL \
+                 AND (id = ?1 OR (?2 IS NOT NULL AND transfer_id = ?2))",
+            )
+            .bind(id)
+            .bind(transfer_id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            if legs.is_empty() {
+                return Ok(false);
+            }
+            let placeholders = vec!["?"; legs.len()].join(", ");
+
+            let sql = format!(
+                "SELECT DISTINCT a.id FROM accounts a JOIN transactions t ON t.account_id = a.id \
+                 WHERE a.deleted_at IS NOT NULL AND t.id IN ({placeholders})"
+            );
+            let mut q = sqlx::query_scalar::<_, i64>(&sql);
+            for leg in &legs {
+                q = q.bind(*leg);
+            }
+            let accounts = q.fetch_all(&mut *tx).await.map_err(|e| e.to_string())?;
+
+            let sql = format!(
+                "SELECT category_id FROM transactions \
+                 WHERE id IN ({placeholders}) AND category_id IS NOT NULL \
+                 UNION SELECT category_id FROM transaction_splits \
+                 WHERE transaction_id IN ({placeholders}) AND category_id IS NOT NULL"
+            );
+            let mut q = sqlx::query_scalar::<_, i64>(&sql);
+            for leg in legs.iter().chain(legs.iter()) {
+                q = q.bind(*leg);
+            }
+            let used = q.fetch_all(&mut *tx).await.map_err(|e| e.to_string())?;
+            let categories = trashed_category_chain(&mut tx, &used).await?;
+
+            changes.extend(untrash(&mut tx, "category", &categories).await?);
+            changes.extend(untrash(&mut tx, "account", &accounts).await?);
+            changes.extend(untrash(&mut tx, "transaction", &legs).await?);
+        }
+        "account" => {
+            let found: Option<i64> = sqlx::query_scalar(
+                "SELECT id FROM accounts WHERE id = ?1 AND deleted_at IS NOT NULL",
+            )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            if found.is_none() {
+                return Ok(false);
+            }
+            changes.extend(untrash(&mut tx, "account", &[id]).await?);
+        }
+        "category" => {
+            let categories = trashed_category_chain(&mut tx, &[id]).await?;
+            if !categories.contains(&id) {
+                return Ok(false);
+            }
+            changes.extend(untrash(&mut tx, "category", &categories).await?);
+        }
+        other => return Err(format!("Unknown trash entry '{other}'")),
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Restore from trash", changes).await;
+    Ok(true)
+}
+
+// Deletes trashed rows for good: with `older_than_days`, only those deleted
+// at least that many days ago. Purged rows are audited but can't be undone.
+// Returns the number of purged rows.
+async fn purge_trashed(pool: &SqlitePool, older_than_days: Option<i64>) -> Result<i64, String> {
+    let cutoff = format!("-{} days", older_than_days.unwrap_or(0).max(0));
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut purged = 0;
+
+    let transactions: Vec<i64> = sqlx::query_scalar(
+        "SELECT id FROM transactions \
+         WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)",
+    )
+    .bind(&cutoff)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &transactions).await?;
+    for id in &transactions {
+        // foreign_keys is per connection, so don't rely on the cascades
+        for sql in [
+            "DELETE FROM transaction_splits WHERE transaction_id = ?1",
+            "DELETE FROM transaction_tags WHERE transaction_id = ?1",
+            "DELETE FROM attachments WHERE transaction_id = ?1",
+            "UPDATE recurring_occurrences SET transaction_id = NULL WHERE transaction_id = ?1",
+            "DELETE FROM transactions WHERE id = ?1",
+        ] {
+            sqlx::query(sql)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    sqlx::query(
+        "DELETE FROM transfers WHERE id NOT IN \
+         (SELECT transfer_id FROM transactions WHERE transfer_id IS NOT NULL)",
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    purged += write_audit(&mut tx, "transaction", before).await?.len() as i64;
+
+    // an account stays while younger trashed transactions still point at it
+    let accounts: Vec<i64> = sqlx::query_scalar(
+        "SELECT a.id FROM accounts a \
+         WHERE a.deleted_at IS NOT NULL AND a.deleted_at <= datetime('now', ?1) \
+           AND NOT EXISTS (SELECT 1 FROM transactions t WHERE t.account_id = a.id) \
+           AND NOT EXISTS (SELECT 1 FROM recurring_transactions r WHERE r.account_id = a.id)",
+    )
+    .bind(&cutoff)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "account", &accounts).await?;
+    for id in &accounts {
+        // rules limited to this account can never match again
+        for sql in [
+            "DELETE FROM rules WHERE account_id = ?1",
+            "DELETE FROM reconciliations WHERE account_id = ?1",
+            "DELETE FROM accounts WHERE id = ?1",
+        ] {
+            sqlx::query(sql)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    purged += write_audit(&mut tx, "account", before).await?.len() as i64;
+
+    let categories: Vec<i64> = sqlx::query_scalar(
+        "SELECT id FROM categories \
+         WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)",
+    )
+    .bind(&cutoff)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "category", &categories).await?;
+    for id in &categories {
+        // only trashed rows can still use it
+        for sql in [
+            "UPDATE transactions SET category_id = NULL WHERE category_id = ?1",
+            "UPDATE transaction_splits SET category_id = NULL WHERE category_id = ?1",
+            "UPDATE rules SET category_id = NULL WHERE category_id = ?1",
+            "UPDATE recurring_transactions SET category_id = NULL WHERE category_id = ?1",
+            "UPDATE recurring_occurrences SET category_id = NULL WHERE category_id = ?1",
+            "UPDATE categories SET parent_id = (SELECT parent_id FROM categories WHERE id = ?1) \
+             WHERE parent_id = ?1",
+            "DELETE FROM budgets WHERE category_id = ?1",
+            "DELETE FROM categories WHERE id = ?1",
+        ] {
+            sqlx::query(sql)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    purged += write_audit(&mut tx, "category", before).await?.len() as i64;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(purged)
+}
+
+// Empties the trash, or only what's older than `older_than_days`.
+#[tauri::command]
+async fn purge_trash(
+    state: State<'_, AppState>,
+    older_than_days: Option<i64>,
+) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    purge_trashed(&pool, older_than_days).await
+}
+
+async fn trash_retention(pool: &SqlitePool) -> Result<Option<i64>, String> {
+    let days = match get_setting(pool, TRASH_RETENTION_KEY)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        Some(v) => v.parse().unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+        None => DEFAULT_TRASH_RETENTION_DAYS,
+    };
+    Ok(Some(days).filter(|d| *d > 0))
+}
+
+// Days a deleted row stays in the trash; None = kept until purged by hand.
+#[tauri::command]
+async fn get_trash_retention(state: State<'_, AppState>) -> Result<Option<i64>, String> {
+    let pool = current_pool(&state).await;
+    trash_retention(&pool).await
+}
+
+#[tauri::command]
+async fn set_trash_retention(state: State<'_, AppState>, days: Option<i64>) -> Result<(), String> {
+    let days = days.unwrap_or(0);
+    if days < 0 {
+        return Err("Retention must be zero or more days.".into());
+    }
+    let pool = current_pool(&state).await;
+    put_setting(&pool, TRASH_RETENTION_KEY, &days.to_string())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/* ---------- Reconciliation ---------- */
+// Reconciled rows are locked: their amount, date and account can't change
+// and they can't be deleted until the reconciliation is undone.
+async fn check_not_reconciled(conn: &mut sqlx::SqliteConnection, id: i64) -> Result<(), String> {
+    // a transfer is locked as soon as one of its legs is
+    let locked: i64 = sqlx::query_scalar(
+        r#"
+    SELECT COUNT(*) FROM transactions
+    WHERE status = 'reconciled'
+      AND (id = ?1 OR transfer_id = (SELECT transfer_id FROM transactions WHERE id = ?1))
+    "#,
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    if locked > 0 {
+        return Err(format!(
+            "Transaction #{id} is reconciled. Undo the reconciliation to change it."
+        ));
+    }
+    Ok(())
+}
+
+// Marks transactions as cleared or uncleared; reconciled ones are skipped.
+// Unknown or trashed ids are an error. Returns the number of rows changed.
+#[tauri::command]
+async fn set_transaction_status(
+    state: State<'_, AppState>,
+    mut ids: Vec<i64>,
+    status: String,
+) -> Result<i64, String> {
+    let status = status.trim().to_ascii_lowercase();
+    if status != "cleared" && status != "uncleared" {
+        return Err(format!("Status must be 'cleared' or 'uncleared', not '{status}'."));
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &ids).await?;
+    let mut changed = 0;
+    for &id in &ids {
+        let exists: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM transactions WHERE id = ?1 AND deleted_at IS NULL")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        if exists.is_none() {
+            return Err(format!("Transaction #{id} does not exist."));
+        }
+        let res = sqlx::query(
+            "UPDATE transactions SET status = ?1 WHERE id = ?2 AND status <> 'reconciled'",
+        )
+        .bind(&status)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        changed += res.rows_affected() as i64;
+    }
+    let changes = write_audit(&mut tx, "transaction", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Change status", changes).await;
+    Ok(changed)
+}
+
+#[derive(Debug, Deserialize)]
+struct ReconcileInput {
+    account_id: i64,
+    statement_date: String, // YYYY-MM-DD, inclusive
+    statement_balance: Money,
+    finish: Option<bool>, // default false: only report the difference
+}
+
+#[derive(Debug, Serialize)]
+struct ReconcileResult {
+    account_id: i64,
+    statement_date: String,
+    statement_balance: Money,
+    cleared_balance: Money, // cleared + reconciled rows up to the statement date
+    difference: Money,      // statement_balance - cleared_balance; 0 = balanced
+    cleared_count: i64,     // cleared rows that the reconciliation would lock
+    uncleared_count: i64,   // rows up to the statement date not yet cleared
+    uncleared_amount: Money,
+    reconciliation_id: Option<i64>, // set once finished
+}
+
+// Compares the cleared balance with a bank statement. With `finish: true` and
+// no difference, the cleared rows up to the statement date become reconciled.
+#[tauri::command]
+async fn reconcile_account(
+    state: State<'_, AppState>,
+    input: ReconcileInput,
+) -> Result<ReconcileResult, String> {
+    let statement_date = parse_iso_date(&input.statement_date)?
+        .format("%Y-%m-%d")
+        .to_string();
+    let pool = current_pool(&state).await;
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM accounts WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(input.account_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        return Err(format!("Account #{} does not exist.", input.account_id));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let totals: (Money, i64, i64, Money) = sqlx::query_as(
+        r#"
+    SELECT
+      COALESCE(SUM(CASE WHEN status <> 'uncleared' THEN amount END), 0),
+      COUNT(CASE WHEN status = 'cleared' THEN 1 END),
+      COUNT(CASE WHEN status = 'uncleared' THEN 1 END),
+      COALESCE(SUM(CASE WHEN status = 'uncleared' THEN amount END), 0)
+    FROM transactions
+    WHERE account_id = ?1 AND DATE(date) <= DATE(?2) AND deleted_at IS NULL
+    "#,
+    )
+    .bind(input.account_id)
+    .bind(&statement_date)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let (cleared_balance, cleared_count, uncleared_count, uncleared_amount) = totals;
+    let difference = input.statement_balance - cleared_balance;
+
+    let mut reconciliation_id = None;
+    let mut changes = Vec::new();
+    if input.finish.unwrap_or(false) {
+        if difference != Money::ZERO {
+            return Err(format!(
+                "The cleared balance differs from the statement by {:.2}.",
+                difference.to_f64()
+            ));
+        }
+        let id = sqlx::query(
+            "INSERT INTO reconciliations (account_id, statement_date, statement_balance) \
+             VALUES (?1, ?2, ?3)",
+        )
+        .bind(input.account_id)
+        .bind(&statement_date)
+        .bind(input.statement_balance)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM transactions \
+             WHERE account_id = ?1 AND status = 'cleared' AND DATE(date) <= DATE(?2) \
+               AND deleted_at IS NULL",
+        )
+        .bind(input.account_id)
+        .bind(&statement_date)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        let before = audit_before(&mut tx, "transaction", &ids).await?;
+        sqlx::query(
+            "UPDATE transactions SET status = 'reconciled', reconciliation_id = ?1 \
+             WHERE account_id = ?2 AND status = 'cleared' AND DATE(date) <= DATE(?3) \
+               AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(input.account_id)
+        .bind(&statement_date)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        // the reconciliation first, so undo drops it after the transactions let go of it
+        changes = write_audit(&mut tx, "reconciliation", vec![(id, None)]).await?;
+        changes.extend(write_audit(&mut tx, "transaction", before).await?);
+        reconciliation_id = Some(id);
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Reconcile account", changes).await;
+
+    Ok(ReconcileResult {
+        account_id: input.account_id,
+        statement_date,
+        statement_balance: input.statement_balance,
+        cleared_balance,
+        difference,
+        cleared_count,
+        uncleared_count,
+        uncleared_amount,
+        reconciliation_id,
+    })
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Reconciliation {
+    id: i64,
+    account_id: i64,
+    statement_date: String,
+    statement_balance: Money,
+    created_at: String,
+    transactions: i64,
+}
+
+#[tauri::command]
+async fn list_reconciliations(
+    state: State<'_, AppState>,
+    account_id: i64,
+) -> Result<Vec<Reconciliation>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, Reconciliation>(
+        r#"
+    SELECT r.id, r.account_id, r.statement_date, r.statement_balance, r.created_at,
+           (SELECT COUNT(*) FROM transactions t WHERE t.reconciliation_id = r.id) AS transactions
+    FROM reconciliations r
+    WHERE r.account_id = ?1
+    ORDER BY r.statement_date DESC, r.id DESC
+    "#,
+    )
+    .bind(account_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Unlocks the rows of the account's latest reconciliation (they go back to cleared).
+#[tauri::command]
+async fn undo_reconciliation(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let latest: Option<i64> = sqlx::query_scalar(
+        r#"
+    SELECT MAX(r2.id) FROM reconciliations r
+    JOIN reconciliations r2 ON r2.account_id = r.account_id
+    WHERE r.id = ?1
+    "#,
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    match latest {
+        None => return Ok(false),
+        Some(latest) if latest != id => {
+            return Err("Only the latest reconciliation of an account can be undone.".into());
+        }
+        Some(_) => {}
+    }
+    let ids: Vec<i64> =
+        sqlx::query_scalar("SELECT id FROM transactions WHERE reconciliation_id = ?1")
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "transaction", &ids).await?;
+    let before_rec = audit_before(&mut tx, "reconciliation", &[id]).await?;
+    sqlx::query(
+        "UPDATE transactions SET status = 'cleared', reconciliation_id = NULL \
+         WHERE reconciliation_id = ?1",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let res = sqlx::query("DELETE FROM reconciliations WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut changes = write_audit(&mut tx, "transaction", before).await?;
+    changes.extend(write_audit(&mut tx, "reconciliation", before_rec).await?);
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Undo reconciliation", changes).await;
+    Ok(res.rows_affected() > 0)
+}
+
+/* ---------- Attachments (receipts, stored in the encrypted database) ---------- */
+const MAX_ATTACHMENT_SIZE: usize = 25 * 1024 * 1024;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Attachment {
+    id: i64,
+    transaction_id: i64,
+    filename: String,
+    mime_type: String,
+    size: i64,
+    sha256: String,
+    created_at: String,
+}
+
+// By content first; the extension only decides for formats we don't sniff.
+fn guess_mime_type(filename: &str, data: &[u8]) -> &'static str {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return "image/jpeg";
+    }
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return "image/png";
+    }
+    if data.starts_with(b"%PDF-") {
+        return "application/pdf";
+    }
+    let ext = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "pdf" => "application/pdf",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "txt" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+// Adding the same file to a transaction twice returns the existing attachment.
+#[tauri::command]
+async fn add_attachment(
+    state: State<'_, AppState>,
+    transaction_id: i64,
+    path: String,
+) -> Result<Attachment, String> {
+    let data = std::fs::read(&path).map_err(|e| format!("Cannot read {path}: {e}"))?;
+    if data.is_empty() {
+        return Err("The file is empty.".into());
+    }
+    if data.len() > MAX_ATTACHMENT_SIZE {
+        return Err(format!(
+            "The file is too large ({} MB, at most {} MB).",
+            data.len() / (1024 * 1024),
+            MAX_ATTACHMENT_SIZE / (1024 * 1024)
+        ));
+    }
+    let filename = Path::new(&path)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".into());
+    let mime_type = guess_mime_type(&filename, &data);
+    let sha256: String = {
+        use sha2::{Digest, Sha256};
+        Sha256::digest(&data)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    };
+
+    let pool = current_pool(&state).await;
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM transactions WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(transaction_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        return Err(format!("Transaction #{transaction_id} does not exist."));
+    }
+
+    let existing: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM attachments WHERE transaction_id = ?1 AND sha256 = ?2")
+            .bind(transaction_id)
+            .bind(&sha256)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    let id = match existing {
+        Some(id) => id,
+        None => sqlx::query(
+            "INSERT INTO attachments (transaction_id, filename, mime_type, size, sha256, data) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(transaction_id)
+        .bind(&filename)
+        .bind(mime_type)
+        .bind(data.len() as i64)
+        .bind(&sha256)
+        .bind(&data)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid(),
+    };
+
+    sqlx::query_as::<_, Attachment>(
+        "SELECT id, transaction_id, filename, mime_type, size, sha256, created_at \
+         FROM attachments WHERE id = ?1",
+    )
+    .bind(id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_attachments(
+    state: State<'_, AppState>,
+    transaction_id: i64,
+) -> Result<Vec<Attachment>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, Attachment>(
+        "SELECT id, transaction_id, filename, mime_type, size, sha256, created_at \
+         FROM attachments WHERE transaction_id = ?1 ORDER BY id",
+    )
+    .bind(transaction_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// Writes the decrypted file to `path`, or to the downloads folder under its own name.
+#[tauri::command]
+async fn extract_attachment(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: i64,
+    path: Option<String>,
+) -> Result<String, String> {
+    let pool = current_pool(&state).await;
+    let (filename, data): (String, Vec<u8>) =
+        sqlx::query_as("SELECT filename, data FROM attachments WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Attachment #{id} does not exist."))?;
+
+    let path = match trimmed(path) {
+        Some(p) => std::path::PathBuf::from(p),
+        None => {
+            let download_dir = app.path().download_dir().map_err(|_| "No downloads directory")?;
+            // keep the name, but never leave the downloads folder
+            let safe_name: String = filename
+                .chars()
+                .map(|c| if c == '/' || c == '\\' || c == ':' { '_' } else { c })
+                .collect();
+            download_dir.join(safe_name)
+        }
+    };
+    std::fs::write(&path, &data).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn delete_attachment(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let res = sqlx::query("DELETE FROM attachments WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+// Width, height and number of color components from a JPEG's SOF segment.
+fn jpeg_dimensions(data: &[u8]) -> Option<(usize, usize, u8)> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut i = 2;
+    while i + 4 <= data.len() {
+        if data[i] != 0xFF {
+            return None;
+        }
+        let marker = data[i + 1];
+        if marker == 0xFF {
+            i += 1; // fill byte
+            continue;
+        }
+        let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        let is_sof = (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            let seg = data.get(i + 4..i + 2 + len)?;
+            let height = u16::from_be_bytes([*seg.get(1)?, *seg.get(2)?]) as usize;
+            let width = u16::from_be_bytes([*seg.get(3)?, *seg.get(4)?]) as usize;
+            return Some((width, height, *seg.get(5)?));
+        }
+        i += 2 + len;
+    }
+    None
+}
+
+// JPEGs go into the PDF as they are (DCTDecode); other formats can't be embedded.
+fn jpeg_to_pdf_image(data: &[u8]) -> Option<printpdf::Image> {
+    use printpdf::{ColorBits, ColorSpace, ImageFilter, ImageXObject, Px};
+    let (width, height, components) = jpeg_dimensions(data)?;
+    let color_space = match components {
+        1 => ColorSpace::Greyscale,
+        3 => ColorSpace::Rgb,
+        4 => ColorSpace::Cmyk,
+        _ => return None,
+    };
+    Some(printpdf::Image::from(ImageXObject {
+        width: Px(width),
+        height: Px(height),
+        color_space,
+        bits_per_component: ColorBits::Bit8,
+        interpolate: true,
+        image_data: data.to_vec(),
+        image_filter: Some(ImageFilter::DCT),
+        clipping_bbox: None,
+    }))
+}
+
+/* ---------- Transfers (linked transaction pairs) ---------- */
+#[tauri::command]
+async fn add_transfer(state: State<'_, AppState>, input: NewTransfer) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+
+    if input.from_account_id == input.to_account_id {
+        return Err("Choose two different accounts for a transfer.".into());
+    }
+    if !input.amount.is_positive() {
+        return Err("Transfer amount must be greater than zero.".into());
+    }
+    check_account_exists(&pool, input.from_account_id).await?;
+    check_account_exists(&pool, input.to_account_id).await?;
+    let to_amount = input.to_amount.unwrap_or(input.amount);
+    if !to_amount.is_positive() {
+        return Err("Received amount must be greater than zero.".into());
+    }
+
+    let category = input.category.clone().or_else(|| Some("Transfer".to_string()));
+    let cat_id = get_or_create_category_id(&pool, category).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let transfer_id = sqlx::query("INSERT INTO transfers DEFAULT VALUES")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+
+    let mut legs = Vec::with_capacity(2);
+    for (account_id, amount) in [
+        (input.from_account_id, -input.amount),
+        (input.to_account_id, to_amount),
+    ] {
+        let id = sqlx::query(
+            r#"
+        INSERT INTO transactions
+          (account_id, date, description, original_description, amount, category_id,
+           transfer_id, fingerprint)
+        VALUES (?1, ?2, ?3, ?3, ?4, ?5, ?6, ?7);
+        "#,
+        )
+        .bind(account_id)
+        .bind(&input.date)
+        .bind(&input.description)
+        .bind(amount)
+        .bind(cat_id)
+        .bind(transfer_id)
+        .bind(fingerprint(&input.date, amount, input.description.as_deref(), None))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .last_insert_rowid();
+        legs.push((id, None));
+    }
+    let changes = write_audit(&mut tx, "transaction", legs).await?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Add transfer", changes).await;
+    Ok(transfer_id)
+}
+
+#[tauri::command]
+async fn delete_account(state: tauri::State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+
+    // refuse if any transactions outside the trash reference this account
+    let cnt: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM transactions WHERE account_id = ?1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if cnt > 0 {
+        return Err(format!(
+            "This account has {} transaction(s). Move or delete them first.",
+            cnt
+        ));
+    }
+    let recurring: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM recurring_transactions WHERE account_id = ?1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    if recurring > 0 {
+        return Err(format!(
+            "This account has {recurring} recurring transaction(s). Delete them first."
+        ));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "account", &[id]).await?;
+    // rules and reconciliations go when the trash is purged
+    let res = sqlx::query(
+        "UPDATE accounts SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let changes = write_audit(&mut tx, "account", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Delete account", changes).await;
+
+    Ok(res.rows_affected() > 0)
+}
+
+#[tauri::command]
+async fn update_account(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    name: Option<String>,
+    color: Option<String>,
+    currency: Option<String>,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let currency = match currency {
+        Some(c) => Some(normalize_currency(Some(&c))?),
+        None => None,
+    };
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "account", &[id]).await?;
+    let res = sqlx::query(
+        r#"
+    UPDATE accounts
+    SET
+      name  = COALESCE(?1, name),
+      color = COALESCE(?2, color),
+      currency = COALESCE(?3, currency),
+      updated_at = CURRENT_TIMESTAMP
+    WHERE id = ?4;
+    "#,
+    )
+    .bind(name)
+    .bind(color)
+    .bind(currency)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let changes = write_audit(&mut tx, "account", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Edit account", changes).await;
+
+    Ok(res.rows_affected() > 0)
+}
+
+/* ---------- Recurring transactions ---------- */
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// Parsed `rrule` column.
+#[derive(Debug)]
+struct Schedule {
+    freq: Freq,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<chrono::NaiveDate>,
+    by_month_day: Option<i32>,    // MONTHLY; negative counts from the end (-1 = last day)
+    by_day: Vec<chrono::Weekday>, // WEEKLY
+}
+
+fn parse_rrule(s: &str) -> Result<Schedule, String> {
+    use chrono::{NaiveDate, Weekday};
+    let s = s.trim();
+    let s = s.strip_prefix("RRULE:").unwrap_or(s);
+    let mut freq = None;
+    let mut schedule = Schedule {
+        freq: Freq::Monthly,
+        interval: 1,
+        count: None,
+        until: None,
+        by_month_day: None,
+        by_day: Vec::new(),
+    };
+    for part in s.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, val) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid schedule part '{part}'"))?;
+        let val = val.trim().to_ascii_uppercase();
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match val.as_str() {
+                    "DAILY" => Freq::Daily,
+                    "WEEKLY" => Freq::Weekly,
+                    "MONTHLY" => Freq::Monthly,
+                    "YEARLY" => Freq::Yearly,
+                    other => return Err(format!("Unsupported FREQ '{other}'")),
+                })
+            }
+            "INTERVAL" => {
+                schedule.interval = val
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=1000).contains(n))
+                    .ok_or_else(|| format!("Invalid INTERVAL '{val}'"))?
+            }
+            "COUNT" => {
+                schedule.count = Some(
+                    val.parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("Invalid COUNT '{val}'"))?,
+                )
+            }
+            "UNTIL" => {
+                // 20261231, 20261231T000000Z or 2026-12-31
+                let d = NaiveDate::parse_from_str(val.get(..8).unwrap_or(&val), "%Y%m%d")
+                    .or_else(|_| NaiveDate::parse_from_str(&val, "%Y-%m-%d"))
+                    .map_err(|_| format!("Invalid UNTIL '{val}'"))?;
+                schedule.until = Some(d);
+            }
+            "BYMONTHDAY" => {
+                schedule.by_month_day = Some(
+                    val.parse()
+                        .ok()
+                        .filter(|d: &i32| *d != 0 && (-31..=31).contains(d))
+                        .ok_or_else(|| format!("Invalid BYMONTHDAY '{val}'"))?,
+                )
+            }
+            "BYDAY" => {
+                for d in val.split(',').map(str::trim) {
+                    schedule.by_day.push(match d {
+                        "MO" => Weekday::Mon,
+                        "TU" => Weekday::Tue,
+                        "WE" => Weekday::Wed,
+                        "TH" => Weekday::Thu,
+                        "FR" => Weekday::Fri,
+                        "SA" => Weekday::Sat,
+                        "SU" => Weekday::Sun,
+                        other => return Err(format!("Invalid BYDAY '{other}'")),
+                    });
+                }
+                schedule.by_day.sort_by_key(|d| d.num_days_from_monday());
+                schedule.by_day.dedup();
+            }
+            other => return Err(format!("Unsupported schedule part '{other}'")),
+        }
+    }
+    schedule.freq = freq.ok_or("The schedule needs a FREQ (DAILY, WEEKLY, MONTHLY or YEARLY).")?;
+    if schedule.by_month_day.is_some() && schedule.freq != Freq::Monthly {
+        return Err("BYMONTHDAY only works with FREQ=MONTHLY.".into());
+    }
+    if !schedule.by_day.is_empty() && schedule.freq != Freq::Weekly {
+        return Err("BYDAY only works with FREQ=WEEKLY.".into());
+    }
+    Ok(schedule)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    use chrono::NaiveDate;
+    let (y, m) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(y, m, 1)
+        .and_then(|d| d.pred_opt())
+        .map_or(28, |d| chrono::Datelike::day(&d))
+}
+
+// All occurrences from `start` through `through` (inclusive). Days past the end of
+// a month fall on its last day (the 31st becomes Feb 28/29).
+fn schedule_dates(
+    s: &Schedule,
+    start: chrono::NaiveDate,
+    through: chrono::NaiveDate,
+) -> Vec<chrono::NaiveDate> {
+    use chrono::{Datelike, Duration, NaiveDate};
+    let last = s.until.map_or(through, |u| u.min(through));
+    let mut out = Vec::new();
+    let mut k: i64 = 0;
+    while k < 100_000 {
+        let step = k * s.interval;
+        let batch: Vec<NaiveDate> = match s.freq {
+            Freq::Daily => vec![start + Duration::days(step)],
+            Freq::Weekly if s.by_day.is_empty() => vec![start + Duration::weeks(step)],
+            Freq::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(step);
+                s.by_day
+                    .iter()
+                    .map(|d| monday + Duration::days(d.num_days_from_monday() as i64))
+                    .collect()
+            }
+            Freq::Monthly => {
+                let m0 = start.year() as i64 * 12 + start.month0() as i64 + step;
+                let (y, m) = (m0.div_euclid(12) as i32, m0.rem_euclid(12) as u32 + 1);
+                let dim = days_in_month(y, m);
+                let day = match s.by_month_day {
+                    Some(d) if d < 0 => (dim as i32 + d + 1).max(1) as u32,
+                    Some(d) => (d as u32).min(dim),
+                    None => start.day().min(dim),
+                };
+                NaiveDate::from_ymd_opt(y, m, day).into_iter().collect()
+            }
+            Freq::Yearly => {
+                let y = start.year() + step as i32;
+                let day = start.day().min(days_in_month(y, start.month()));
+                NaiveDate::from_ymd_opt(y, start.month(), day).into_iter().collect()
+            }
+        };
+        for d in batch.into_iter().filter(|d| *d >= start) {
+            if d > last || s.count.is_some_and(|c| out.len() >= c) {
+                return out;
+            }
+            out.push(d);
+        }
+        k += 1;
+    }
+    out
+}
+
+fn parse_iso_date(s: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| format!("Invalid date '{s}'"))
+}
+
+fn today() -> chrono::NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct RecurringOut {
+    id: i64,
+    account_id: i64,
+    account_name: String,
+    currency: String,
+    category: Option<String>,
+    description: Option<String>,
+    amount: Money,
+    rrule: String,
+    start_date: String,
+    auto_post: bool,
+    handled_through: Option<String>,
+    #[sqlx(skip)]
+    next_date: Option<String>, // None = schedule has ended (or can't be read)
+    #[sqlx(skip)]
+    schedule_error: Option<String>, // rrule / start date that can't be read; never posted
+}
+
+#[derive(Debug, Deserialize)]
+struct RecurringInput {
+    account_id: i64,
+    category: Option<String>,
+    description: Option<String>,
+    amount: Money,
+    rrule: String,           // e.g. "FREQ=MONTHLY;BYMONTHDAY=1"
+    start_date: String,      // YYYY-MM-DD
+    auto_post: Option<bool>, // default true; false = queue for confirmation
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RecurringRow {
+    id: i64,
+    account_id: i64,
+    category_id: Option<i64>,
+    description: Option<String>,
+    amount: Money,
+    rrule: String,
+    start_date: String,
+    auto_post: bool,
+    handled_through: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OccurrenceRow {
+    date: String,
+    status: String,
+    post_date: Option<String>,
+    amount: Option<Money>,
+    description: Option<String>,
+    category_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Occurrence {
+    recurring_id: i64,
+    date: String,      // scheduled date; identifies the occurrence
+    post_date: String, // booking date (differs when moved)
+    account_id: i64,
+    amount: Money,
+    description: Option<String>,
+    category: Option<String>,
+    status: String, // "planned" | "pending" (due, waiting for confirmation) | "skipped"
+}
+
+#[derive(Debug, Deserialize)]
+struct OccurrenceEdit {
+    recurring_id: i64,
+    date: String,              // scheduled date
+    post_date: Option<String>, // overrides; None = as scheduled
+    amount: Option<Money>,
+    description: Option<String>,
+    category: Option<String>,
+}
+
+async fn load_recurring(
+    conn: &mut sqlx::SqliteConnection,
+    id: i64,
+) -> Result<RecurringRow, String> {
+    sqlx::query_as::<_, RecurringRow>(
+        r#"
+    SELECT id, account_id, category_id, description, amount, rrule, start_date,
+           auto_post, handled_through
+    FROM recurring_transactions WHERE id = ?1
+    "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Recurring transaction #{id} does not exist."))
+}
+
+async fn load_occurrence(
+    conn: &mut sqlx::SqliteConnection,
+    recurring_id: i64,
+    date: &str,
+) -> Result<Option<OccurrenceRow>, sqlx::Error> {
+    sqlx::query_as::<_, OccurrenceRow>(
+        r#"
+    SELECT date, status, post_date, amount, description, category_id
+    FROM recurring_occurrences WHERE recurring_id = ?1 AND date = ?2
+    "#,
+    )
+    .bind(recurring_id)
+    .bind(date)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+// Book one occurrence (with its overrides) as a transaction.
+async fn post_occurrence(
+    conn: &mut sqlx::SqliteConnection,
+    rec: &RecurringRow,
+    date: &str,
+    ov: Option<&OccurrenceRow>,
+) -> Result<i64, String> {
+    let post_date = ov.and_then(|o| o.post_date.clone()).unwrap_or(date.to_string());
+    let amount = ov.and_then(|o| o.amount).unwrap_or(rec.amount);
+    let description = ov
+        .and_then(|o| o.description.clone())
+        .or(rec.description.clone());
+    let category_id = ov.and_then(|o| o.category_id).or(rec.category_id);
+
+    let id = sqlx::query(
+        r#"
+    INSERT INTO transactions
+      (account_id, date, description, original_description, amount, category_id, fingerprint)
+    VALUES (?1, ?2, ?3, ?3, ?4, ?5, ?6);
+    "#,
+    )
+    .bind(rec.account_id)
+    .bind(&post_date)
+    .bind(&description)
+    .bind(amount)
+    .bind(category_id)
+    .bind(fingerprint(&post_date, amount, description.as_deref(), None))
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?
+    .last_insert_rowid();
+    write_audit(conn, "transaction", vec![(id, None)]).await?;
+
+    sqlx::query(
+        r#"
+    INSERT INTO recurring_occurrences (recurring_id, date, status, transaction_id)
+    VALUES (?1, ?2, 'posted', ?3)
+    ON CONFLICT(recurring_id, date) DO UPDATE
+      SET status = 'posted', transaction_id = excluded.transaction_id;
+    "#,
+    )
+    .bind(rec.id)
+    .bind(date)
+    .bind(id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+// Post (or queue) every occurrence that is due by `today`. Runs when a database is opened.
+// Schedules that can't be read are left alone; `list_recurring` reports them.
+async fn post_due_recurring(pool: &SqlitePool, today: chrono::NaiveDate) -> Result<(), String> {
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM recurring_transactions ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let today_s = today.format("%Y-%m-%d").to_string();
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for id in ids {
+        let rec = load_recurring(&mut tx, id).await?;
+        let (Ok(schedule), Ok(start)) = (parse_rrule(&rec.rrule), parse_iso_date(&rec.start_date))
+        else {
+            continue;
+        };
+
+        let new_dates: Vec<String> = schedule_dates(&schedule, start, today)
+            .into_iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .filter(|d| rec.handled_through.as_ref().is_none_or(|h| d > h))
+            .collect();
+        // planned edits that are due now, including occurrences moved to an earlier date
+        let planned: Vec<String> = sqlx::query_scalar(
+            r#"
+        SELECT date FROM recurring_occurrences
+        WHERE recurring_id = ?1 AND status = 'planned' AND COALESCE(post_date, date) <= ?2
+        "#,
+        )
+        .bind(id)
+        .bind(&today_s)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut due = planned;
+        for d in new_dates {
+            if !due.contains(&d) {
+                due.push(d);
+            }
+        }
+        due.sort();
+
+        for date in due {
+            let ov = load_occurrence(&mut tx, id, &date)
+                .await
+                .map_err(|e| e.to_string())?;
+            if let Some(o) = &ov {
+                // moved to a later date, or already handled
+                if o.status != "planned" || o.post_date.as_ref().is_some_and(|p| *p > today_s) {
+                    continue;
+                }
+            }
+            if rec.auto_post {
+                post_occurrence(&mut tx, &rec, &date, ov.as_ref()).await?;
+            } else {
+                sqlx::query(
+                    r#"
+                INSERT INTO recurring_occurrences (recurring_id, date, status)
+                VALUES (?1, ?2, 'pending')
+                ON CONFLICT(recurring_id, date) DO UPDATE SET status = 'pending';
+                "#,
+                )
+                .bind(id)
+                .bind(&date)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            }
+        }
+
+        sqlx::query("UPDATE recurring_transactions SET handled_through = ?1 WHERE id = ?2")
+            .bind(&today_s)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+async fn check_recurring_input(pool: &SqlitePool, input: &RecurringInput) -> Result<(), String> {
+    parse_rrule(&input.rrule)?;
+    parse_iso_date(&input.start_date)?;
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM accounts WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(input.account_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        return Err(format!("Account #{} does not exist.", input.account_id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_recurring(state: State<'_, AppState>) -> Result<Vec<RecurringOut>, String> {
+    let pool = current_pool(&state).await;
+    let mut items = sqlx::query_as::<_, RecurringOut>(
+        r#"
+    SELECT r.id, r.account_id, a.name AS account_name, a.currency, c.name AS category,
+           r.description, r.amount, r.rrule, r.start_date, r.auto_post, r.handled_through
+    FROM recurring_transactions r
+    JOIN accounts a ON a.id = r.account_id
+    LEFT JOIN categories c ON c.id = r.category_id
+    ORDER BY r.id
+    "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let horizon = today() + chrono::Duration::days(400);
+    for it in items.iter_mut() {
+        let (s, start) = match (parse_rrule(&it.rrule), parse_iso_date(&it.start_date)) {
+            (Ok(s), Ok(start)) => (s, start),
+            (Err(e), _) | (_, Err(e)) => {
+                it.schedule_error = Some(e);
+                continue;
+            }
+        };
+        it.next_date = schedule_dates(&s, start, horizon)
+            .into_iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .find(|d| it.handled_through.as_ref().is_none_or(|h| d > h));
+    }
+    Ok(items)
+}
+
+#[tauri::command]
+async fn add_recurring(state: State<'_, AppState>, input: RecurringInput) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    check_recurring_input(&pool, &input).await?;
+    let cat_id = get_or_create_category_id(&pool, input.category.clone()).await?;
+
+    let id = sqlx::query(
+        r#"
+    INSERT INTO recurring_transactions
+      (account_id, category_id, description, amount, rrule, start_date, auto_post)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);
+    "#,
+    )
+    .bind(input.account_id)
+    .bind(cat_id)
+    .bind(trimmed(input.description))
+    .bind(input.amount)
+    .bind(input.rrule.trim())
+    .bind(input.start_date.trim())
+    .bind(input.auto_post.unwrap_or(true))
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .last_insert_rowid();
+
+    // a start date in the past books what's due right away
+    post_due_recurring(&pool, today()).await?;
+    Ok(id)
+}
+
+#[tauri::command]
+async fn update_recurring(
+    state: State<'_, AppState>,
+    id: i64,
+    input: RecurringInput,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    check_recurring_input(&pool, &input).await?;
+    let cat_id = get_or_create_category_id(&pool, input.category.clone()).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let res = sqlx::query(
+        r#"
+    UPDATE recurring_transactions
+    SET account_id = ?1, category_id = ?2, description = ?3, amount = ?4,
+        rrule = ?5, start_date = ?6, auto_post = ?7
+    WHERE id = ?8
+    "#,
+    )
+    .bind(input.account_id)
+    .bind(cat_id)
+    .bind(trimmed(input.description))
+    .bind(input.amount)
+    .bind(input.rrule.trim())
+    .bind(input.start_date.trim())
+    .bind(input.auto_post.unwrap_or(true))
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    // edits and skips of future occurrences belonged to the old schedule
+    sqlx::query(
+        "DELETE FROM recurring_occurrences WHERE recurring_id = ?1 AND status IN ('planned', 'skipped') AND date > ?2",
+    )
+    .bind(id)
+    .bind(today().format("%Y-%m-%d").to_string())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    post_due_recurring(&pool, today()).await?;
+    Ok(res.rows_affected() > 0)
+}
+
+#[tauri::command]
+async fn delete_recurring(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    // transactions already posted stay
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM recurring_occurrences WHERE recurring_id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let res = sqlx::query("DELETE FROM recurring_transactions WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+// Queued occurrences plus everything scheduled in the next `days` days (default 30).
+#[tauri::command]
+async fn upcoming_occurrences(
+    state: State<'_, AppState>,
+    days: Option<i64>,
+) -> Result<Vec<Occurrence>, String> {
+    let pool = current_pool(&state).await;
+    let through = today() + chrono::Duration::days(days.unwrap_or(30).max(0));
+
+    let recs = sqlx::query_as::<_, RecurringRow>(
+        r#"
+    SELECT id, account_id, category_id, description, amount, rrule, start_date,
+           auto_post, handled_through
+    FROM recurring_transactions ORDER BY id
+    "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let categories: std::collections::HashMap<i64, String> =
+        sqlx::query_as::<_, (i64, String)>("SELECT id, name FROM categories")
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+
+    let mut out = Vec::new();
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    for rec in recs {
+        let (Ok(schedule), Ok(start)) = (parse_rrule(&rec.rrule), parse_iso_date(&rec.start_date))
+        else {
+            continue; // reported by list_recurring
+        };
+        let overrides = sqlx::query_as::<_, OccurrenceRow>(
+            r#"
+        SELECT date, status, post_date, amount, description, category_id
+        FROM recurring_occurrences WHERE recurring_id = ?1 AND status <> 'posted'
+        "#,
+        )
+        .bind(rec.id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let mut dates: Vec<String> = overrides
+            .iter()
+            .filter(|o| o.status == "pending")
+            .map(|o| o.date.clone())
+            .collect();
+        dates.extend(
+            schedule_dates(&schedule, start, through)
+                .into_iter()
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .filter(|d| rec.handled_through.as_ref().is_none_or(|h| d > h)),
+        );
+        dates.sort();
+        dates.dedup();
+
+        for date in dates {
+            let ov = overrides.iter().find(|o| o.date == date);
+            let category_id = ov.and_then(|o| o.category_id).or(rec.category_id);
+            out.push(Occurrence {
+                recurring_id: rec.id,
+                post_date: ov.and_then(|o| o.post_date.clone()).unwrap_or(date.clone()),
+                account_id: rec.account_id,
+                amount: ov.and_then(|o| o.amount).unwrap_or(rec.amount),
+                description: ov
+                    .and_then(|o| o.description.clone())
+                    .or(rec.description.clone()),
+                category: category_id.and_then(|c| categories.get(&c).cloned()),
+                status: ov.map_or("planned".to_string(), |o| o.status.clone()),
+                date,
+            });
+        }
+    }
+    out.sort_by(|a, b| a.post_date.cmp(&b.post_date).then(a.recurring_id.cmp(&b.recurring_id)));
+    Ok(out)
+}
+
+// The occurrence must be on the schedule and not booked yet.
+async fn check_open_occurrence(
+    conn: &mut sqlx::SqliteConnection,
+    rec: &RecurringRow,
+    date: &str,
+) -> Result<Option<OccurrenceRow>, String> {
+    let d = parse_iso_date(date)?;
+    let schedule = parse_rrule(&rec.rrule)?;
+    if !schedule_dates(&schedule, parse_iso_date(&rec.start_date)?, d).contains(&d) {
+        return Err(format!("{date} is not an occurrence of this schedule."));
+    }
+    let ov = load_occurrence(conn, rec.id, date)
+        .await
+        .map_err(|e| e.to_string())?;
+    if ov.as_ref().is_some_and(|o| o.status == "posted") {
+        return Err(format!("The occurrence on {date} was already posted."));
+    }
+    Ok(ov)
+}
+
+#[tauri::command]
+async fn skip_occurrence(
+    state: State<'_, AppState>,
+    recurring_id: i64,
+    date: String,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let rec = load_recurring(&mut conn, recurring_id).await?;
+    check_open_occurrence(&mut conn, &rec, date.trim()).await?;
+
+    sqlx::query(
+        r#"
+    INSERT INTO recurring_occurrences (recurring_id, date, status)
+    VALUES (?1, ?2, 'skipped')
+    ON CONFLICT(recurring_id, date) DO UPDATE SET status = 'skipped';
+    "#,
+    )
+    .bind(recurring_id)
+    .bind(date.trim())
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+// Change one occurrence; a skipped one is scheduled again.
+#[tauri::command]
+async fn edit_occurrence(state: State<'_, AppState>, input: OccurrenceEdit) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let date = input.date.trim();
+    let post_date = trimmed(input.post_date);
+    if let Some(p) = &post_date {
+        parse_iso_date(p)?;
+    }
+    let cat_id = get_or_create_category_id(&pool, input.category.clone()).await?;
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let rec = load_recurring(&mut conn, input.recurring_id).await?;
+    let ov = check_open_occurrence(&mut conn, &rec, date).await?;
+    let status = match ov.map(|o| o.status) {
+        Some(s) if s == "pending" => s,
+        _ => "planned".to_string(),
+    };
+
+    sqlx::query(
+        r#"
+    INSERT INTO recurring_occurrences
+      (recurring_id, date, status, post_date, amount, description, category_id)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+    ON CONFLICT(recurring_id, date) DO UPDATE
+      SET status = excluded.status, post_date = excluded.post_date, amount = excluded.amount,
+          description = excluded.description, category_id = excluded.category_id;
+    "#,
+    )
+    .bind(input.recurring_id)
+    .bind(date)
+    .bind(&status)
+    .bind(&post_date)
+    .bind(input.amount)
+    .bind(trimmed(input.description))
+    .bind(cat_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    drop(conn);
+
+    // an occurrence moved into the past is due now
+    post_due_recurring(&pool, today()).await?;
+    Ok(true)
+}
+
+// Book a queued (or any upcoming) occurrence now; returns the transaction id.
+#[tauri::command]
+async fn confirm_occurrence(
+    state: State<'_, AppState>,
+    recurring_id: i64,
+    date: String,
+) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let rec = load_recurring(&mut tx, recurring_id).await?;
+    let ov = check_open_occurrence(&mut tx, &rec, date.trim()).await?;
+    let id = post_occurrence(&mut tx, &rec, date.trim(), ov.as_ref()).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/* ---------- Statement import (shared) ---------- */
+// Every importer parses into `ImportRow`s first; nothing touches the DB until the
+// caller re-runs the import with `dry_run: false`.
+#[derive(Debug, Serialize, Clone, Default)]
+struct ImportRow {
+    line: usize,  // 1-based line (or entry) in the source file
+    date: String, // YYYY-MM-DD
+    description: Option<String>,
+    amount: Money,
+    category: Option<String>,
+    counterparty: Option<String>,
+    counterparty_iban: Option<String>,
+    bank_ref: Option<String>, // bank's entry reference, unique per account
+    currency: Option<String>, // as stated in the file; must match the account
+    already_imported: bool,   // bank_ref / fingerprint already in the account (skipped on commit)
+    splits: Vec<SplitInput>,  // QIF split lines; must add up to `amount`
+    possible_duplicates: Vec<i64>, // same amount within a few days: review before committing
+    excluded: bool,                // listed in `skip_lines` by the caller
+    tags: Vec<String>,             // added by auto-categorization rules
+    payee_id: Option<i64>,         // matched by payee alias
+    payee: Option<String>,
+    #[serde(skip)]
+    fingerprint: String,
+    #[serde(skip)]
+    original_description: Option<String>, // the file's text, before rules rewrite it
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ImportIssue {
+    line: usize,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Default)]
+struct ImportResult {
+    rows: Vec<ImportRow>,
+    errors: Vec<ImportIssue>, // rows that could not be parsed (never imported)
+    imported: i64,            // 0 for a dry run
+    skipped: i64,             // already imported or excluded rows
+}
+
+// Bank-style amount: "1.234,56", "-12,30", "12,30-", "(5.00)", "€ 3,50".
+fn parse_statement_amount(s: &str, decimal_sep: char) -> Option<Money> {
+    let mut t: String = s
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'' && *c != '€' && *c != '$')
+        .collect();
+    for code in ["EUR", "USD", "CHF", "GBP"] {
+        t = t.replace(code, "");
+    }
+    let mut neg = false;
+    if let Some(inner) = t.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+        neg = true;
+        t = inner.to_string();
+    }
+    if let Some(rest) = t.strip_suffix('-') {
+        neg = !neg;
+        t = rest.to_string();
+    }
+    let thousands = if decimal_sep == ',' { '.' } else { ',' };
+    let t: String = t
+        .chars()
+        .filter(|c| *c != thousands)
+        .map(|c| if c == decimal_sep { '.' } else { c })
+        .collect();
+    let m = Money::parse(&t)?;
+    Some(if neg { -m } else { m })
+}
+
+fn parse_statement_date(s: &str, format: Option<&str>) -> Option<String> {
+    use chrono::{Datelike, NaiveDate};
+    let s = s.trim();
+    let formats: Vec<&str> = match format {
+        Some(f) if !f.trim().is_empty() => vec![f.trim()],
+        _ => vec!["%d.%m.%Y", "%Y-%m-%d", "%d.%m.%y", "%d/%m/%Y", "%m/%d/%Y"],
+    };
+    // "%Y" happily reads "24" as the year 24; leave short years to "%y"
+    formats
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(s, f).ok().filter(|d| d.year() >= 1000))
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+// Single-account import in its own transaction.
+async fn finish_import(
+    pool: &SqlitePool,
+    account_id: i64,
+    result: &mut ImportResult,
+    skip_lines: &[usize],
+    dry_run: bool,
+) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    import_into_account(&mut tx, account_id, result, skip_lines, dry_run).await?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+// Check parsed rows against the target account (currency, refs imported before),
+// then insert them unless this is a dry run. The caller owns the transaction.
+async fn import_into_account(
+    conn: &mut sqlx::SqliteConnection,
+    account_id: i64,
+    result: &mut ImportResult,
+    skip_lines: &[usize],
+    dry_run: bool,
+) -> Result<(), String> {
+    let currency: Option<String> =
+        sqlx::query_scalar("SELECT currency FROM accounts WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(account_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    let Some(currency) = currency else {
+        return Err(format!("Account #{account_id} does not exist."));
+    };
+
+    let rules = load_rules(&mut *conn).await?;
+    let payees = PayeeMatcher::load(&mut *conn).await.map_err(|e| e.to_string())?;
+
+    // how often each fingerprint is already booked, so two identical coffees
+    // on the same day are only skipped if both were imported before
+    let mut known_fps: std::collections::HashMap<String, i64> = Default::default();
+
+    let rows = std::mem::take(&mut result.rows);
+    for mut r in rows {
+        if let Some(cur) = r.currency.as_deref() {
+            if !cur.eq_ignore_ascii_case(&currency) {
+                result.errors.push(ImportIssue {
+                    line: r.line,
+                    message: format!("amount is in {cur}, the account uses {currency}"),
+                });
+                continue;
+            }
+        }
+        if let Some(bank_ref) = &r.bank_ref {
+            let known: Option<i64> = sqlx::query_scalar(
+                "SELECT id FROM transactions \
+                 WHERE account_id = ?1 AND bank_ref = ?2 AND deleted_at IS NULL",
+            )
+            .bind(account_id)
+            .bind(bank_ref)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+            r.already_imported = known.is_some()
+                || result.rows.iter().any(|p| p.bank_ref.as_ref() == Some(bank_ref));
+        }
+
+        r.fingerprint = fingerprint(&r.date, r.amount, r.description.as_deref(), r.bank_ref.as_deref());
+        r.original_description = r.description.clone();
+        if !r.already_imported {
+            if !known_fps.contains_key(&r.fingerprint) {
+                let n: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM transactions \
+                     WHERE account_id = ?1 AND fingerprint = ?2 AND deleted_at IS NULL",
+                )
+                .bind(account_id)
+                .bind(&r.fingerprint)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+                known_fps.insert(r.fingerprint.clone(), n);
+            }
+            if let Some(n) = known_fps.get_mut(&r.fingerprint).filter(|n| **n > 0) {
+                *n -= 1;
+                r.already_imported = true;
+            } else {
+                r.possible_duplicates =
+                    duplicate_candidates(&mut *conn, account_id, &r.date, r.amount, None)
+                        .await
+                        .map_err(|e| e.to_string())?;
+            }
+        }
+        r.excluded = skip_lines.contains(&r.line);
+        if let Some((id, name)) = payees.find(r.counterparty.as_deref(), r.description.as_deref()) {
+            r.payee_id = Some(id);
+            r.payee = Some(name.to_string());
+        }
+
+        // after fingerprinting, which must see the bank's original text
+        let ruled = run_rules(
+            &rules,
+            account_id,
+            r.amount,
+            r.description.as_deref(),
+            r.counterparty.as_deref(),
+        );
+        if r.category.is_none() && r.splits.is_empty() {
+            r.category = ruled.category;
+        }
+        // the bank's text is not something the user typed, so rules may rewrite it
+        if ruled.description.is_some() {
+            r.description = ruled.description;
+        }
+        r.tags = ruled.tags;
+        result.rows.push(r);
+    }
+    result.errors.sort_by_key(|e| e.line);
+    result.skipped = result
+        .rows
+        .iter()
+        .filter(|r| r.already_imported || r.excluded)
+        .count() as i64;
+
+    if !dry_run {
+        result.imported = insert_import_rows(conn, account_id, &result.rows).await?;
+    }
+    Ok(())
+}
+
+// Insert the rows that are neither already imported nor excluded.
+async fn insert_import_rows(
+    conn: &mut sqlx::SqliteConnection,
+    account_id: i64,
+    rows: &[ImportRow],
+) -> Result<i64, String> {
+    let rows: Vec<&ImportRow> = rows
+        .iter()
+        .filter(|r| !r.already_imported && !r.excluded)
+        .collect();
+
+    for r in &rows {
+        let lines = resolve_splits(&mut *conn, &r.splits).await?;
+        let cat_id = if lines.is_empty() {
+            get_or_create_category_id(&mut *conn, r.category.clone()).await?
+        } else {
+            None
+        };
+        let res = sqlx::query(
+            r#"
+        INSERT INTO transactions
+          (account_id, date, description, amount, category_id,
+           counterparty, counterparty_iban, bank_ref, fingerprint, payee_id,
+           original_description)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11);
+        "#,
+        )
+        .bind(account_id)
+        .bind(&r.date)
+        .bind(&r.description)
+        .bind(r.amount)
+        .bind(cat_id)
+        .bind(&r.counterparty)
+        .bind(&r.counterparty_iban)
+        .bind(&r.bank_ref)
+        .bind(&r.fingerprint)
+        .bind(r.payee_id)
+        .bind(&r.original_description)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Line {}: {e}", r.line))?;
+        let id = res.last_insert_rowid();
+        add_tags(&mut *conn, id, &r.tags)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !lines.is_empty() {
+            replace_splits(&mut *conn, id, &lines)
+                .await
+                .map_err(|e| e.to_string())?;
+            check_split_total(&mut *conn, id)
+                .await
+                .map_err(|e| format!("Line {}: {e}", r.line))?;
+        }
+    }
+    Ok(rows.len() as i64)
+}
+
+/* ---------- CSV import ---------- */
+#[derive(Debug, Deserialize)]
+struct CsvColumnMapping {
+    // 0-based column indexes
+    date: usize,
+    description: Option<usize>,
+    amount: Option<usize>, // signed amount ...
+    debit: Option<usize>,  // ... or separate debit / credit columns
+    credit: Option<usize>,
+    category: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CsvImport {
+    path: String,
+    account_id: i64,
+    mapping: CsvColumnMapping,
+    delimiter: Option<String>,         // ";" | "," | "tab" (default: guessed)
+    encoding: Option<String>,          // "utf-8" (default), "windows-1252", ...
+    date_format: Option<String>,       // chrono format, e.g. "%d.%m.%Y"
+    decimal_separator: Option<String>, // "," (default) or "."
+    skip_rows: Option<usize>,          // preamble lines before the header
+    has_header: Option<bool>,          // default true
+    dry_run: Option<bool>,             // default true: only preview
+    skip_lines: Option<Vec<usize>>,    // rows the user excluded after the preview
+}
+
+#[derive(Debug, Serialize)]
+struct CsvImportResult {
+    headers: Vec<String>,
+    #[serde(flatten)]
+    result: ImportResult,
+}
+
+fn read_text_with_encoding(path: &str, encoding: Option<&str>) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let label = encoding.unwrap_or("utf-8");
+    let enc = encoding_rs::Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("Unknown encoding '{label}'"))?;
+    let (text, _, had_errors) = enc.decode(&bytes);
+    if had_errors {
+        return Err(format!("File is not valid {}", enc.name()));
+    }
+    Ok(text.into_owned())
+}
+
+// UTF-8 if it decodes, otherwise Windows-1252 (older .sta / .qif / .ofx exports)
+fn read_text_guess_encoding(path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(t) => t,
+        Err(e) => encoding_rs::WINDOWS_1252.decode(e.as_bytes()).0.into_owned(),
+    })
+}
+
+fn parse_csv_statement(text: &str, opts: &CsvImport) -> Result<CsvImportResult, String> {
+    let skip = opts.skip_rows.unwrap_or(0);
+    let delimiter = match opts.delimiter.as_deref().map(str::trim) {
+        Some("tab") | Some("\\t") => b'\t',
+        Some(d) if d.len() == 1 => d.as_bytes()[0],
+        Some(d) if !d.is_empty() => return Err(format!("Unsupported delimiter '{d}'")),
+        _ => {
+            let first = text.lines().nth(skip).unwrap_or("");
+            if first.contains(';') {
+                b';'
+            } else if first.contains('\t') {
+                b'\t'
+            } else {
+                b','
+            }
+        }
+    };
+    let decimal_sep = match opts.decimal_separator.as_deref().map(str::trim) {
+        Some(".") => '.',
+        None | Some(",") | Some("") => ',',
+        Some(d) => return Err(format!("Unsupported decimal separator '{d}'")),
+    };
+    let m = &opts.mapping;
+    if m.amount.is_none() && m.debit.is_none() && m.credit.is_none() {
+        return Err("Map either an amount column or debit/credit columns.".into());
+    }
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let has_header = opts.has_header.unwrap_or(true);
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (idx, rec) in rdr.records().enumerate() {
+        let line = idx + 1;
+        if idx < skip {
+            continue;
+        }
+        let rec = match rec {
+            Ok(r) => r,
+            Err(e) => {
+                errors.push(ImportIssue {
+                    line,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        if idx == skip && has_header {
+            headers = rec.iter().map(|h| h.trim().to_string()).collect();
+            continue;
+        }
+        if rec.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        let cell = |i: Option<usize>| -> Option<&str> {
+            i.and_then(|i| rec.get(i))
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+        };
+
+        let raw_date = cell(Some(m.date)).unwrap_or("");
+        let Some(date) = parse_statement_date(raw_date, opts.date_format.as_deref()) else {
+            errors.push(ImportIssue {
+                line,
+                message: format!("invalid date '{raw_date}'"),
+            });
+            continue;
+        };
+
+        let amount = if m.amount.is_some() {
+            let raw = cell(m.amount).unwrap_or("");
+            parse_statement_amount(raw, decimal_sep).ok_or(format!("invalid amount '{raw}'"))
+        } else {
+            // debit/credit columns usually hold unsigned values
+            let part = |i: Option<usize>| -> Result<Money, String> {
+                match cell(i) {
+                    Some(raw) => parse_statement_amount(raw, decimal_sep)
+                        .map(Money::abs)
+                        .ok_or(format!("invalid amount '{raw}'")),
+                    None => Ok(Money::ZERO),
+                }
+            };
+            part(m.credit).and_then(|c| Ok(c - part(m.debit)?))
+        };
+        let amount = match amount {
+            Ok(a) => a,
+            Err(message) => {
+                errors.push(ImportIssue { line, message });
+                continue;
+            }
+        };
+
+        rows.push(ImportRow {
+            line,
+            date,
+            description: cell(m.description).map(str::to_string),
+            amount,
+            category: cell(m.category).map(str::to_string),
+            counterparty: None,
+            counterparty_iban: None,
+            bank_ref: None,
+            currency: None,
+            ..Default::default()
+        });
+    }
+
+    Ok(CsvImportResult {
+        headers,
+        result: ImportResult {
+            rows,
+            errors,
+            imported: 0,
+            skipped: 0,
+        },
+    })
+}
+
+#[tauri::command]
+async fn import_csv(
+    state: State<'_, AppState>,
+    input: CsvImport,
+) -> Result<CsvImportResult, String> {
+    let text = read_text_with_encoding(&input.path, input.encoding.as_deref())?;
+    let mut out = parse_csv_statement(&text, &input)?;
+
+    let pool = current_pool(&state).await;
+    finish_import(
+        &pool,
+        input.account_id,
+        &mut out.result,
+        input.skip_lines.as_deref().unwrap_or_default(),
+        input.dry_run.unwrap_or(true),
+    )
+    .await?;
+    Ok(out)
+}
+
+/* ---------- CAMT.053 / CAMT.052 import (ISO 20022) ---------- */
+#[derive(Debug, Deserialize)]
+struct StatementImport {
+    path: String,
+    account_id: i64,
+    dry_run: Option<bool>,          // default true: only preview
+    skip_lines: Option<Vec<usize>>, // entries the user excluded after the preview
+}
+
+// Direct children by local name, so any camt.05x namespace version works.
+fn xml_child<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|c| c.tag_name().name() == name)
+}
+
+fn xml_path<'a, 'i>(
+    node: roxmltree::Node<'a, 'i>,
+    path: &[&str],
+) -> Option<roxmltree::Node<'a, 'i>> {
+    path.iter().try_fold(node, |n, p| xml_child(n, p))
+}
+
+fn xml_text(node: roxmltree::Node, path: &[&str]) -> Option<String> {
+    xml_path(node, path)
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+fn camt_date(node: roxmltree::Node) -> Option<String> {
+    xml_text(node, &["Dt"])
+        .or_else(|| xml_text(node, &["DtTm"]).map(|t| t.chars().take(10).collect()))
+}
+
+// Party name moved under <Pty> in camt.053.001.08+.
+fn camt_party_name(party: roxmltree::Node) -> Option<String> {
+    xml_text(party, &["Nm"]).or_else(|| xml_text(party, &["Pty", "Nm"]))
+}
+
+fn parse_camt(text: &str) -> Result<ImportResult, String> {
+    let doc = roxmltree::Document::parse(text).map_err(|e| format!("Not a valid XML file: {e}"))?;
+    let root = doc.root_element();
+    let (container, item) = match xml_child(root, "BkToCstmrStmt") {
+        Some(n) => (n, "Stmt"),
+        None => match xml_child(root, "BkToCstmrAcctRpt") {
+            Some(n) => (n, "Rpt"),
+            None => return Err("Not a CAMT.053 / CAMT.052 document.".into()),
+        },
+    };
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for stmt in container.children().filter(|n| n.tag_name().name() == item) {
+        for ntry in stmt.children().filter(|n| n.tag_name().name() == "Ntry") {
+            let line = doc.text_pos_at(ntry.range().start).row as usize;
+
+            // Sts is plain text up to .001.08, <Sts><Cd>..</Cd></Sts> afterwards
+            let status = xml_text(ntry, &["Sts"]).or_else(|| xml_text(ntry, &["Sts", "Cd"]));
+            if let Some(s) = status.as_deref().filter(|s| *s != "BOOK") {
+                errors.push(ImportIssue {
+                    line,
+                    message: format!("entry not booked yet (status {s}), skipped"),
+                });
+                continue;
+            }
+
+            let Some(amt) = xml_child(ntry, "Amt") else {
+                errors.push(ImportIssue {
+                    line,
+                    message: "entry has no amount".into(),
+                });
+                continue;
+            };
+            let raw_amount = amt.text().unwrap_or("").trim();
+            let Some(amount) = Money::parse(raw_amount).map(Money::abs) else {
+                errors.push(ImportIssue {
+                    line,
+                    message: format!("invalid amount '{raw_amount}'"),
+                });
+                continue;
+            };
+            let debit = match xml_text(ntry, &["CdtDbtInd"]).as_deref() {
+                Some("DBIT") => true,
+                Some("CRDT") => false,
+                other => {
+                    errors.push(ImportIssue {
+                        line,
+                        message: format!("invalid credit/debit indicator {other:?}"),
+                    });
+                    continue;
+                }
+            };
+            let amount = if debit { -amount } else { amount };
+
+            let date = xml_child(ntry, "BookgDt")
+                .and_then(camt_date)
+                .or_else(|| xml_child(ntry, "ValDt").and_then(camt_date));
+            let Some(date) = date.and_then(|d| parse_statement_date(&d, Some("%Y-%m-%d"))) else {
+                errors.push(ImportIssue {
+                    line,
+                    message: "entry has no booking date".into(),
+                });
+                continue;
+            };
+
+            // Counterparty and remittance info only when the entry is a single transaction;
+            // batch bookings keep the bank's summary text.
+            let tx_dtls: Vec<_> = xml_child(ntry, "NtryDtls")
+                .map(|d| {
+                    d.descendants()
+                        .filter(|n| n.tag_name().name() == "TxDtls")
+                        .collect()
+                })
+                .unwrap_or_default();
+            let single = if tx_dtls.len() == 1 { Some(tx_dtls[0]) } else { None };
+
+            let (party, party_acct) = if debit {
+                ("Cdtr", "CdtrAcct")
+            } else {
+                ("Dbtr", "DbtrAcct")
+            };
+            let counterparty = single
+                .and_then(|t| xml_path(t, &["RltdPties", party]))
+                .and_then(camt_party_name);
+            let counterparty_iban =
+                single.and_then(|t| xml_text(t, &["RltdPties", party_acct, "Id", "IBAN"]));
+
+            let remittance: Vec<String> = single
+                .and_then(|t| xml_child(t, "RmtInf"))
+                .map(|r| {
+                    r.children()
+                        .filter(|n| n.tag_name().name() == "Ustrd")
+                        .filter_map(|n| n.text())
+                        .map(|t| t.trim().to_string())
+                        .filter(|t| !t.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let description = if !remittance.is_empty() {
+                Some(remittance.join(" "))
+            } else {
+                single
+                    .and_then(|t| xml_text(t, &["AddtlTxInf"]))
+                    .or_else(|| xml_text(ntry, &["AddtlNtryInf"]))
+            };
+
+            // only the bank's own references are unique; the payer's EndToEndId
+            // repeats (standing orders), so those entries rely on the fingerprint
+            let bank_ref = xml_text(ntry, &["AcctSvcrRef"])
+                .or_else(|| single.and_then(|t| xml_text(t, &["Refs", "AcctSvcrRef"])))
+                .or_else(|| xml_text(ntry, &["NtryRef"]));
+
+            rows.push(ImportRow {
+                line,
+                date,
+                description,
+                amount,
+                category: None,
+                counterparty,
+                counterparty_iban,
+                bank_ref,
+                currency: amt.attribute("Ccy").map(str::to_string),
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(ImportResult {
+        rows,
+        errors,
+        imported: 0,
+        skipped: 0,
+    })
+}
+
+#[tauri::command]
+async fn import_camt(
+    state: State<'_, AppState>,
+    input: StatementImport,
+) -> Result<ImportResult, String> {
+    let text = std::fs::read_to_string(&input.path).map_err(|e| e.to_string())?;
+    let mut out = parse_camt(&text)?;
+
+    let pool = current_pool(&state).await;
+    finish_import(
+        &pool,
+        input.account_id,
+        &mut out,
+        input.skip_lines.as_deref().unwrap_or_default(),
+        input.dry_run.unwrap_or(true),
+    )
+    .await?;
+    Ok(out)
+}
+
+/* ---------- MT940 import (SWIFT .sta) ---------- */
+// Parsed :61: line: (booking date, signed amount, bank reference)
+fn parse_mt940_61(v: &str) -> Result<(String, Money, Option<String>), String> {
+    use chrono::{Datelike, NaiveDate};
+    let first = v.lines().next().unwrap_or("").trim();
+    let leading_digits =
+        |s: &str, n: usize| s.bytes().take(n).filter(u8::is_ascii_digit).count() == n;
+    if !leading_digits(first, 6) {
+        return Err(format!("invalid value date in :61:{first}"));
+    }
+    let value_date = NaiveDate::parse_from_str(&format!("20{}", &first[..6]), "%Y%m%d")
+        .map_err(|_| format!("invalid value date '{}'", &first[..6]))?;
+    let mut rest = &first[6..];
+
+    // optional booking date MMDD (same year, unless it crosses new year)
+    let mut date = value_date;
+    if leading_digits(rest, 4) {
+        let m: u32 = rest[..2].parse().unwrap_or(0);
+        let d: u32 = rest[2..4].parse().unwrap_or(0);
+        let year = match (value_date.month(), m) {
+            (1, 12) => value_date.year() - 1,
+            (12, 1) => value_date.year() + 1,
+            _ => value_date.year(),
+        };
+        date = NaiveDate::from_ymd_opt(year, m, d)
+            .ok_or(format!("invalid booking date '{}'", &rest[..4]))?;
+        rest = &rest[4..];
+    }
+
+    // D / C, or RD / RC for reversals
+    let (negative, r) = if let Some(r) = rest.strip_prefix("RD") {
+        (false, r)
+    } else if let Some(r) = rest.strip_prefix("RC") {
+        (true, r)
+    } else if let Some(r) = rest.strip_prefix('D') {
+        (true, r)
+    } else if let Some(r) = rest.strip_prefix('C') {
+        (false, r)
+    } else {
+        return Err(format!("missing debit/credit mark in :61:{first}"));
+    };
+    rest = r;
+    // optional funds code (third letter of the currency code)
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..];
+    }
+
+    let end = rest.find(|c: char| !(c.is_ascii_digit() || c == ',')).unwrap_or(rest.len());
+    let amount = parse_statement_amount(&rest[..end], ',')
+        .ok_or(format!("invalid amount '{}'", &rest[..end]))?;
+    rest = &rest[end..];
+
+    // transaction type (N/F/S + 3 chars), customer reference, then //bank reference
+    let bank_ref = rest
+        .split_once("//")
+        .map(|(_, b)| b.trim().to_string())
+        .filter(|b| !b.is_empty() && b != "NONREF");
+
+    Ok((
+        date.format("%Y-%m-%d").to_string(),
+        if negative { -amount } else { amount },
+        bank_ref,
+    ))
+}
+
+// :86: with German structured subfields ("166?00GUTSCHRIFT?20Text?32Name"),
+// or free text. Returns (description, counterparty, counterparty IBAN).
+fn parse_mt940_86(v: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let flat: String = v.lines().map(str::trim_end).collect();
+    let mut chars = flat.chars();
+    let structured = flat.len() > 4
+        && chars.by_ref().take(3).all(|c| c.is_ascii_digit())
+        && chars.next().is_some_and(|c| !c.is_ascii_alphanumeric() && c != ' ');
+    if !structured {
+        let text = v.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+        let text = text.trim().to_string();
+        return ((!text.is_empty()).then_some(text), None, None);
+    }
+
+    let sep = flat[3..].chars().next().unwrap_or('?');
+    let mut booking_text = None;
+    let mut purpose = String::new();
+    let mut name = String::new();
+    let mut iban = None;
+    for part in flat[3..].split(sep).skip(1) {
+        if part.len() < 2 || !part.is_char_boundary(2) {
+            continue;
+        }
+        let (code, val) = part.split_at(2);
+        match code {
+            "00" => booking_text = Some(val.trim().to_string()),
+            "20" | "21" | "22" | "23" | "24" | "25" | "26" | "27" | "28" | "29" | "60" | "61"
+            | "62" | "63" => purpose.push_str(val),
+            "31" => iban = Some(val.trim().to_string()),
+            "32" | "33" => name.push_str(val),
+            _ => {}
+        }
+    }
+    let purpose = purpose.trim().to_string();
+    let name = name.trim().to_string();
+    let description = if purpose.is_empty() {
+        booking_text.filter(|t| !t.is_empty())
+    } else {
+        Some(purpose)
+    };
+    (
+        description,
+        (!name.is_empty()).then_some(name),
+        iban.filter(|i| !i.is_empty()),
+    )
+}
+
+fn parse_mt940(text: &str) -> ImportResult {
+    // Split into (tag, value, first line); continuation lines belong to the previous tag.
+    let mut fields: Vec<(String, String, usize)> = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let l = raw.trim_end();
+        let tagged = l
+            .strip_prefix(':')
+            .and_then(|r| r.split_once(':'))
+            .filter(|(tag, _)| {
+                !tag.is_empty() && tag.len() <= 3 && tag.chars().all(|c| c.is_ascii_alphanumeric())
+            });
+        if let Some((tag, val)) = tagged {
+            fields.push((tag.to_string(), val.to_string(), idx + 1));
+        } else if l.starts_with('-') || l.starts_with('{') || l.is_empty() {
+            // end of statement / SWIFT block headers
+            continue;
+        } else if let Some(last) = fields.last_mut() {
+            last.1.push('\n');
+            last.1.push_str(l);
+        }
+    }
+
+    let mut rows: Vec<ImportRow> = Vec::new();
+    let mut errors = Vec::new();
+    let mut currency: Option<String> = None;
+    let mut open_row: Option<usize> = None; // row index still waiting for its :86:
+
+    for (tag, val, line) in fields {
+        match tag.as_str() {
+            "60F" | "60M" => {
+                // C/D + YYMMDD + currency + amount
+                currency = val.get(7..10).map(|c| c.to_ascii_uppercase());
+            }
+            "61" => {
+                open_row = None;
+                match parse_mt940_61(&val) {
+                    Ok((date, amount, bank_ref)) => {
+                        rows.push(ImportRow {
+                            line,
+                            date,
+                            description: None,
+                            amount,
+                            category: None,
+                            counterparty: None,
+                            counterparty_iban: None,
+                            bank_ref,
+                            currency: currency.clone(),
+                            ..Default::default()
+                        });
+                        open_row = Some(rows.len() - 1);
+                    }
+                    Err(message) => errors.push(ImportIssue { line, message }),
+                }
+            }
+            "86" => {
+                if let Some(i) = open_row.take() {
+                    let (description, counterparty, iban) = parse_mt940_86(&val);
+                    let row = &mut rows[i];
+                    row.description = description;
+                    row.counterparty = counterparty;
+                    row.counterparty_iban = iban;
+                }
+            }
+            _ => open_row = None,
+        }
+    }
+
+    ImportResult {
+        rows,
+        errors,
+        imported: 0,
+        skipped: 0,
+    }
+}
+
+#[tauri::command]
+async fn import_mt940(
+    state: State<'_, AppState>,
+    input: StatementImport,
+) -> Result<ImportResult, String> {
+    let text = read_text_guess_encoding(&input.path)?;
+    let mut out = parse_mt940(&text);
+
+    let pool = current_pool(&state).await;
+    finish_import(
+        &pool,
+        input.account_id,
+        &mut out,
+        input.skip_lines.as_deref().unwrap_or_default(),
+        input.dry_run.unwrap_or(true),
+    )
+    .await?;
+    Ok(out)
+}
+
+/* ---------- OFX / QFX and QIF import (other finance apps) ---------- */
+// These files can hold several accounts; each is matched to an existing account
+// by name (case-insensitive) or created on commit.
+#[derive(Debug, Deserialize)]
+struct MultiAccountImport {
+    path: String,
+    account_id: Option<i64>,     // used when the file doesn't name its account
+    date_format: Option<String>, // QIF only, e.g. "%d.%m.%Y" (default: US style)
+    dry_run: Option<bool>,       // default true: only preview
+    skip_lines: Option<Vec<usize>>,
+}
+
+#[derive(Debug, Serialize)]
+struct AccountImport {
+    name: String,
+    account_id: Option<i64>, // None = will be created on commit
+    created: bool,
+    #[serde(flatten)]
+    result: ImportResult,
+}
+
+// One account's worth of parsed rows: (account name from the file, currency, rows)
+type ParsedAccount = (Option<String>, Option<String>, ImportResult);
+
+// "1,234.56" / "1.234,56" / "-12,5": the last separator is decimal unless 3 digits follow it.
+fn parse_guessed_amount(s: &str) -> Option<Money> {
+    let t = s.trim();
+    let decimal_sep = match t.rfind(['.', ',']) {
+        Some(i) if t.len() - i - 1 <= 2 => t.as_bytes()[i] as char,
+        Some(i) if t.as_bytes()[i] == b'.' => ',',
+        _ => '.',
+    };
+    parse_statement_amount(t, decimal_sep)
+}
+
+fn byte_line(text: &str, pos: usize) -> usize {
+    text[..pos].matches('\n').count() + 1
+}
+
+fn ofx_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+// Handles SGML OFX 1.x (unclosed leaf tags) and XML OFX 2.x alike by reading
+// `<TAG>value` pairs in order.
+fn parse_ofx(text: &str) -> Result<Vec<ParsedAccount>, String> {
+    let start = text
+        .find("<OFX>")
+        .ok_or("Not an OFX/QFX file (no <OFX> element).")?;
+
+    let mut out: Vec<ParsedAccount> = Vec::new();
+    let mut acct: Option<String> = None;
+    let mut currency: Option<String> = None;
+    let mut trn: Option<(usize, std::collections::HashMap<String, String>)> = None;
+    let mut cur = ImportResult::default();
+
+    let mut pos = start;
+    while let Some(lt) = text[pos..].find('<') {
+        let tag_start = pos + lt + 1;
+        let Some(gt) = text[tag_start..].find('>') else {
+            break;
+        };
+        let tag = text[tag_start..tag_start + gt].trim().to_ascii_uppercase();
+        let value_start = tag_start + gt + 1;
+        let value_end = text[value_start..]
+            .find('<')
+            .map(|i| value_start + i)
+            .unwrap_or(text.len());
+        let value = ofx_unescape(text[value_start..value_end].trim());
+        pos = value_end;
+
+        match tag.as_str() {
+            "STMTRS" | "CCSTMTRS" => {
+                acct = None;
+                currency = None;
+            }
+            "CURDEF" => currency = Some(value.to_ascii_uppercase()),
+            "ACCTID" if trn.is_none() => acct = Some(value),
+            "STMTTRN" => trn = Some((byte_line(text, tag_start), Default::default())),
+            "/STMTTRN" => {
+                let Some((line, f)) = trn.take() else {
+                    continue;
+                };
+                let get = |k: &str| f.get(k).filter(|v| !v.is_empty()).cloned();
+
+                let raw_date = get("DTPOSTED").unwrap_or_default();
+                let date = raw_date
+                    .get(..8)
+                    .and_then(|d| parse_statement_date(d, Some("%Y%m%d")));
+                let Some(date) = date else {
+                    cur.errors.push(ImportIssue {
+                        line,
+                        message: format!("invalid date '{raw_date}'"),
+                    });
+                    continue;
+                };
+                let raw_amount = get("TRNAMT").unwrap_or_default();
+                let Some(amount) = parse_guessed_amount(&raw_amount) else {
+                    cur.errors.push(ImportIssue {
+                        line,
+                        message: format!("invalid amount '{raw_amount}'"),
+                    });
+                    continue;
+                };
+
+                let name = get("NAME").or_else(|| get("PAYEE"));
+                cur.rows.push(ImportRow {
+                    line,
+                    date,
+                    description: get("MEMO").or_else(|| name.clone()),
+                    amount,
+                    category: None,
+                    counterparty: name,
+                    counterparty_iban: None,
+                    bank_ref: get("FITID"),
+                    currency: get("CURRENCY").or_else(|| currency.clone()),
+                    ..Default::default()
+                });
+            }
+            "/STMTRS" | "/CCSTMTRS" => {
+                out.push((acct.take(), currency.take(), std::mem::take(&mut cur)));
+            }
+            t if !t.starts_with('/') => {
+                if let Some((_, f)) = trn.as_mut() {
+                    f.insert(t.to_string(), value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // SGML files sometimes omit closing aggregates
+    if !cur.rows.is_empty() || !cur.errors.is_empty() {
+        out.push((acct, currency, cur));
+    }
+    Ok(out)
+}
+
+fn parse_qif(text: &str, date_format: Option<&str>) -> Result<Vec<ParsedAccount>, String> {
+    let mut out: Vec<ParsedAccount> = Vec::new();
+    let mut account: Option<String> = None; // last !Account seen
+    let mut rows_account: Option<String> = None; // account of the rows in `cur`
+    let mut in_account_header = false;
+    let mut in_transactions = false;
+    let mut skip_section = false;
+    let mut cur = ImportResult::default();
+
+    // current record: (first line, fields in order)
+    let mut rec: Vec<(char, String)> = Vec::new();
+    let mut rec_line = 0;
+
+    let parse_date = |s: &str| -> Option<String> {
+        // Quicken writes "1/ 5'24" for 2024
+        let s = s.trim().replace(' ', "").replace('\'', "/");
+        match date_format {
+            Some(f) if !f.trim().is_empty() => parse_statement_date(&s, Some(f)),
+            _ => ["%m/%d/%Y", "%m/%d/%y", "%Y-%m-%d", "%d.%m.%Y", "%d.%m.%y"]
+                .iter()
+                .find_map(|f| parse_statement_date(&s, Some(f))),
+        }
+    };
+
+    for (idx, raw) in text.lines().enumerate() {
+        let line = raw.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('!') {
+            let header = header.trim();
+            if header.eq_ignore_ascii_case("Account") {
+                in_account_header = true;
+                in_transactions = false;
+            } else if let Some(kind) = header
+                .get(..5)
+                .filter(|p| p.eq_ignore_ascii_case("Type:"))
+                .and_then(|_| header.get(5..))
+            {
+                // a new transaction list: flush what we had for the previous account
+                if !cur.rows.is_empty() || !cur.errors.is_empty() {
+                    out.push((rows_account.take(), None, std::mem::take(&mut cur)));
+                }
+                rows_account = account.clone();
+                let kind = kind.trim().to_ascii_lowercase();
+                skip_section =
+                    !matches!(kind.as_str(), "bank" | "cash" | "ccard" | "oth a" | "oth l");
+                if skip_section && kind != "cat" && kind != "class" {
+                    cur.errors.push(ImportIssue {
+                        line: idx + 1,
+                        message: format!("section !Type:{kind} is not supported, skipped"),
+                    });
+                }
+                in_account_header = false;
+                in_transactions = !skip_section;
+            }
+            // !Option:AutoSwitch / !Clear:AutoSwitch only toggle account lists
+            continue;
+        }
+
+        if rec.is_empty() {
+            rec_line = idx + 1;
+        }
+        if line != "^" {
+            let mut chars = line.chars();
+            let code = chars.next().unwrap_or(' ');
+            rec.push((code, chars.as_str().trim().to_string()));
+            continue;
+        }
+
+        // end of record
+        let fields = std::mem::take(&mut rec);
+        if in_account_header {
+            if let Some((_, n)) = fields.iter().find(|(c, _)| *c == 'N') {
+                account = Some(n.clone());
+            }
+            continue;
+        }
+        if !in_transactions || skip_section {
+            continue;
+        }
+
+        let field = |c: char| {
+            fields
+                .iter()
+                .find(|(k, _)| *k == c)
+                .map(|(_, v)| v.clone())
+                .filter(|v| !v.is_empty())
+        };
+
+        let raw_date = field('D').unwrap_or_default();
+        let Some(date) = parse_date(&raw_date) else {
+            cur.errors.push(ImportIssue {
+                line: rec_line,
+                message: format!("invalid date '{raw_date}'"),
+            });
+            continue;
+        };
+        let raw_amount = field('T').or_else(|| field('U')).unwrap_or_default();
+        let Some(amount) = parse_guessed_amount(&raw_amount) else {
+            cur.errors.push(ImportIssue {
+                line: rec_line,
+                message: format!("invalid amount '{raw_amount}'"),
+            });
+            continue;
+        };
+
+        // Quicken appends the class to the category ("Food:Groceries/Vacation")
+        let without_class = |v: String| {
+            let v = v.split('/').next().unwrap_or_default().trim().to_string();
+            Some(v).filter(|v| !v.is_empty())
+        };
+
+        // "[Other account]" in L is a transfer; against the own account it's the opening balance
+        let category = match field('L').and_then(without_class) {
+            Some(l) if l.starts_with('[') => {
+                let target = l.trim_matches(|c| c == '[' || c == ']');
+                rows_account
+                    .as_deref()
+                    .filter(|a| a.eq_ignore_ascii_case(target))
+                    .map(|_| "Init".to_string())
+            }
+            other => other,
+        };
+
+        // split lines: S category, E memo, $ amount
+        let mut splits: Vec<SplitInput> = Vec::new();
+        let mut bad_split = None;
+        for (code, v) in &fields {
+            match code {
+                'S' => splits.push(SplitInput {
+                    category: without_class(v.clone()).filter(|c| !c.starts_with('[')),
+                    amount: Money::ZERO,
+                    memo: None,
+                }),
+                'E' => {
+                    if let Some(s) = splits.last_mut() {
+                        s.memo = Some(v.clone()).filter(|m| !m.is_empty());
+                    }
+                }
+                '$' => match (splits.last_mut(), parse_guessed_amount(v)) {
+                    (Some(s), Some(a)) => s.amount = a,
+                    _ => bad_split = Some(format!("invalid split amount '{v}'")),
+                },
+                _ => {}
+            }
+        }
+        splits.retain(|s| s.amount != Money::ZERO);
+        let split_total: Money = splits.iter().map(|s| s.amount).sum();
+        if bad_split.is_none() && !splits.is_empty() && split_total != amount {
+            bad_split = Some(format!(
+                "split lines add up to {:.2}, not {:.2}",
+                split_total.to_f64(),
+                amount.to_f64()
+            ));
+        }
+        if let Some(message) = bad_split {
+            cur.errors.push(ImportIssue {
+                line: rec_line,
+                message,
+            });
+            continue;
+        }
+
+        let payee = field('P');
+        cur.rows.push(ImportRow {
+            line: rec_line,
+            date,
+            description: field('M').or_else(|| payee.clone()),
+            amount,
+            category: if splits.is_empty() { category } else { None },
+            counterparty: payee,
+            counterparty_iban: None,
+            bank_ref: None,
+            currency: None,
+            splits,
+            ..Default::default()
+        });
+    }
+
+    if !cur.rows.is_empty() || !cur.errors.is_empty() {
+        out.push((rows_account, None, cur));
+    }
+    if out.is_empty() {
+        return Err("No QIF transactions found.".into());
+    }
+    Ok(out)
+}
+
+// Match (or, on commit, create) the account for each parsed block and finish the import.
+// All blocks go in one transaction, so a failing block leaves no accounts or rows behind.
+async fn finish_multi_import(
+    pool: &SqlitePool,
+    parsed: Vec<ParsedAccount>,
+    fallback_account: Option<i64>,
+    skip_lines: &[usize],
+    dry_run: bool,
+) -> Result<Vec<AccountImport>, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    for (name, currency, mut result) in parsed {
+        let found: Option<(i64, String)> = match (&name, fallback_account) {
+            (Some(n), _) => sqlx::query_as(
+                "SELECT id, name FROM accounts \
+                 WHERE LOWER(name) = LOWER(?1) AND deleted_at IS NULL ORDER BY id LIMIT 1",
+            )
+            .bind(n.trim())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?,
+            (None, Some(id)) => {
+                sqlx::query_as("SELECT id, name FROM accounts WHERE id = ?1 AND deleted_at IS NULL")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?
+            }
+            (None, None) => {
+                return Err("The file doesn't name its account; choose one to import into.".into())
+            }
+        };
+
+        let (account_id, label, created) = match (found, name) {
+            (Some((id, label)), _) => (Some(id), label, false),
+            (None, Some(n)) if dry_run => (None, n, true),
+            (None, Some(n)) => {
+                let cur = normalize_currency(currency.as_deref())?;
+                let id = sqlx::query(
+                    "INSERT INTO accounts (name, color, type, currency) VALUES (?1, NULL, 'standard', ?2);",
+                )
+                .bind(n.trim())
+                .bind(&cur)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?
+                .last_insert_rowid();
+                (Some(id), n, true)
+            }
+            (None, None) => return Err("The chosen account does not exist.".into()),
+        };
+
+        if let Some(id) = account_id {
+            import_into_account(&mut tx, id, &mut result, skip_lines, dry_run).await?;
+        }
+        out.push(AccountImport {
+            name: label,
+            account_id,
+            created,
+            result,
+        });
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+#[tauri::command]
+async fn import_ofx(
+    state: State<'_, AppState>,
+    input: MultiAccountImport,
+) -> Result<Vec<AccountImport>, String> {
+    let text = read_text_guess_encoding(&input.path)?;
+    let parsed = parse_ofx(&text)?;
+
+    let pool = current_pool(&state).await;
+    finish_multi_import(
+        &pool,
+        parsed,
+        input.account_id,
+        input.skip_lines.as_deref().unwrap_or_default(),
+        input.dry_run.unwrap_or(true),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn import_qif(
+    state: State<'_, AppState>,
+    input: MultiAccountImport,
+) -> Result<Vec<AccountImport>, String> {
+    let text = read_text_guess_encoding(&input.path)?;
+    let parsed = parse_qif(&text, input.date_format.as_deref())?;
+
+    let pool = current_pool(&state).await;
+    finish_multi_import(
+        &pool,
+        parsed,
+        input.account_id,
+        input.skip_lines.as_deref().unwrap_or_default(),
+        input.dry_run.unwrap_or(true),
+    )
+    .await
+}
+
+/* ---------- Categories list (for chooser) ---------- */
+#[tauri::command]
+async fn list_categories(state: State<'_, AppState>) -> Result<Vec<Category>, String> {
+    let pool = current_pool(&state).await;
+
+    let mut items = sqlx::query_as::<_, Category>(
+        "SELECT id, name, parent_id FROM categories WHERE deleted_at IS NULL",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let tree = CategoryTree::load(&pool).await.map_err(|e| e.to_string())?;
+    for it in items.iter_mut() {
+        it.path = tree.path(it.id);
+        it.depth = tree.chain(it.id).len() as i64 - 1;
+    }
+    // parents directly above their children
+    items.sort_by_key(|c| c.path.to_lowercase());
+    Ok(items)
+}
+
+/* ---------- Helpers for search/export ---------- */
+enum BindArg {
+    I(i64),
+    S(String),
+}
+
+// `per_line`: the query joins `transaction_lines l` and `c` is the line's category
+// (sums + exports); otherwise `c` is the parent category and split lines match via EXISTS.
+fn build_where(
+    filters: &TxSearch,
+    where_sql: &mut String,
+    args: &mut Vec<BindArg>,
+    per_line: bool,
+) {
+    let amount_col = if per_line { "l.amount" } else { "t.amount" };
+    where_sql.push_str(" WHERE t.deleted_at IS NULL ");
+    if let Some(acc) = filters.account_id {
+        where_sql.push_str(" AND t.account_id = ? ");
+        args.push(BindArg::I(acc));
+    }
+    if let Some(ref df) = filters.date_from {
+        where_sql.push_str(" AND DATE(t.date) >= DATE(?) ");
+        args.push(BindArg::S(df.clone()));
+    }
+    if let Some(ref dt) = filters.date_to {
+        where_sql.push_str(" AND DATE(t.date) <= DATE(?) ");
+        args.push(BindArg::S(dt.clone()));
+    }
+    if let Some(ref t) = filters.tx_type {
+        match t.as_str() {
+            "income" => where_sql.push_str(&format!(" AND {amount_col} > 0 ")),
+            "expense" => where_sql.push_str(&format!(" AND {amount_col} < 0 ")),
+            "transfer" => where_sql.push_str(" AND t.transfer_id IS NOT NULL "),
+            _ => {}
+        }
+    }
+    if let Some(ref q) = filters.query {
+        let like = format!("%{}%", q.to_lowercase());
+        if per_line {
+            where_sql.push_str(
+                " AND (LOWER(t.description) LIKE ? \
+             OR LOWER(c.name) LIKE ? \
+             OR LOWER(t.counterparty) LIKE ? \
+             OR LOWER(l.memo) LIKE ?) ",
+            );
+            args.push(BindArg::S(like.clone()));
+            args.push(BindArg::S(like.clone()));
+        } else {
+            where_sql.push_str(
+                " AND (LOWER(t.description) LIKE ? \
+             OR LOWER(c.name) LIKE ? \
+             OR LOWER(t.counterparty) LIKE ? \
+             OR EXISTS (SELECT 1 FROM transaction_splits s \
+                        LEFT JOIN categories sc ON sc.id = s.category_id \
+                        WHERE s.transaction_id = t.id \
+                          AND (LOWER(sc.name) LIKE ? OR LOWER(s.memo) LIKE ?))) ",
+            );
+            args.push(BindArg::S(like.clone()));
+            args.push(BindArg::S(like.clone()));
+            args.push(BindArg::S(like.clone()));
+        }
+        args.push(BindArg::S(like.clone()));
+        args.push(BindArg::S(like));
+    }
+    if let Some(ref status) = filters.status {
+        where_sql.push_str(" AND t.status = ? ");
+        args.push(BindArg::S(status.clone()));
+    }
+    if let Some(payee) = filters.payee_id {
+        where_sql.push_str(" AND t.payee_id = ? ");
+        args.push(BindArg::I(payee));
+    }
+    if let Some(cat) = filters.category_id {
+        if per_line {
+            where_sql.push_str(&format!(" AND l.category_id IN ({CATEGORY_SUBTREE}) "));
+        } else {
+            where_sql.push_str(&format!(
+                " AND (t.category_id IN ({CATEGORY_SUBTREE}) \
+                   OR EXISTS (SELECT 1 FROM transaction_splits s \
+                              WHERE s.transaction_id = t.id \
+                                AND s.category_id IN ({CATEGORY_SUBTREE}))) "
+            ));
+            args.push(BindArg::I(cat));
+        }
+        args.push(BindArg::I(cat));
+    }
+    let tags = clean_tag_names(filters.tags.as_deref().unwrap_or_default());
+    if !tags.is_empty() {
+        // tags.name is NOCASE, so IN compares case-insensitively
+        let placeholders = vec!["?"; tags.len()].join(", ");
+        where_sql.push_str(&format!(
+            " AND (SELECT COUNT(*) FROM transaction_tags tt \
+                   JOIN tags g ON g.id = tt.tag_id \
+                   WHERE tt.transaction_id = t.id AND g.name IN ({placeholders})) >= ? "
+        ));
+        let needed = match filters.tag_match.as_deref() {
+            Some("all") => tags.len() as i64,
+            _ => 1,
+        };
+        args.extend(tags.into_iter().map(BindArg::S));
+        args.push(BindArg::I(needed));
+    }
+}
+
+fn build_order(filters: &TxSearch, per_line: bool) -> String {
+    let dir = match filters.sort_dir.as_deref() {
+        Some("desc") => "DESC",
+        _ => "ASC",
+    };
+    let primary = match filters.sort_by.as_deref() {
+        Some("category") => "c.name",
+        Some("description") => "t.description",
+        Some("amount") if per_line => "l.amount",
+        Some("amount") => "t.amount",
+        Some("account") => "a.name",
+        Some("payee") => "(SELECT name FROM payees WHERE id = t.payee_id)",
+        Some("id") => "t.id",
+        _ => "DATE(t.date)", // default
+    };
+    if primary == "t.id" {
+        format!(" ORDER BY t.id {} ", dir)
+    } else {
+        format!(" ORDER BY {} {}, t.id {} ", primary, dir, dir)
+    }
+}
+
+/* ---------- Search & Export commands ---------- */
+#[tauri::command]
+async fn search_transactions(
+    state: tauri::State<'_, AppState>,
+    filters: TxSearch,
+) -> Result<TxSearchResult, String> {
+    let mut where_sql = String::new();
+    let mut args: Vec<BindArg> = Vec::new();
+    build_where(&filters, &mut where_sql, &mut args, false);
+    let order_sql = build_order(&filters, false);
+
+    // Sums work per line so split transactions count towards each category
+    let mut where_lines = String::new();
+    let mut line_args: Vec<BindArg> = Vec::new();
+    build_where(&filters, &mut where_lines, &mut line_args, true);
+
+    // Count first (needed to compute last page offset when offset < 0)
+    let mut sql_count = String::from(
+        "SELECT COUNT(*) FROM transactions t \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = t.category_id",
+    );
+    sql_count.push_str(&where_sql);
+    let mut q_count = sqlx::query_scalar::<_, i64>(&sql_count);
+    for a in &args {
+        match a {
+            BindArg::I(v) => {
+                q_count = q_count.bind(*v);
+            }
+            BindArg::S(s) => {
+                q_count = q_count.bind(s);
+            }
+        }
+    }
+    let pool = current_pool(&state).await;
+
+    let total = q_count.fetch_one(&pool).await.map_err(|e| e.to_string())?;
+
+    let limit = filters.limit.unwrap_or(15).max(0);
+    let req_offset = filters.offset.unwrap_or(-1);
+    let last_offset = if total == 0 || limit == 0 {
+        0
+    } else {
+        ((total - 1) / limit) * limit
+    };
+    let effective_offset = if req_offset < 0 {
+        last_offset
+    } else if req_offset >= total {
+        last_offset
+    } else {
+        req_offset
+    };
+
+    // Items
+    let mut sql_items = String::from(
+        "SELECT t.id, t.account_id, a.name AS account_name, a.color AS account_color, \
+            a.currency, t.date, c.name AS category, t.description, t.amount, t.transfer_id, \
+            t.counterparty, t.counterparty_iban, t.payee_id, p.name AS payee, t.status \
+     FROM transactions t \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = t.category_id \
+     LEFT JOIN payees p ON p.id = t.payee_id",
+    );
+    sql_items.push_str(&where_sql);
+    sql_items.push_str(&order_sql);
+    sql_items.push_str(" LIMIT ? OFFSET ? ");
+
+    let mut q_items = sqlx::query_as::<_, TransactionOut>(&sql_items);
+    for a in &args {
+        match a {
+            BindArg::I(v) => {
+                q_items = q_items.bind(*v);
+            }
+            BindArg::S(s) => {
+                q_items = q_items.bind(s);
+            }
+        }
+    }
+    q_items = q_items.bind(limit).bind(effective_offset);
+    let mut items = q_items.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+    attach_splits(&pool, &mut items)
+        .await
+        .map_err(|e| e.to_string())?;
+    attach_tags(&pool, &mut items)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    /* ---------- Sums (global across all results, not current page) ----------
+       Exclude linked transfer legs because internal transfers don’t change
+       net income/expense, and the 'Init' opening balances (summed separately).
+    */
+    let mut sql_sums = String::from(
+    "SELECT a.currency, \
+       COALESCE(SUM(CASE WHEN l.amount > 0 THEN l.amount END), 0) AS income, \
+       COALESCE(SUM(CASE WHEN l.amount < 0 THEN l.amount END), 0) AS expense, \
+       COALESCE(SUM(CASE WHEN a.type = 'standard'     AND l.amount > 0 THEN l.amount END), 0) AS inc_std, \
+       COALESCE(SUM(CASE WHEN a.type = 'standard'     AND l.amount < 0 THEN l.amount END), 0) AS exp_std, \
+       COALESCE(SUM(CASE WHEN a.type = 'reimbursable' AND l.amount > 0 THEN l.amount END), 0) AS inc_reimb, \
+       COALESCE(SUM(CASE WHEN a.type = 'reimbursable' AND l.amount < 0 THEN l.amount END), 0) AS exp_reimb \
+     FROM transactions t \
+     JOIN transaction_lines l ON l.tx_id = t.id \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = l.category_id"
+  );
+
+    // Start from the same WHERE (filters), then add "not transfer" for sums only
+    let mut where_sums = where_lines.clone();
+    where_sums.push_str(" AND t.transfer_id IS NULL AND COALESCE(LOWER(c.name), '') <> 'init' ");
+
+    sql_sums.push_str(&where_sums);
+    sql_sums.push_str(" GROUP BY a.currency ORDER BY a.currency ");
+
+    let mut q_sums =
+        sqlx::query_as::<_, (String, Money, Money, Money, Money, Money, Money)>(&sql_sums);
+    for a in &line_args {
+        match a {
+            BindArg::I(v) => {
+                q_sums = q_sums.bind(*v);
+            }
+            BindArg::S(s) => {
+                q_sums = q_sums.bind(s);
+            }
+        }
+    }
+
+    let sums_by_cur = q_sums.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+
+    // --- Init sum (only "Init", included in saldo but not in income/expense) ---
+    let mut sql_init = String::from(
+        "SELECT a.currency, COALESCE(SUM(l.amount), 0) \
+     FROM transactions t \
+     JOIN transaction_lines l ON l.tx_id = t.id \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = l.category_id",
+    );
+    let mut where_init = where_lines.clone();
+    where_init.push_str(" AND LOWER(c.name) = 'init' ");
+    sql_init.push_str(&where_init);
+    sql_init.push_str(" GROUP BY a.currency ");
+
+    let mut q_init = sqlx::query_as::<_, (String, Money)>(&sql_init);
+    for a in &line_args {
+        match a {
+            BindArg::I(v) => {
+                q_init = q_init.bind(*v);
+            }
+            BindArg::S(s) => {
+                q_init = q_init.bind(s);
+            }
+        }
+    }
+    let init_by_cur = q_init.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+
+    /* ---------- Convert per-currency sums into the base currency ---------- */
+    let base = normalize_currency(filters.base_currency.as_deref())?;
+    let mut by_currency: Vec<CurrencyTotals> = Vec::new();
+    let mut missing_rates: Vec<String> = Vec::new();
+    let mut totals = [Money::ZERO; 7];
+
+    let mut currencies: Vec<String> = sums_by_cur.iter().map(|r| r.0.clone()).collect();
+    for (cur, _) in &init_by_cur {
+        if !currencies.contains(cur) {
+            currencies.push(cur.clone());
+        }
+    }
+    currencies.sort();
+
+    for cur in currencies {
+        let (inc, exp, inc_std, exp_std, inc_reimb, exp_reimb) = sums_by_cur
+            .iter()
+            .find(|r| r.0 == cur)
+            .map(|r| (r.1, r.2, r.3, r.4, r.5, r.6))
+            .unwrap_or_default();
+        let init = init_by_cur
+            .iter()
+            .find(|r| r.0 == cur)
+            .map(|r| r.1)
+            .unwrap_or_default();
+
+        let rate = find_rate(&pool, &cur, &base, filters.date_to.as_deref())
+            .await
+            .map_err(|e| e.to_string())?;
+        match rate {
+            Some(rate) => {
+                let native = [inc, exp, inc_std, exp_std, inc_reimb, exp_reimb, init];
+                for (acc, v) in totals.iter_mut().zip(native) {
+                    *acc += convert_money(v, rate);
+                }
+            }
+            None => missing_rates.push(cur.clone()),
+        }
+        by_currency.push(CurrencyTotals {
+            currency: cur,
+            sum_income: inc,
+            sum_expense: exp,
+            sum_init: init,
+        });
+    }
+
+    let [sum_income, sum_expense, inc_std, exp_std, inc_reimb, exp_reimb, sum_init] = totals;
+    Ok(TxSearchResult {
+        items,
+        total,
+        offset: effective_offset,
+        sum_income,
+        sum_expense,
+        sum_income_std: inc_std,
+        sum_expense_std: exp_std,
+        sum_income_reimb: inc_reimb,
+        sum_expense_reimb: exp_reimb,
+        sum_init,
+        base_currency: base,
+        by_currency,
+        missing_rates,
+    })
+}
+
+/* ---------- Category totals (per split line, in base currency) ---------- */
+#[derive(Debug, Serialize)]
+struct CategoryTotal {
+    category_id: Option<i64>,
+    category: Option<String>,
+    path: Option<String>, // "Food > Groceries"
+    total: Money,
+}
+
+// `level`: roll subcategories up into their ancestor at this depth (0 = top level);
+// None keeps the categories as booked.
+#[tauri::command]
+async fn category_totals(
+    state: tauri::State<'_, AppState>,
+    filters: TxSearch,
+    level: Option<i64>,
+) -> Result<Vec<CategoryTotal>, String> {
+    let mut where_sql = String::new();
+    let mut args: Vec<BindArg> = Vec::new();
+    build_where(&filters, &mut where_sql, &mut args, true);
+    // transfers and opening balances aren't spending/earning
+    where_sql.push_str(" AND t.transfer_id IS NULL AND COALESCE(LOWER(c.name), '') <> 'init' ");
+
+    let mut sql = String::from(
+        "SELECT a.currency, c.id, c.name, COALESCE(SUM(l.amount), 0) \
+     FROM transactions t \
+     JOIN transaction_lines l ON l.tx_id = t.id \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = l.category_id",
+    );
+    sql.push_str(&where_sql);
+    sql.push_str(" GROUP BY a.currency, c.id ");
+
+    let mut q = sqlx::query_as::<_, (String, Option<i64>, Option<String>, Money)>(&sql);
+    for a in &args {
+        match a {
+            BindArg::I(v) => {
+                q = q.bind(*v);
+            }
+            BindArg::S(s) => {
+                q = q.bind(s);
+            }
+        }
+    }
+    let pool = current_pool(&state).await;
+    let rows = q.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+
+    let base = normalize_currency(filters.base_currency.as_deref())?;
+    let mut rates: std::collections::HashMap<String, Option<f64>> = Default::default();
+    for (cur, ..) in &rows {
+        if !rates.contains_key(cur) {
+            let rate = find_rate(&pool, cur, &base, filters.date_to.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            rates.insert(cur.clone(), rate);
+        }
+    }
+
+    let tree = CategoryTree::load(&pool).await.map_err(|e| e.to_string())?;
+
+    // currencies without a known rate are left out, like in the search totals
+    let mut out: Vec<CategoryTotal> = Vec::new();
+    for (cur, cat_id, name, sum) in rows {
+        let Some(rate) = rates.get(&cur).copied().flatten() else {
+            continue;
+        };
+        let v = convert_money(sum, rate);
+        let cat_id = match (cat_id, level) {
+            (Some(id), Some(l)) => Some(tree.at_level(id, l.max(0) as usize)),
+            _ => cat_id,
+        };
+        match out.iter_mut().find(|c| c.category_id == cat_id) {
+            Some(c) => c.total += v,
+            None => out.push(CategoryTotal {
+                category_id: cat_id,
+                category: cat_id
+                    .and_then(|id| tree.0.get(&id).map(|(_, n)| n.clone()))
+                    .or(name),
+                path: cat_id.map(|id| tree.path(id)),
+                total: v,
+            }),
+        }
+    }
+    out.sort_by(|a, b| a.total.abs().cmp(&b.total.abs()).reverse());
+    Ok(out)
+}
+
+/* ---------- Budgets (budget vs. actual per category) ---------- */
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Budget {
+    id: i64,
+    category_id: i64,
+    category: String,
+    period: String, // "monthly" | "quarterly" | "yearly"
+    amount: Money,  // per period, positive
+    currency: String,
+    rollover: bool, // unspent money carries into the next period
+    start_date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BudgetInput {
+    category: String,
+    period: Option<String>, // default "monthly"
+    amount: Money,
+    currency: Option<String>,   // default EUR
+    rollover: Option<bool>,     // default false
+    start_date: Option<String>, // default: first day of the current month
+}
+
+#[derive(Debug, Serialize)]
+struct BudgetLine {
+    budget_id: i64,
+    category_id: i64,
+    category: String,
+    period: String,
+    budgeted: Money,  // amount × periods in the report range
+    carried: Money,   // unspent money rolled over from before the range
+    actual: Money,    // net spending in the range (refunds reduce it)
+    remaining: Money, // budgeted + carried - actual; negative = overspent
+    overspent: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct BudgetReport {
+    date_from: String,
+    date_to: String,
+    base_currency: String, // all amounts are converted into this
+    lines: Vec<BudgetLine>,
+    total_budgeted: Money,
+    total_actual: Money,
+    total_remaining: Money,
+    missing_rates: Vec<String>, // currencies left out of the converted sums
+}
+
+fn period_months(period: &str) -> Result<i64, String> {
+    match period {
+        "monthly" => Ok(1),
+        "quarterly" => Ok(3),
+        "yearly" => Ok(12),
+        other => Err(format!("Unknown budget period '{other}'")),
+    }
+}
+
+// Calendar period a date falls into, counted from year 0.
+fn period_index(months: i64, d: chrono::NaiveDate) -> i64 {
+    use chrono::Datelike;
+    (d.year() as i64 * 12 + d.month0() as i64).div_euclid(months)
+}
+
+fn month_start(d: chrono::NaiveDate) -> chrono::NaiveDate {
+    use chrono::Datelike;
+    d.with_day(1).unwrap_or(d)
+}
+
+const BUDGET_COLUMNS: &str = r#"
+    b.id, b.category_id, c.name AS category, b.period, b.amount, b.currency,
+    b.rollover, b.start_date
+    FROM budgets b
+    JOIN categories c ON c.id = b.category_id AND c.deleted_at IS NULL
+"#;
+
+async fn compute_budget_report(pool: &SqlitePool, filters: &TxSearch) -> Result<BudgetReport, String> {
+    use chrono::NaiveDate;
+    let base = normalize_currency(filters.base_currency.as_deref())?;
+
+    // default range: the current month
+    let now = today();
+    let from = match filters.date_from.as_deref() {
+        Some(d) => parse_iso_date(d)?,
+        None => month_start(now),
+    };
+    let to = match filters.date_to.as_deref() {
+        Some(d) => parse_iso_date(d)?,
+        None if filters.date_from.is_some() => now,
+        None => {
+            let next = month_start(now) + chrono::Duration::days(32);
+            month_start(next).pred_opt().unwrap_or(now)
+        }
+    };
+    if from > to {
+        return Err("The start date is after the end date.".into());
+    }
+    let iso = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+
+    let budgets = sqlx::query_as::<_, Budget>(&format!("SELECT {BUDGET_COLUMNS} ORDER BY c.name"))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // earliest date rollover needs to look at
+    let mut scan_from = from;
+    for b in budgets.iter().filter(|b| b.rollover) {
+        let start = month_start(parse_iso_date(&b.start_date)?);
+        scan_from = scan_from.min(start);
+    }
+
+    // spending per (category, month, currency), split by whether it's inside the range
+    let mut scan = filters.clone();
+    scan.date_from = Some(iso(scan_from));
+    scan.date_to = Some(iso(to));
+    let mut where_sql = String::new();
+    let mut args: Vec<BindArg> = vec![BindArg::S(iso(from))];
+    build_where(&scan, &mut where_sql, &mut args, true);
+    where_sql.push_str(
+        " AND t.transfer_id IS NULL AND COALESCE(LOWER(c.name), '') <> 'init' \
+          AND l.category_id IN (WITH RECURSIVE sub(id) AS (SELECT category_id FROM budgets \
+            UNION SELECT c2.id FROM categories c2 JOIN sub ON c2.parent_id = sub.id) \
+            SELECT id FROM sub) ",
+    );
+    let mut sql = String::from(
+        "SELECT l.category_id, strftime('%Y-%m-01', t.date), a.currency, \
+                DATE(t.date) >= DATE(?) AS in_range, COALESCE(SUM(l.amount), 0) \
+     FROM transactions t \
+     JOIN transaction_lines l ON l.tx_id = t.id \
+     JOIN accounts a ON a.id = t.account_id \
+     LEFT JOIN categories c ON c.id = l.category_id",
+    );
+    sql.push_str(&where_sql);
+    sql.push_str(" GROUP BY 1, 2, 3, 4 ");
+
+    let mut q = sqlx::query_as::<_, (i64, String, String, bool, Money)>(&sql);
+    for a in &args {
+        match a {
+            BindArg::I(v) => {
+                q = q.bind(*v);
+            }
+            BindArg::S(s) => {
+                q = q.bind(s);
+            }
+        }
+    }
+    let rows = q.fetch_all(pool).await.map_err(|e| e.to_string())?;
+
+    let mut rates: std::collections::HashMap<String, Option<f64>> = Default::default();
+    let currencies = rows
+        .iter()
+        .map(|r| r.2.clone())
+        .chain(budgets.iter().map(|b| b.currency.clone()));
+    for cur in currencies {
+        if let std::collections::hash_map::Entry::Vacant(slot) = rates.entry(cur) {
+            let rate = find_rate(pool, slot.key(), &base, Some(&iso(to)))
+                .await
+                .map_err(|e| e.to_string())?;
+            slot.insert(rate);
+        }
+    }
+    let mut missing_rates: Vec<String> = rates
+        .iter()
+        .filter(|(_, r)| r.is_none())
+        .map(|(c, _)| c.clone())
+        .collect();
+    missing_rates.sort();
+
+    let mut lines = Vec::new();
+    for b in budgets {
+        // a budget on a parent category covers its subcategories too
+        let subtree: std::collections::HashSet<i64> = sqlx::query_scalar(CATEGORY_SUBTREE)
+            .bind(b.category_id)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+        let months = period_months(&b.period)?;
+        let Some(rate) = rates.get(&b.currency).copied().flatten() else {
+            continue;
+        };
+        let amount = convert_money(b.amount, rate);
+        let p_start = period_index(months, parse_iso_date(&b.start_date)?);
+        let (p_from, p_to) = (period_index(months, from), period_index(months, to));
+
+        // spending of this category: inside the range, and per period before it
+        let mut actual = Money::ZERO;
+        let mut before: std::collections::BTreeMap<i64, Money> = Default::default();
+        for (cat, month, cur, in_range, sum) in &rows {
+            if !subtree.contains(cat) {
+                continue;
+            }
+            let Some(r) = rates.get(cur).copied().flatten() else {
+                continue;
+            };
+            let spent = -convert_money(*sum, r);
+            if *in_range {
+                actual += spent;
+            } else if let Ok(m) = parse_iso_date(month) {
+                *before.entry(period_index(months, m)).or_default() += spent;
+            }
+        }
+
+        let mut carried = Money::ZERO;
+        if b.rollover {
+            for p in p_start..p_from {
+                let spent = before.get(&p).copied().unwrap_or_default();
+                carried = (carried + amount - spent).max(Money::ZERO);
+            }
+        }
+        let periods = (p_to - p_from.max(p_start) + 1).max(0);
+        let budgeted = Money(amount.cents() * periods);
+        let remaining = budgeted + carried - actual;
+        lines.push(BudgetLine {
+            budget_id: b.id,
+            category_id: b.category_id,
+            category: b.category,
+            period: b.period,
+            budgeted,
+            carried,
+            actual,
+            remaining,
+            overspent: remaining.is_negative(),
+        });
+    }
+
+    Ok(BudgetReport {
+        date_from: iso(from),
+        date_to: iso(to),
+        base_currency: base,
+        total_budgeted: lines.iter().map(|l| l.budgeted + l.carried).sum(),
+        total_actual: lines.iter().map(|l| l.actual).sum(),
+        total_remaining: lines.iter().map(|l| l.remaining).sum(),
+        lines,
+        missing_rates,
+    })
+}
+
+#[tauri::command]
+async fn list_budgets(state: State<'_, AppState>) -> Result<Vec<Budget>, String> {
+    let pool = current_pool(&state).await;
+    sqlx::query_as::<_, Budget>(&format!("SELECT {BUDGET_COLUMNS} ORDER BY c.name"))
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// One budget per category: setting it again replaces the old one.
+#[tauri::command]
+async fn set_budget(state: State<'_, AppState>, input: BudgetInput) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let period = input.period.as_deref().map(str::trim).unwrap_or("monthly").to_ascii_lowercase();
+    period_months(&period)?;
+    if !input.amount.is_positive() {
+        return Err("The budget amount must be positive.".into());
+    }
+    let currency = normalize_currency(input.currency.as_deref())?;
+    let start = match trimmed(input.start_date) {
+        Some(d) => parse_iso_date(&d)?,
+        None => month_start(today()),
+    };
+    let cat_id = get_or_create_category_id(&pool, Some(input.category))
+        .await?
+        .ok_or("Category name cannot be empty")?;
+
+    sqlx::query(
+        r#"
+    INSERT INTO budgets (category_id, period, amount, currency, rollover, start_date)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+    ON CONFLICT(category_id) DO UPDATE
+      SET period = excluded.period, amount = excluded.amount, currency = excluded.currency,
+          rollover = excluded.rollover, start_date = excluded.start_date;
+    "#,
+    )
+    .bind(cat_id)
+    .bind(&period)
+    .bind(input.amount)
+    .bind(&currency)
+    .bind(input.rollover.unwrap_or(false))
+    .bind(start.format("%Y-%m-%d").to_string())
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_scalar("SELECT id FROM budgets WHERE category_id = ?1")
+        .bind(cat_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_budget(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let res = sqlx::query("DELETE FROM budgets WHERE id = ?1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
+
+// Actuals use the same filters as search_transactions (account, text, type, dates).
+#[tauri::command]
+async fn budget_report(
+    state: State<'_, AppState>,
+    filters: TxSearch,
+) -> Result<BudgetReport, String> {
+    let pool = current_pool(&state).await;
+    compute_budget_report(&pool, &filters).await
+}
+
+#[tauri::command]
+async fn export_budget_report_xlsx(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    filters: TxSearch,
+) -> Result<String, String> {
+    use chrono::Local;
+    use rust_xlsxwriter::{Format, Workbook};
+    let pool = current_pool(&state).await;
+    let report = compute_budget_report(&pool, &filters).await?;
+    let base = report.base_currency.as_str();
+
+    let download_dir = app.path().download_dir().map_err(|_| "No downloads directory")?;
+    let ts = Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let path = download_dir.join(format!("budget_{}.xlsx", ts));
+
+    let mut wb = Workbook::new();
+    let sheet = wb.add_worksheet();
+    let title_fmt = Format::new().set_bold().set_font_size(14);
+    let label_fmt = Format::new().set_bold();
+
+    let info = [
+        ("Time span", format!("{} – {}", iso_to_de(&report.date_from), iso_to_de(&report.date_to))),
+        ("Currency", report.base_currency.clone()),
+        ("Generated", Local::now().format("%d.%m.%Y %H:%M").to_string()),
+    ];
+    sheet
+        .write_string_with_format(0, 0, "Budget report", &title_fmt)
+        .map_err(|e| e.to_string())?;
+    for (i, (label, value)) in info.iter().enumerate() {
+        let row = 1 + i as u32;
+        sheet
+            .write_string_with_format(row, 0, *label, &label_fmt)
+            .map_err(|e| e.to_string())?;
+        sheet
+            .write_string(row, 1, value)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let headers = ["Category", "Period", "Budgeted", "Carried over", "Actual", "Remaining"];
+    let table_row: u32 = 5;
+    let mut col_widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for (c, h) in headers.iter().enumerate() {
+        sheet
+            .write_string_with_format(table_row, c as u16, *h, &label_fmt)
+            .map_err(|e| e.to_string())?;
+    }
+
+    for (i, l) in report.lines.iter().enumerate() {
+        let row = table_row + 1 + i as u32;
+        sheet
+            .write_string(row, 0, &l.category)
+            .map_err(|e| e.to_string())?;
+        sheet
+            .write_string(row, 1, &l.period)
+            .map_err(|e| e.to_string())?;
+        col_widths[0] = col_widths[0].max(l.category.chars().count());
+        for (c, v) in [(2, l.budgeted), (3, l.carried), (4, -l.actual), (5, l.remaining)] {
+            sheet
+                .write_number_with_format(row, c, v.to_f64(), &xlsx_money_format(base, v))
+                .map_err(|e| e.to_string())?;
+            col_widths[c as usize] = col_widths[c as usize].max(display_len_amount(v, base));
+        }
+    }
+
+    let total_row = table_row + 2 + report.lines.len() as u32;
+    sheet
+        .write_string_with_format(total_row, 0, "Total", &label_fmt)
+        .map_err(|e| e.to_string())?;
+    for (c, v) in [
+        (2, report.total_budgeted),
+        (4, -report.total_actual),
+        (5, report.total_remaining),
+    ] {
+        sheet
+            .write_number_with_format(total_row, c, v.to_f64(), &xlsx_money_format(base, v))
+            .map_err(|e| e.to_string())?;
+        col_widths[c as usize] = col_widths[c as usize].max(display_len_amount(v, base));
+    }
+    if !report.missing_rates.is_empty() {
+        sheet
+            .write_string(
+                total_row + 1,
+                0,
+                format!("Left out (no exchange rate): {}", report.missing_rates.join(", ")),
+            )
+            .map_err(|e| e.to_string())?;
+    }
+
+    for (c, w) in col_widths.iter().enumerate() {
+        sheet
+            .set_column_width(c as u16, ((*w as f64) + 2.0).min(60.0))
+            .map_err(|e| e.to_string())?;
+    }
+    wb.save(&path).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn export_budget_report_pdf(
+    app: AppHandle,
+    state: tauri::State<'_, AppState>,
+    filters: TxSearch,
+) -> Result<String, String> {
+    use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument};
+    use std::io::{BufWriter, Cursor};
+    let pool = current_pool(&state).await;
+    let report = compute_budget_report(&pool, &filters).await?;
+    let base = report.base_currency.as_str();
+
+    let download_dir = app.path().download_dir().map_err(|_| "No downloads directory")?;
+    let ts = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let path = download_dir.join(format!("budget_{}.pdf", ts));
+
+    let page_w = Mm(210.0);
+    let page_h = Mm(297.0);
+    let m_l = 14.0;
+    let m_t = 16.0;
+    let m_b = 18.0;
+    let content_w = page_w.0 - 2.0 * m_l;
+    let (doc, page_id, layer_id) = PdfDocument::new("Budget Report", page_w, page_h, "Layer 1");
+
+    fn load_font(
+        doc: &printpdf::PdfDocumentReference,
+        file: &str,
+        fallback: BuiltinFont,
+    ) -> Result<IndirectFontRef, String> {
+        let path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), file);
+        match std::fs::read(&path) {
+            Ok(bytes) => doc
+                .add_external_font(Cursor::new(bytes))
+                .map_err(|e| e.to_string()),
+            Err(_) => doc.add_builtin_font(fallback).map_err(|e| e.to_string()),
+        }
+    }
+    let font_normal = load_font(&doc, "DejaVuSans.ttf", BuiltinFont::Helvetica)?;
+    let font_bold = load_font(&doc, "DejaVuSans-Bold.ttf", BuiltinFont::HelveticaBold)?;
+
+    let (fs_title, fs_meta, fs_head, fs_cell) = (13.0, 9.5, 10.2, 9.7);
+    let (header_h, row_h, pad) = (9.0, 7.2, 1.8);
+
+    let cols: Vec<String> = ["Category", "Period", "Budgeted", "Carried", "Actual", "Remaining"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let mut col_w_mm = vec![0.0, 22.0, 28.0, 26.0, 28.0, 28.0];
+    col_w_mm[0] = content_w - col_w_mm.iter().sum::<f64>();
+
+    let mut layer_ref = doc.get_page(page_id).get_layer(layer_id);
+    let mut y = page_h.0 - m_t;
+
+    draw_text(&layer_ref, &font_bold, "Budget report", m_l, y, fs_title, black());
+    y -= 4.0 + row_h;
+    for meta in [
+        format!("Time span: {} – {}", iso_to_de(&report.date_from), iso_to_de(&report.date_to)),
+        format!("Currency: {}", report.base_currency),
+        format!("Generated: {}", chrono::Local::now().format("%d.%m.%Y %H:%M")),
+    ] {
+        draw_text(&layer_ref, &font_normal, &meta, m_l, y, fs_meta, black());
+        y -= row_h;
+    }
+    y -= 2.0;
+
+    draw_table_header(
+        &layer_ref, &font_bold, m_l, y, content_w, header_h, &cols, &col_w_mm, fs_head, pad,
+    );
+    y -= header_h;
+
+    for (row_idx, l) in report.lines.iter().enumerate() {
+        if y < m_b + row_h * 3.0 {
+            let (np, nl) = doc.add_page(page_w, page_h, "Layer");
+            layer_ref = doc.get_page(np).get_layer(nl);
+            y = page_h.0 - m_t;
+            draw_table_header(
+                &layer_ref, &font_bold, m_l, y, content_w, header_h, &cols, &col_w_mm, fs_head,
+                pad,
+            );
+            y -= header_h;
+        }
+        if row_idx % 2 == 1 {
+            draw_rect(&layer_ref, m_l, y, content_w, row_h, Some(row_alt()), None);
+        }
+
+        let mut x = m_l;
+        let texts = [l.category.clone(), l.period.clone()];
+        for (i, s) in texts.iter().enumerate() {
+            let s = clip_for_width_with_font(&font_normal, s, col_w_mm[i], fs_cell, pad);
+            draw_text(&layer_ref, &font_normal, &s, x + pad, y, fs_cell, black());
+            x += col_w_mm[i];
+        }
+        for (i, v) in [l.budgeted, l.carried, -l.actual, l.remaining].iter().enumerate() {
+            let w = col_w_mm[i + 2];
+            let s = clip_by_max_chars(&format_money_eu(*v, base), w, fs_cell, pad);
+            let color = if v.is_negative() { expense() } else { income() };
+            let rx = text_right_x(x, w, &font_bold, &s, fs_cell, pad);
+            draw_text(&layer_ref, &font_bold, &s, rx, y, fs_cell, color);
+            x += w;
+        }
+        draw_rect(&layer_ref, m_l, y, content_w, 0.1, None, Some((grid(), 0.18)));
+        y -= row_h;
+    }
+
+    // totals band
+    let mut summary = vec![
+        ("Total budgeted".to_string(), report.total_budgeted),
+        ("Total spent".to_string(), -report.total_actual),
+        ("Remaining".to_string(), report.total_remaining),
+    ];
+    if !report.missing_rates.is_empty() {
+        summary.push((
+            format!("Left out (no exchange rate): {}", report.missing_rates.join(", ")),
+            Money::ZERO,
+        ));
+    }
+    let n_lines = summary.len() as f64;
+    if y < m_b + row_h * (n_lines + 1.0) {
+        let (np, nl) = doc.add_page(page_w, page_h, "Layer");
+        layer_ref = doc.get_page(np).get_layer(nl);
+        y = page_h.0 - m_t;
+    }
+    y -= 2.0;
+    draw_rect(
+        &layer_ref,
+        m_l,
+        y,
+        content_w,
+        row_h * n_lines,
+        Some(total_bg()),
+        Some((grid(), 0.3)),
+    );
+    for (i, (label, amount)) in summary.iter().enumerate() {
+        draw_text(&layer_ref, &font_bold, label, m_l + pad, y, fs_head, black());
+        if i < 3 {
+            let value = format_money_eu(*amount, base);
+            let rx = text_right_x(m_l, content_w, &font_bold, &value, fs_head, pad);
+            let col = if amount.is_negative() { expense() } else { income() };
+            draw_text(&layer_ref, &font_bold, &value, rx, y, fs_head, col);
+        }
+        y -= row_h;
+    }
+
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/* ---------- Settings ---------- */
+async fn get_setting(pool: &SqlitePool, key: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT value FROM settings WHERE key = ?1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+}
+
+async fn put_setting(pool: &SqlitePool, key: &str, value: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2) \
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+// "categories" (default: per-category budgets) or "envelopes"
+#[tauri::command]
+async fn get_budget_mode(state: State<'_, AppState>) -> Result<String, String> {
+    let pool = current_pool(&state).await;
+    Ok(get_setting(&pool, "budget_mode")
+        .await
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| "categories".to_string()))
+}
+
+#[tauri::command]
+async fn set_budget_mode(state: State<'_, AppState>, mode: String) -> Result<(), String> {
+    let mode = mode.trim().to_ascii_lowercase();
+    if mode != "categories" && mode != "envelopes" {
+        return Err(format!("Unknown budget mode '{mode}'"));
+    }
+    let pool = current_pool(&state).await;
+    put_setting(&pool, "budget_mode", &mode)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/* ---------- Envelope budgeting ---------- */
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct Envelope {
+    id: i64,
+    name: String,
+    position: i64,
+    #[sqlx(skip)]
+    categories: Vec<String>, // spending in these draws the envelope down
+}
+
+#[derive(Debug, Serialize)]
+struct EnvelopeStatus {
+    id: i64,
+    name: String,
+    carried: Money,  // balance at the end of the previous month (negative = overspent)
+    assigned: Money, // assigned this month
+    moved: Money,    // net moves in (+) / out (-) this month
+    activity: Money, // spending (negative) and refunds this month
+    available: Money, // carried + assigned + moved + activity
+}
+
+#[derive(Debug, Serialize)]
+struct EnvelopeMonth {
+    month: String,              // YYYY-MM
+    currency: String,           // amounts are in this currency
+    income: Money,              // income this month (not in any envelope)
+    available_to_budget: Money, // all income so far minus everything assigned so far
+    envelopes: Vec<EnvelopeStatus>,
+    unassigned_activity: Money, // spending this month in categories without an envelope
+    missing_rates: Vec<String>, // currencies left out
+}
+
+fn parse_month(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    chrono::NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d")
+        .map(|d| d.format("%Y-%m").to_string())
+        .map_err(|_| format!("Invalid month '{s}' (expected YYYY-MM)"))
+}
+
+async fn set_envelope_categories(
+    conn: &mut sqlx::SqliteConnection,
+    envelope_id: i64,
+    categories: &[String],
+) -> Result<(), String> {
+    sqlx::query("UPDATE categories SET envelope_id = NULL WHERE envelope_id = ?1")
+        .bind(envelope_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    for name in categories
+        .iter()
+        .map(|c| c.trim())
+        .filter(|c| !c.is_empty())
+    {
+        sqlx::query("INSERT OR IGNORE INTO categories(name) VALUES (?1)")
+            .bind(name)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        sqlx::query("UPDATE categories SET envelope_id = ?1 WHERE name = ?2 COLLATE NOCASE")
+            .bind(envelope_id)
+            .bind(name)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_envelopes(state: State<'_, AppState>) -> Result<Vec<Envelope>, String> {
+    let pool = current_pool(&state).await;
+    let mut items = sqlx::query_as::<_, Envelope>(
+        "SELECT id, name, position FROM envelopes ORDER BY position, name",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let cats: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT envelope_id, name FROM categories \
+         WHERE envelope_id IS NOT NULL AND deleted_at IS NULL ORDER BY name",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    for it in items.iter_mut() {
+        it.categories = cats
+            .iter()
+            .filter(|(e, _)| *e == it.id)
+            .map(|(_, n)| n.clone())
+            .collect();
+    }
+    Ok(items)
+}
+
+// A category belongs to at most one envelope; listing it here moves it.
+#[tauri::command]
+async fn add_envelope(
+    state: State<'_, AppState>,
+    name: String,
+    categories: Vec<String>,
+) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Envelope name cannot be empty".into());
+    }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let id = sqlx::query(
+        "INSERT INTO envelopes (name, position) \
+         VALUES (?1, (SELECT COALESCE(MAX(position), 0) + 1 FROM envelopes))",
+    )
+    .bind(name)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?
+    .last_insert_rowid();
+    set_envelope_categories(&mut tx, id, &categories).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+async fn update_envelope(
+    state: State<'_, AppState>,
+    id: i64,
+    name: String,
+    categories: Vec<String>,
+) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Envelope name cannot be empty".into());
+    }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let res = sqlx::query("UPDATE envelopes SET name = ?1 WHERE id = ?2")
+        .bind(name)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    if res.rows_affected() > 0 {
+        set_envelope_categories(&mut tx, id, &categories).await?;
     }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(res.rows_affected() > 0)
+}
 
-    let res = sqlx::query("DELETE FROM accounts WHERE id = ?1")
+// Money assigned to a deleted envelope goes back to "available to budget".
+#[tauri::command]
+async fn delete_envelope(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for sql in [
+        "UPDATE categories SET envelope_id = NULL WHERE envelope_id = ?1",
+        "DELETE FROM envelope_assignments WHERE envelope_id = ?1",
+        "DELETE FROM envelope_moves WHERE from_envelope_id = ?1 OR to_envelope_id = ?1",
+    ] {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let res = sqlx::query("DELETE FROM envelopes WHERE id = ?1")
         .bind(id)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
-
+    tx.commit().await.map_err(|e| e.to_string())?;
     Ok(res.rows_affected() > 0)
 }
 
+// Sets (not adds to) the amount assigned to an envelope for one month.
 #[tauri::command]
-async fn update_account(
-    state: tauri::State<'_, AppState>,
-    id: i64,
-    name: Option<String>,
-    color: Option<String>,
-) -> Result<bool, String> {
+async fn assign_to_envelope(
+    state: State<'_, AppState>,
+    envelope_id: i64,
+    month: String,
+    amount: Money,
+) -> Result<(), String> {
     let pool = current_pool(&state).await;
-
-    let res = sqlx::query(
+    let month = parse_month(&month)?;
+    sqlx::query(
         r#"
-    UPDATE accounts
-    SET
-      name  = COALESCE(?1, name),
-      color = COALESCE(?2, color),
-      updated_at = CURRENT_TIMESTAMP
-    WHERE id = ?3;
+    INSERT INTO envelope_assignments (envelope_id, month, amount)
+    SELECT id, ?2, ?3 FROM envelopes WHERE id = ?1
+    ON CONFLICT(envelope_id, month) DO UPDATE SET amount = excluded.amount;
     "#,
     )
-    .bind(name)
-    .bind(color)
-    .bind(id)
+    .bind(envelope_id)
+    .bind(&month)
+    .bind(amount)
     .execute(&pool)
     .await
-    .map_err(|e| e.to_string())?;
-
-    Ok(res.rows_affected() > 0)
+    .map_err(|e| e.to_string())
+    .and_then(|r| {
+        if r.rows_affected() == 0 {
+            Err(format!("Envelope #{envelope_id} does not exist."))
+        } else {
+            Ok(())
+        }
+    })
 }
 
-/* ---------- Categories list (for chooser) ---------- */
 #[tauri::command]
-async fn list_categories(state: State<'_, AppState>) -> Result<Vec<Category>, String> {
+async fn move_between_envelopes(
+    state: State<'_, AppState>,
+    from_envelope_id: i64,
+    to_envelope_id: i64,
+    month: String,
+    amount: Money,
+) -> Result<i64, String> {
     let pool = current_pool(&state).await;
-
-    sqlx::query_as::<_, Category>("SELECT id, name FROM categories ORDER BY name COLLATE NOCASE")
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| e.to_string())
-}
-
-/* ---------- Helpers for search/export ---------- */
-enum BindArg {
-    I(i64),
-    S(String),
-}
-
-fn build_where(filters: &TxSearch, where_sql: &mut String, args: &mut Vec<BindArg>) {
-    where_sql.push_str(" WHERE 1=1 ");
-    if let Some(acc) = filters.account_id {
-        where_sql.push_str(" AND t.account_id = ? ");
-        args.push(BindArg::I(acc));
-    }
-    if let Some(ref df) = filters.date_from {
-        where_sql.push_str(" AND DATE(t.date) >= DATE(?) ");
-        args.push(BindArg::S(df.clone()));
-    }
-    if let Some(ref dt) = filters.date_to {
-        where_sql.push_str(" AND DATE(t.date) <= DATE(?) ");
-        args.push(BindArg::S(dt.clone()));
-    }
-    if let Some(ref t) = filters.tx_type {
-        match t.as_str() {
-            "income" => where_sql.push_str(" AND t.amount > 0 "),
-            "expense" => where_sql.push_str(" AND t.amount < 0 "),
-            _ => {}
-        }
+    let month = parse_month(&month)?;
+    if !amount.is_positive() {
+        return Err("The amount to move must be positive.".into());
     }
-    if let Some(ref q) = filters.query {
-        let like = format!("%{}%", q.to_lowercase());
-        where_sql.push_str(
-            " AND (LOWER(t.description) LIKE ? \
-         OR LOWER(c.name) LIKE ?) ",
-        );
-        args.push(BindArg::S(like.clone()));
-        args.push(BindArg::S(like));
+    if from_envelope_id == to_envelope_id {
+        return Err("Choose two different envelopes.".into());
     }
-}
-
-fn build_order(filters: &TxSearch) -> String {
-    let dir = match filters.sort_dir.as_deref() {
-        Some("desc") => "DESC",
-        _ => "ASC",
-    };
-    let primary = match filters.sort_by.as_deref() {
-        Some("category") => "c.name",
-        Some("description") => "t.description",
-        Some("amount") => "t.amount",
-        Some("account") => "a.name",
-        Some("id") => "t.id",
-        _ => "DATE(t.date)", // default
-    };
-    if primary == "t.id" {
-        format!(" ORDER BY t.id {} ", dir)
-    } else {
-        format!(" ORDER BY {} {}, t.id {} ", primary, dir, dir)
+    let known: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM envelopes WHERE id IN (?1, ?2)")
+        .bind(from_envelope_id)
+        .bind(to_envelope_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if known != 2 {
+        return Err("Envelope does not exist.".into());
     }
+    let res = sqlx::query(
+        "INSERT INTO envelope_moves (month, from_envelope_id, to_envelope_id, amount) \
+         VALUES (?1, ?2, ?3, ?4)",
+    )
+    .bind(&month)
+    .bind(from_envelope_id)
+    .bind(to_envelope_id)
+    .bind(amount)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(res.last_insert_rowid())
 }
 
-/* ---------- Search & Export commands ---------- */
 #[tauri::command]
-async fn search_transactions(
-    state: tauri::State<'_, AppState>,
-    filters: TxSearch,
-) -> Result<TxSearchResult, String> {
-    let mut where_sql = String::new();
-    let mut args: Vec<BindArg> = Vec::new();
-    build_where(&filters, &mut where_sql, &mut args);
-    let order_sql = build_order(&filters);
-
-    // Count first (needed to compute last page offset when offset < 0)
-    let mut sql_count = String::from(
-        "SELECT COUNT(*) FROM transactions t \
-     JOIN accounts a ON a.id = t.account_id \
-     LEFT JOIN categories c ON c.id = t.category_id",
-    );
-    sql_count.push_str(&where_sql);
-    let mut q_count = sqlx::query_scalar::<_, i64>(&sql_count);
-    for a in &args {
-        match a {
-            BindArg::I(v) => {
-                q_count = q_count.bind(*v);
-            }
-            BindArg::S(s) => {
-                q_count = q_count.bind(s);
-            }
-        }
-    }
+async fn envelope_month(
+    state: State<'_, AppState>,
+    month: String,
+    base_currency: Option<String>,
+) -> Result<EnvelopeMonth, String> {
     let pool = current_pool(&state).await;
+    let month = parse_month(&month)?;
+    let base = normalize_currency(base_currency.as_deref())?;
+    let month_end = {
+        let first = parse_iso_date(&format!("{month}-01"))?;
+        let next = month_start(first + chrono::Duration::days(32));
+        next.pred_opt()
+            .unwrap_or(first)
+            .format("%Y-%m-%d")
+            .to_string()
+    };
 
-    let total = q_count.fetch_one(&pool).await.map_err(|e| e.to_string())?;
+    // (month, category, currency, inflow, sum) up to the end of `month`
+    let by_category: Vec<(String, Option<i64>, String, bool, Money)> = sqlx::query_as(
+        r#"
+    SELECT strftime('%Y-%m', t.date), l.category_id, a.currency,
+           (l.amount > 0 OR COALESCE(LOWER(c.name), '') = 'init') AS inflow,
+           COALESCE(SUM(l.amount), 0)
+    FROM transactions t
+    JOIN transaction_lines l ON l.tx_id = t.id
+    JOIN accounts a ON a.id = t.account_id
+    LEFT JOIN categories c ON c.id = l.category_id
+    WHERE t.transfer_id IS NULL AND t.deleted_at IS NULL AND DATE(t.date) <= DATE(?1)
+    GROUP BY 1, 2, 3, 4
+    "#,
+    )
+    .bind(&month_end)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
 
-    let limit = filters.limit.unwrap_or(15).max(0);
-    let req_offset = filters.offset.unwrap_or(-1);
-    let last_offset = if total == 0 || limit == 0 {
-        0
-    } else {
-        ((total - 1) / limit) * limit
-    };
-    let effective_offset = if req_offset < 0 {
-        last_offset
-    } else if req_offset >= total {
-        last_offset
-    } else {
-        req_offset
+    // a subcategory without an envelope of its own spends from its parent's
+    let tree = CategoryTree::load(&pool).await.map_err(|e| e.to_string())?;
+    let own: std::collections::HashMap<i64, i64> =
+        sqlx::query_as("SELECT id, envelope_id FROM categories WHERE envelope_id IS NOT NULL")
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+    let envelope_of = |cat: i64| {
+        tree.chain(cat)
+            .iter()
+            .rev()
+            .find_map(|c| own.get(c).copied())
     };
 
-    // Items
-    let mut sql_items = String::from(
-        "SELECT t.id, t.account_id, a.name AS account_name, a.color AS account_color, \
-            t.date, c.name AS category, t.description, t.amount \
-     FROM transactions t \
-     JOIN accounts a ON a.id = t.account_id \
-     LEFT JOIN categories c ON c.id = t.category_id",
-    );
-    sql_items.push_str(&where_sql);
-    sql_items.push_str(&order_sql);
-    sql_items.push_str(" LIMIT ? OFFSET ? ");
+    // (month, envelope, currency, counts as income, sum);
+    // opening balances count as income, transfers are neither
+    let rows: Vec<(String, Option<i64>, String, bool, Money)> = by_category
+        .into_iter()
+        .map(|(m, cat, cur, inflow, sum)| {
+            let env = cat.and_then(envelope_of);
+            (m, env, cur, env.is_none() && inflow, sum)
+        })
+        .collect();
 
-    let mut q_items = sqlx::query_as::<_, TransactionOut>(&sql_items);
-    for a in &args {
-        match a {
-            BindArg::I(v) => {
-                q_items = q_items.bind(*v);
-            }
-            BindArg::S(s) => {
-                q_items = q_items.bind(s);
-            }
+    let mut rates: std::collections::HashMap<String, Option<f64>> = Default::default();
+    for (_, _, cur, ..) in &rows {
+        if let std::collections::hash_map::Entry::Vacant(slot) = rates.entry(cur.clone()) {
+            let rate = find_rate(&pool, slot.key(), &base, Some(&month_end))
+                .await
+                .map_err(|e| e.to_string())?;
+            slot.insert(rate);
         }
     }
-    q_items = q_items.bind(limit).bind(effective_offset);
-    let items = q_items.fetch_all(&pool).await.map_err(|e| e.to_string())?;
-
-    /* ---------- Sums (global across all results, not current page) ----------
-       Exclude category = 'Transfer' (case-insensitive) because internal transfers
-       don’t change net income/expense.
-    */
-    let mut sql_sums = String::from(
-    "SELECT \
-       COALESCE(SUM(CASE WHEN t.amount > 0 THEN t.amount END), 0.0) AS income, \
-       COALESCE(SUM(CASE WHEN t.amount < 0 THEN t.amount END), 0.0) AS expense, \
-       COALESCE(SUM(CASE WHEN a.type = 'standard'     AND t.amount > 0 THEN t.amount END), 0.0) AS inc_std, \
-       COALESCE(SUM(CASE WHEN a.type = 'standard'     AND t.amount < 0 THEN t.amount END), 0.0) AS exp_std, \
-       COALESCE(SUM(CASE WHEN a.type = 'reimbursable' AND t.amount > 0 THEN t.amount END), 0.0) AS inc_reimb, \
-       COALESCE(SUM(CASE WHEN a.type = 'reimbursable' AND t.amount < 0 THEN t.amount END), 0.0) AS exp_reimb \
-     FROM transactions t \
-     JOIN accounts a ON a.id = t.account_id \
-     LEFT JOIN categories c ON c.id = t.category_id"
-  );
-
-    // Start from the same WHERE (filters), then add "not transfer" for sums only
-    let mut where_sums = where_sql.clone();
-    where_sums.push_str(" AND LOWER(c.name) NOT IN ('transfer', 'init') ");
+    let mut missing_rates: Vec<String> = rates
+        .iter()
+        .filter(|(_, r)| r.is_none())
+        .map(|(c, _)| c.clone())
+        .collect();
+    missing_rates.sort();
 
-    sql_sums.push_str(&where_sums);
+    let assignments: Vec<(i64, String, Money)> = sqlx::query_as(
+        "SELECT envelope_id, month, amount FROM envelope_assignments WHERE month <= ?1",
+    )
+    .bind(&month)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let moves: Vec<(i64, i64, String, Money)> = sqlx::query_as(
+        "SELECT from_envelope_id, to_envelope_id, month, amount FROM envelope_moves WHERE month <= ?1",
+    )
+    .bind(&month)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
 
-    let mut q_sums = sqlx::query_as::<_, (f64, f64, f64, f64, f64, f64)>(&sql_sums);
-    for a in &args {
-        match a {
-            BindArg::I(v) => {
-                q_sums = q_sums.bind(*v);
-            }
-            BindArg::S(s) => {
-                q_sums = q_sums.bind(s);
+    let mut income = Money::ZERO;
+    let mut income_total = Money::ZERO;
+    let mut unassigned_activity = Money::ZERO;
+    for (m, env, cur, is_income, sum) in &rows {
+        let Some(rate) = rates.get(cur).copied().flatten() else {
+            continue;
+        };
+        let v = convert_money(*sum, rate);
+        if *is_income {
+            income_total += v;
+            if *m == month {
+                income += v;
             }
+        } else if env.is_none() && *m == month {
+            unassigned_activity += v;
         }
     }
 
-    let (sum_income, sum_expense, inc_std, exp_std, inc_reimb, exp_reimb) =
-        q_sums.fetch_one(&pool).await.map_err(|e| e.to_string())?;
-
-    // --- Init sum (only "Init", included in saldo but not in income/expense) ---
-    let mut sql_init = String::from(
-        "SELECT COALESCE(SUM(t.amount), 0.0) \
-     FROM transactions t \
-     JOIN accounts a ON a.id = t.account_id \
-     LEFT JOIN categories c ON c.id = t.category_id",
-    );
-    let mut where_init = where_sql.clone();
-    where_init.push_str(" AND LOWER(c.name) = 'init' ");
-    sql_init.push_str(&where_init);
+    let list = sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, name FROM envelopes ORDER BY position, name",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
 
-    let mut q_init = sqlx::query_scalar::<_, f64>(&sql_init);
-    for a in &args {
-        match a {
-            BindArg::I(v) => {
-                q_init = q_init.bind(*v);
-            }
-            BindArg::S(s) => {
-                q_init = q_init.bind(s);
-            }
-        }
+    let mut envelopes = Vec::with_capacity(list.len());
+    for (id, name) in list {
+        // (before this month, this month)
+        let split = |pairs: Vec<(&String, Money)>| -> (Money, Money) {
+            pairs
+                .into_iter()
+                .fold((Money::ZERO, Money::ZERO), |(b, c), (m, v)| {
+                    if *m == month {
+                        (b, c + v)
+                    } else {
+                        (b + v, c)
+                    }
+                })
+        };
+        let (assigned_before, assigned) = split(
+            assignments
+                .iter()
+                .filter(|(e, ..)| *e == id)
+                .map(|(_, m, v)| (m, *v))
+                .collect(),
+        );
+        let (moved_before, moved) = split(
+            moves
+                .iter()
+                .filter_map(|(from, to, m, v)| match (*from == id, *to == id) {
+                    (true, false) => Some((m, -*v)),
+                    (false, true) => Some((m, *v)),
+                    _ => None,
+                })
+                .collect(),
+        );
+        let (activity_before, activity) = split(
+            rows.iter()
+                .filter(|(_, env, ..)| *env == Some(id))
+                .filter_map(|(m, _, cur, _, sum)| {
+                    let rate = rates.get(cur).copied().flatten()?;
+                    Some((m, convert_money(*sum, rate)))
+                })
+                .collect(),
+        );
+        let carried = assigned_before + moved_before + activity_before;
+        envelopes.push(EnvelopeStatus {
+            id,
+            name,
+            carried,
+            assigned,
+            moved,
+            activity,
+            available: carried + assigned + moved + activity,
+        });
     }
-    let sum_init = q_init.fetch_one(&pool).await.map_err(|e| e.to_string())?;
 
-    Ok(TxSearchResult {
-        items,
-        total,
-        offset: effective_offset,
-        sum_income,
-        sum_expense,
-        sum_income_std: inc_std,
-        sum_expense_std: exp_std,
-        sum_income_reimb: inc_reimb,
-        sum_expense_reimb: exp_reimb,
-        sum_init,
+    let assigned_total: Money = assignments.iter().map(|(_, _, v)| *v).sum();
+    Ok(EnvelopeMonth {
+        month,
+        currency: base,
+        income,
+        available_to_budget: income_total - assigned_total,
+        envelopes,
+        unassigned_activity,
+        missing_rates,
     })
 }
 
@@ -608,24 +7478,30 @@ async fn export_transactions_xlsx(
     columns: Option<Vec<String>>,
 ) -> Result<String, String> {
     use chrono::{Datelike, Local, NaiveDate};
-    use rust_xlsxwriter::{Color, ExcelDateTime, Format, Workbook};
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
 
     /* ---------- Build WHERE + ORDER like search_transactions ---------- */
     let mut where_sql = String::new();
     let mut args: Vec<BindArg> = Vec::new();
-    build_where(&filters, &mut where_sql, &mut args);
-    let order_sql = build_order(&filters);
+    build_where(&filters, &mut where_sql, &mut args, true);
+    let order_sql = build_order(&filters, true);
 
     /* ---------- Fetch all matching rows (no paging) ---------- */
     let mut sql = String::from(
         "SELECT t.id, t.account_id, a.name AS account_name, a.color AS account_color, \
-            t.date, c.name AS category, t.description, t.amount \
+            a.currency, t.date, c.name AS category, \
+            CASE WHEN COALESCE(l.memo, '') = '' THEN t.description \
+                 ELSE COALESCE(t.description || ' – ', '') || l.memo END AS description, \
+            l.amount AS amount, t.transfer_id \
      FROM transactions t \
+     JOIN transaction_lines l ON l.tx_id = t.id \
      JOIN accounts a ON a.id = t.account_id \
-     LEFT JOIN categories c ON c.id = t.category_id",
+     LEFT JOIN categories c ON c.id = l.category_id",
     );
+    // one row per split line, kept in split order under the parent
     sql.push_str(&where_sql);
     sql.push_str(&order_sql);
+    sql.push_str(", l.position ");
 
     let mut q = sqlx::query_as::<_, TransactionOut>(&sql);
     for a in &args {
@@ -640,7 +7516,7 @@ async fn export_transactions_xlsx(
     }
     let pool = current_pool(&state).await;
 
-    let items = q.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+    let mut items = q.fetch_all(&pool).await.map_err(|e| e.to_string())?;
 
     /* ---------- Report metadata (Account / Time span / Generated) ---------- */
     // Account label
@@ -696,8 +7572,20 @@ async fn export_transactions_xlsx(
             "amount".into(),
         ];
     }
-    let order = ["date", "account", "category", "description", "amount"];
+    let order = [
+        "date",
+        "account",
+        "category",
+        "tags",
+        "description",
+        "amount",
+    ];
     cols.sort_by_key(|k| order.iter().position(|x| x == &k.as_str()).unwrap_or(999));
+    if cols.iter().any(|c| c == "tags") {
+        attach_tags(&pool, &mut items)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
 
     /* ---------- Workbook + formats ---------- */
     let mut wb = Workbook::new();
@@ -710,26 +7598,6 @@ async fn export_transactions_xlsx(
     // Real Excel dates with fixed display format
     let date_fmt = Format::new().set_num_format("dd.mm.yyyy");
 
-    // Calm money colors + correct numeric pattern (Excel localizes separators in UI)
-    let money_fmt_pos = Format::new()
-        .set_num_format("#,##0.00 \"€\"")
-        .set_font_color(Color::RGB(0x1B5E20));
-    let money_fmt_neg = Format::new()
-        .set_num_format("#,##0.00 \"€\"")
-        .set_font_color(Color::RGB(0xB71C1C));
-    let money_fmt_zero = Format::new()
-        .set_num_format("#,##0.00 \"€\"")
-        .set_font_color(Color::RGB(0x424242));
-    let pick_money_fmt = |v: f64| {
-        if v > 0.0 {
-            &money_fmt_pos
-        } else if v < 0.0 {
-            &money_fmt_neg
-        } else {
-            &money_fmt_zero
-        }
-    };
-
     /* ---------- Info block at top ---------- */
     let mut current_row: u32 = 0;
 
@@ -769,6 +7637,7 @@ async fn export_transactions_xlsx(
             "date" => "Date",
             "account" => "Account",
             "category" => "Category",
+            "tags" => "Tags",
             "description" => "Notes",
             "amount" => "Value",
             _ => key,
@@ -779,23 +7648,13 @@ async fn export_transactions_xlsx(
     }
 
     /* ---------- Autosize helpers ---------- */
-    // Estimate display width for formatted currency like "1,234,567.89 €"
-    fn display_len_amount(v: f64) -> usize {
-        let abs = v.abs();
-        let whole = abs.trunc() as i128;
-        let digits = whole.to_string().len();
-        let groups = if digits > 3 { (digits - 1) / 3 } else { 0 };
-        let sign = if v < 0.0 { 1 } else { 0 };
-        // digits + thousand separators + decimal ".00" + space + € + sign
-        digits + groups + 3 + 2 + sign
-    }
-
     let header_labels: Vec<&str> = cols
         .iter()
         .map(|k| match k.as_str() {
             "date" => "Date",
             "account" => "Account",
             "category" => "Category",
+            "tags" => "Tags",
             "description" => "Notes",
             "amount" => "Value",
             _ => k,
@@ -804,9 +7663,7 @@ async fn export_transactions_xlsx(
     let mut col_widths: Vec<usize> = header_labels.iter().map(|s| s.chars().count()).collect();
 
     /* ---------- Rows + totals ---------- */
-    let mut sum_income: f64 = 0.0;
-    let mut sum_expense: f64 = 0.0;
-    let mut sum_init: f64 = 0.0;
+    let mut sums = CurrencySums::new();
 
     for (r, item) in items.iter().enumerate() {
         let row = table_start_row + 1 + r as u32;
@@ -846,6 +7703,13 @@ async fn export_transactions_xlsx(
                         .map_err(|e| e.to_string())?;
                     col_widths[c] = col_widths[c].max(s.chars().count());
                 }
+                "tags" => {
+                    let s = item.tags.join(", ");
+                    sheet
+                        .write_string(row, c as u16, &s)
+                        .map_err(|e| e.to_string())?;
+                    col_widths[c] = col_widths[c].max(s.chars().count());
+                }
                 "description" => {
                     let s = item.description.as_deref().unwrap_or("");
                     sheet
@@ -854,11 +7718,12 @@ async fn export_transactions_xlsx(
                     col_widths[c] = col_widths[c].max(s.chars().count());
                 }
                 "amount" => {
-                    let fmt = pick_money_fmt(item.amount);
+                    let fmt = xlsx_money_format(&item.currency, item.amount);
                     sheet
-                        .write_number_with_format(row, c as u16, item.amount, fmt)
+                        .write_number_with_format(row, c as u16, item.amount.to_f64(), &fmt)
                         .map_err(|e| e.to_string())?;
-                    col_widths[c] = col_widths[c].max(display_len_amount(item.amount));
+                    col_widths[c] =
+                        col_widths[c].max(display_len_amount(item.amount, &item.currency));
                 }
                 _ => {
                     sheet
@@ -873,73 +7738,60 @@ async fn export_transactions_xlsx(
             .as_deref()
             .map(|s| s.to_ascii_lowercase())
             .unwrap_or_default();
-        let is_transfer = lower_cat == "transfer";
+        let is_transfer = item.transfer_id.is_some();
         let is_init = lower_cat == "init";
 
+        let (sum_income, sum_expense, sum_init) =
+            sums.entry(item.currency.clone()).or_default();
         if is_init {
-            sum_init += item.amount; // <— collect initial balance separately
+            *sum_init += item.amount; // <— collect initial balance separately
         }
         if !is_transfer && !is_init {
-            if item.amount > 0.0 {
-                sum_income += item.amount;
+            if item.amount.is_positive() {
+                *sum_income += item.amount;
             }
-            if item.amount < 0.0 {
-                sum_expense += item.amount;
+            if item.amount.is_negative() {
+                *sum_expense += item.amount;
             }
         }
     }
 
-    /* ---------- Summary ---------- */
+    /* ---------- Summary (native per currency + converted into base) ---------- */
+    let base = normalize_currency(filters.base_currency.as_deref())?;
+    let currencies: Vec<String> = sums.keys().cloned().collect();
+    let (rates, missing_rates) =
+        rates_into_base(&pool, &currencies, &base, filters.date_to.as_deref()).await?;
+    let summary = export_summary_lines(&sums, &base, &rates);
+
     let summary_row_start = table_start_row + 1 + items.len() as u32 + 1;
     let value_col: u16 = (cols.len().saturating_sub(1)) as u16; // last visible column
     let label_col: u16 = 0;
 
-    sheet
-        .write_string_with_format(summary_row_start, label_col, "Total income", &label_fmt)
-        .map_err(|e| e.to_string())?;
-    sheet
-        .write_number_with_format(
-            summary_row_start,
-            value_col,
-            sum_income,
-            pick_money_fmt(sum_income),
-        )
-        .map_err(|e| e.to_string())?;
-    col_widths[value_col as usize] =
-        col_widths[value_col as usize].max(display_len_amount(sum_income));
-
-    sheet
-        .write_string_with_format(
-            summary_row_start + 1,
-            label_col,
-            "Total expenses",
-            &label_fmt,
-        )
-        .map_err(|e| e.to_string())?;
-    sheet
-        .write_number_with_format(
-            summary_row_start + 1,
-            value_col,
-            sum_expense,
-            pick_money_fmt(sum_expense),
-        )
-        .map_err(|e| e.to_string())?;
-    col_widths[value_col as usize] =
-        col_widths[value_col as usize].max(display_len_amount(sum_expense));
-
-    let saldo = sum_init + sum_income + sum_expense;
-    sheet
-        .write_string_with_format(summary_row_start + 2, label_col, "Saldo", &label_fmt)
-        .map_err(|e| e.to_string())?;
-    sheet
-        .write_number_with_format(
-            summary_row_start + 2,
-            value_col,
-            saldo,
-            pick_money_fmt(saldo),
-        )
-        .map_err(|e| e.to_string())?;
-    col_widths[value_col as usize] = col_widths[value_col as usize].max(display_len_amount(saldo));
+    for (i, (label, value, cur)) in summary.iter().enumerate() {
+        let row = summary_row_start + i as u32;
+        sheet
+            .write_string_with_format(row, label_col, label, &label_fmt)
+            .map_err(|e| e.to_string())?;
+        sheet
+            .write_number_with_format(
+                row,
+                value_col,
+                value.to_f64(),
+                &xlsx_money_format(cur, *value),
+            )
+            .map_err(|e| e.to_string())?;
+        col_widths[value_col as usize] =
+            col_widths[value_col as usize].max(display_len_amount(*value, cur));
+    }
+    if !missing_rates.is_empty() {
+        sheet
+            .write_string(
+                summary_row_start + summary.len() as u32,
+                label_col,
+                format!("Left out (no exchange rate): {}", missing_rates.join(", ")),
+            )
+            .map_err(|e| e.to_string())?;
+    }
 
     /* ---------- Autosize columns (use Result to avoid warnings) ---------- */
     for (c, w) in col_widths.iter().enumerate() {
@@ -969,18 +7821,24 @@ async fn export_transactions_pdf(
     /* ---------- fetch rows (respect current filters + sort) ---------- */
     let mut where_sql = String::new();
     let mut args: Vec<BindArg> = Vec::new();
-    build_where(&filters, &mut where_sql, &mut args);
-    let order_sql = build_order(&filters);
+    build_where(&filters, &mut where_sql, &mut args, true);
+    let order_sql = build_order(&filters, true);
 
     let mut sql = String::from(
         "SELECT t.id, t.account_id, a.name AS account_name, a.color AS account_color, \
-            t.date, c.name AS category, t.description, t.amount \
+            a.currency, t.date, c.name AS category, \
+            CASE WHEN COALESCE(l.memo, '') = '' THEN t.description \
+                 ELSE COALESCE(t.description || ' – ', '') || l.memo END AS description, \
+            l.amount AS amount, t.transfer_id \
      FROM transactions t \
+     JOIN transaction_lines l ON l.tx_id = t.id \
      JOIN accounts a ON a.id = t.account_id \
-     LEFT JOIN categories c ON c.id = t.category_id",
+     LEFT JOIN categories c ON c.id = l.category_id",
     );
+    // one row per split line, kept in split order under the parent
     sql.push_str(&where_sql);
     sql.push_str(&order_sql);
+    sql.push_str(", l.position ");
     let pool = current_pool(&state).await;
 
     let mut q = sqlx::query_as::<_, TransactionOut>(&sql);
@@ -994,7 +7852,7 @@ async fn export_transactions_pdf(
             }
         }
     }
-    let items = q.fetch_all(&pool).await.map_err(|e| e.to_string())?;
+    let mut items = q.fetch_all(&pool).await.map_err(|e| e.to_string())?;
 
     /* ---------- metadata strings ---------- */
     let account_label = if let Some(acc_id) = filters.account_id {
@@ -1072,11 +7930,18 @@ async fn export_transactions_pdf(
         ]
     });
 
+    if cols.iter().any(|c| c == "tags") {
+        attach_tags(&pool, &mut items)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
     fn base_width_for(col: &str) -> f64 {
         match col {
             "date" => 24.0,
             "account" => 36.0,
             "category" => 36.0,
+            "tags" => 30.0,
             "amount" => 28.0,
             _ => 24.0,
         }
@@ -1156,9 +8021,7 @@ async fn export_transactions_pdf(
     y -= header_h;
 
     /* ---------- rows ---------- */
-    let mut sum_income: f64 = 0.0;
-    let mut sum_expense: f64 = 0.0;
-    let mut sum_init: f64 = 0.0;
+    let mut sums = CurrencySums::new();
 
     for (row_idx, it) in items.iter().enumerate() {
         // page break (keep some space for summary)
@@ -1204,15 +8067,16 @@ async fn export_transactions_pdf(
             let key = cols[i].as_str();
             if key == "amount" {
                 // SAFEST: left-align inside the cell to guarantee it's inside the box
-                let s_full = format!("{} €", format_amount_eu(it.amount));
+                let s_full = format_money_eu(it.amount, &it.currency);
                 let s = clip_by_max_chars(&s_full, *w, fs_cell, pad);
-                let color = if it.amount < 0.0 { expense() } else { income() };
+                let color = if it.amount.is_negative() { expense() } else { income() };
                 draw_text(&layer_ref, &font_bold, &s, x + pad, y, fs_cell, color);
             } else {
                 let content = match key {
                     "date" => iso_to_de(&it.date),
                     "account" => it.account_name.clone(),
                     "category" => it.category.clone().unwrap_or_default(),
+                    "tags" => it.tags.join(", "),
                     "description" => it.description.clone().unwrap_or_default(),
                     other => other.to_string(),
                 };
@@ -1238,27 +8102,36 @@ async fn export_transactions_pdf(
             .as_deref()
             .map(|s| s.to_ascii_lowercase())
             .unwrap_or_default();
-        let is_transfer = lower == "transfer";
+        let is_transfer = it.transfer_id.is_some();
         let is_init = lower == "init";
 
+        let (sum_income, sum_expense, sum_init) = sums.entry(it.currency.clone()).or_default();
         if is_init {
-            sum_init += it.amount; // <— collect initial balance
+            *sum_init += it.amount; // <— collect initial balance
         }
         if !is_transfer && !is_init {
-            if it.amount > 0.0 {
-                sum_income += it.amount;
+            if it.amount.is_positive() {
+                *sum_income += it.amount;
             }
-            if it.amount < 0.0 {
-                sum_expense += it.amount;
+            if it.amount.is_negative() {
+                *sum_expense += it.amount;
             }
         }
 
         y -= row_h;
     }
 
-    /* ---------- summary ---------- */
-    let saldo = sum_init + sum_income + sum_expense;
-    if y < m_b.0 + (row_h * 4.0) {
+    /* ---------- summary (native per currency + converted into base) ---------- */
+    let base = normalize_currency(filters.base_currency.as_deref())?;
+    let currencies: Vec<String> = sums.keys().cloned().collect();
+    let (rates, missing_rates) =
+        rates_into_base(&pool, &currencies, &base, filters.date_to.as_deref()).await?;
+    let summary = export_summary_lines(&sums, &base, &rates);
+    let missing_note = (!missing_rates.is_empty())
+        .then(|| format!("Left out (no exchange rate): {}", missing_rates.join(", ")));
+
+    let n_lines = (summary.len() + usize::from(missing_note.is_some())) as f64;
+    if y < m_b.0 + (row_h * (n_lines + 1.0)) {
         let (np, nl) = doc.add_page(page_w, page_h, "Layer");
         page = np;
         layer = nl;
@@ -1272,32 +8145,13 @@ async fn export_transactions_pdf(
         m_l.0,
         y,
         content_w,
-        row_h * 3.0,
+        row_h * n_lines,
         Some(total_bg()),
         Some((grid(), 0.3)),
     );
 
-    // income
-    {
-        let label = "Total income";
-        let value = format!("{} €", format_amount_eu(sum_income));
-        draw_text(
-            &layer_ref,
-            &font_bold,
-            label,
-            m_l.0 + pad,
-            y,
-            fs_head,
-            black(),
-        );
-        let rx = text_right_x(m_l.0, content_w, &font_bold, &value, fs_head, pad);
-        draw_text(&layer_ref, &font_bold, &value, rx, y, fs_head, income());
-        y -= row_h;
-    }
-    // expenses
-    {
-        let label = "Total expenses";
-        let value = format!("{} €", format_amount_eu(sum_expense));
+    for (label, amount, cur) in &summary {
+        let value = format_money_eu(*amount, cur);
         draw_text(
             &layer_ref,
             &font_bold,
@@ -1308,25 +8162,16 @@ async fn export_transactions_pdf(
             black(),
         );
         let rx = text_right_x(m_l.0, content_w, &font_bold, &value, fs_head, pad);
-        draw_text(&layer_ref, &font_bold, &value, rx, y, fs_head, expense());
+        let col = if amount.is_negative() || label.starts_with("Total expenses") {
+            expense()
+        } else {
+            income()
+        };
+        draw_text(&layer_ref, &font_bold, &value, rx, y, fs_head, col);
         y -= row_h;
     }
-    // saldo
-    {
-        let label = "Saldo";
-        let value = format!("{} €", format_amount_eu(saldo));
-        draw_text(
-            &layer_ref,
-            &font_bold,
-            label,
-            m_l.0 + pad,
-            y,
-            fs_head,
-            black(),
-        );
-        let rx = text_right_x(m_l.0, content_w, &font_bold, &value, fs_head, pad);
-        let s_col = if saldo < 0.0 { expense() } else { income() };
-        draw_text(&layer_ref, &font_bold, &value, rx, y, fs_head, s_col);
+    if let Some(note) = &missing_note {
+        draw_text(&layer_ref, &font_bold, note, m_l.0 + pad, y, fs_head, black());
     }
 
     // save
@@ -1449,6 +8294,7 @@ fn draw_table_header(
             "date" => "Date",
             "account" => "Account",
             "category" => "Category",
+            "tags" => "Tags",
             "description" => "Notes",
             "amount" => "Value",
             other => other,
@@ -1473,13 +8319,11 @@ fn iso_to_de(iso: &str) -> String {
 }
 
 // 1.234,56 with sign (no currency symbol)
-fn format_amount_eu(v: f64) -> String {
-    let sign = if v < 0.0 { "-" } else { "" };
-    let n = (v.abs() * 100.0).round() / 100.0;
-    let s = format!("{:.2}", n);
-    let parts = s.split('.').collect::<Vec<_>>();
-    let mut int = parts[0].to_string();
-    let frac = parts.get(1).copied().unwrap_or("00");
+fn format_amount_eu(v: Money) -> String {
+    let sign = if v.is_negative() { "-" } else { "" };
+    let abs = v.cents().unsigned_abs();
+    let mut int = (abs / 100).to_string();
+    let frac = format!("{:02}", abs % 100);
     let mut out = String::new();
     while int.len() > 3 {
         let rest = int.split_off(int.len() - 3);
@@ -1489,6 +8333,74 @@ fn format_amount_eu(v: f64) -> String {
     format!("{}{},{}", sign, out, frac)
 }
 
+// 1.234,56 € / 1.234,56 CHF
+fn format_money_eu(v: Money, currency: &str) -> String {
+    format!("{} {}", format_amount_eu(v), currency_symbol(currency))
+}
+
+/* ---- export totals shared by XLSX + PDF ---- */
+
+// currency -> (income, expense, init)
+type CurrencySums = std::collections::BTreeMap<String, (Money, Money, Money)>;
+
+// Summary footer lines (label, value, currency). A single base-currency export keeps the
+// classic three lines; mixed exports list native totals first, then the converted ones.
+fn export_summary_lines(
+    sums: &CurrencySums,
+    base: &str,
+    rates: &std::collections::HashMap<String, f64>,
+) -> Vec<(String, Money, String)> {
+    let mut lines = Vec::new();
+    let mixed = sums.keys().any(|c| c != base);
+
+    let (mut inc_b, mut exp_b, mut init_b) = (Money::ZERO, Money::ZERO, Money::ZERO);
+    for (cur, (inc, exp, init)) in sums {
+        if mixed {
+            lines.push((format!("Total income ({cur})"), *inc, cur.clone()));
+            lines.push((format!("Total expenses ({cur})"), *exp, cur.clone()));
+            lines.push((format!("Saldo ({cur})"), *init + *inc + *exp, cur.clone()));
+        }
+        // currencies without a rate stay out of the converted totals
+        let Some(&rate) = rates.get(cur) else {
+            continue;
+        };
+        inc_b += convert_money(*inc, rate);
+        exp_b += convert_money(*exp, rate);
+        init_b += convert_money(*init, rate);
+    }
+
+    let suffix = if mixed { format!(" ({base}, converted)") } else { String::new() };
+    lines.push((format!("Total income{suffix}"), inc_b, base.to_string()));
+    lines.push((format!("Total expenses{suffix}"), exp_b, base.to_string()));
+    lines.push((format!("Saldo{suffix}"), init_b + inc_b + exp_b, base.to_string()));
+    lines
+}
+
+// Calm money colors + numeric pattern with the currency symbol
+// (Excel localizes separators in UI)
+fn xlsx_money_format(currency: &str, v: Money) -> rust_xlsxwriter::Format {
+    let color = if v.is_positive() {
+        0x1B5E20
+    } else if v.is_negative() {
+        0xB71C1C
+    } else {
+        0x424242
+    };
+    rust_xlsxwriter::Format::new()
+        .set_num_format(format!("#,##0.00 \"{}\"", currency_symbol(currency)))
+        .set_font_color(rust_xlsxwriter::Color::RGB(color))
+}
+
+// Estimate display width for formatted currency like "1,234,567.89 €"
+fn display_len_amount(v: Money, currency: &str) -> usize {
+    let whole = v.abs().cents() / 100;
+    let digits = whole.to_string().len();
+    let groups = if digits > 3 { (digits - 1) / 3 } else { 0 };
+    let sign = if v.is_negative() { 1 } else { 0 };
+    // digits + thousand separators + decimal ".00" + space + symbol + sign
+    digits + groups + 3 + 1 + currency_symbol(currency).chars().count() + sign
+}
+
 /* ---- conservative clipping & right-edge placement for summary ---- */
 
 // VERY conservative char-based clip so content never spills out of a column.
@@ -1602,28 +8514,30 @@ fn clip_for_width_with_font(
 ///
 /// Returns:
 /// - account_name
+/// - account currency (ISO code)
 /// - current_balance (final running sum over all tx)
 /// - carry_at_cut (>=0): positive balance at the cut point that must be applied to subsequent expenses
 /// - slice_oldest_first: transactions *after* the cut, in the natural order (oldest → newest)
 async fn compute_reimbursable_slice(
     pool: &SqlitePool,
     account_id: i64,
-) -> Result<(String, f64, f64, Vec<TransactionOut>), String> {
+) -> Result<(String, String, Money, Money, Vec<TransactionOut>), String> {
     // Ensure account exists + type + current balance
-    let (acc_name, acc_type, _balance): (String, String, f64) = sqlx::query_as(
-        r#"
-        SELECT a.name, a.type, COALESCE(SUM(t.amount), 0.0) AS balance
+    let (acc_name, acc_type, acc_currency, _balance): (String, String, String, Money) =
+        sqlx::query_as(
+            r#"
+        SELECT a.name, a.type, a.currency, COALESCE(SUM(t.amount), 0) AS balance
         FROM accounts a
-        LEFT JOIN transactions t ON t.account_id = a.id
+        LEFT JOIN transactions t ON t.account_id = a.id AND t.deleted_at IS NULL
         WHERE a.id = ?1
         GROUP BY a.id
         "#,
-    )
-    .bind(account_id)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| e.to_string())?
-    .ok_or_else(|| "Account not found".to_string())?;
+        )
+        .bind(account_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Account not found".to_string())?;
 
     if acc_type.to_lowercase() != "reimbursable" {
         return Err("This export requires a reimbursable account".into());
@@ -1634,11 +8548,11 @@ async fn compute_reimbursable_slice(
         r#"
         SELECT
           t.id, t.account_id, a.name AS account_name, a.color AS account_color,
-          t.date, c.name AS category, t.description, t.amount
+          a.currency, t.date, c.name AS category, t.description, t.amount, t.transfer_id
         FROM transactions t
         JOIN accounts a ON a.id = t.account_id
         LEFT JOIN categories c ON c.id = t.category_id
-        WHERE t.account_id = ?1
+        WHERE t.account_id = ?1 AND t.deleted_at IS NULL
         ORDER BY DATE(t.date) ASC, t.id ASC
         "#,
     )
@@ -1648,12 +8562,12 @@ async fn compute_reimbursable_slice(
     .map_err(|e| e.to_string())?;
 
     // Running balance to find the last moment the balance was >= 0
-    let mut running = 0.0f64;
+    let mut running = Money::ZERO;
     let mut last_non_neg_idx: isize = -1;
-    let mut carry_at_cut: f64 = 0.0;
+    let mut carry_at_cut = Money::ZERO;
     for (i, it) in oldest_first.iter().enumerate() {
         running += it.amount;
-        if running >= 0.0 {
+        if !running.is_negative() {
             last_non_neg_idx = i as isize;
             carry_at_cut = running; // could be > 0
         }
@@ -1671,6 +8585,7 @@ let slice_oldest_first: Vec<TransactionOut> = if start_idx < oldest_first.len()
 
     Ok((
         acc_name,
+        acc_currency,
         running, /*current_balance*/
         carry_at_cut,
         slice_oldest_first,
@@ -1685,13 +8600,13 @@ async fn export_reimbursable_report_xlsx(
     columns: Option<Vec<String>>,
 ) -> Result<String, String> {
     use chrono::{Datelike, Local, NaiveDate};
-    use rust_xlsxwriter::{Color, ExcelDateTime, Format, Workbook};
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
     let pool = current_pool(&state).await;
 
     let acc_id = filters
         .account_id
         .ok_or("Filter to a reimbursable account first")?;
-    let (account_label, _current_balance, carry_at_cut, items_oldest) =
+    let (account_label, currency, _current_balance, carry_at_cut, items_oldest) =
         compute_reimbursable_slice(&pool, acc_id).await?;
 
     // Columns (stable order)
@@ -1721,50 +8636,50 @@ use std::collections::VecDeque;
 
 struct RowRef<'a> {
     it: &'a TransactionOut,
-    adj_amount: f64,
+    adj_amount: Money,
     partial_note: Option<String>,
 }
 
 struct Open<'a> {
     it: &'a TransactionOut,
     // positive numbers: amount still outstanding for this expense
-    remaining: f64,
-    original: f64,
+    remaining: Money,
+    original: Money,
 }
 
 let mut open: VecDeque<Open<'_>> = VecDeque::new();
 
 // reimbursements that occurred before the slice (if any)
-let mut pre = carry_at_cut.max(0.0);
+let mut pre = carry_at_cut.max(Money::ZERO);
 
 for it in &items_oldest {
-    if it.amount < 0.0 {
+    if it.amount.is_negative() {
         // expense
-        let mut rem = (-it.amount).max(0.0);
+        let mut rem = -it.amount;
 
         // apply pre-slice carry to earliest expenses
-        if pre > 0.0 {
+        if pre.is_positive() {
             let apply = pre.min(rem);
             rem -= apply;
             pre -= apply;
         }
 
-        if rem > 1e-9 {
+        if rem.is_positive() {
             open.push_back(Open {
                 it,
                 remaining: rem,
-                original: (-it.amount).max(0.0),
+                original: -it.amount,
             });
         }
-    } else if it.amount > 0.0 {
+    } else if it.amount.is_positive() {
         // reimbursement (+) cancels oldest open expense first
         let mut payoff = it.amount;
-        while payoff > 1e-9 {
+        while payoff.is_positive() {
             if let Some(front) = open.front_mut() {
                 let apply = payoff.min(front.remaining);
                 front.remaining -= apply;
                 payoff -= apply;
-                if front.remaining <= 1e-9 {
+                if !front.remaining.is_positive() {
                     open.pop_front(); // fully covered
                 }
             } else {
@@ -1780,11 +8695,11 @@ for it in &items_oldest {
 let mut rows: Vec<RowRef<'_>> = Vec::with_capacity(open.len());
 for o in open.iter() {
     let adj = -o.remaining; // negative value to write
-    let partial_note = if (o.remaining + 1e-9) < o.original {
+    let partial_note = if o.remaining < o.original {
         Some(format!(
-            "(partial: {} € of {} €)",
-            format_amount_eu(o.remaining),
-            format_amount_eu(o.original)
+            "(partial: {} of {})",
+            format_money_eu(o.remaining, &currency),
+            format_money_eu(o.original, &currency)
         ))
     } else {
         None
@@ -1838,24 +8753,6 @@ for o in open.iter() {
     let header_fmt = Format::new().set_bold();
     let date_fmt = Format::new().set_num_format("dd.mm.yyyy");
 
-    let money_fmt_pos = Format::new()
-        .set_num_format("#,##0.00 \"€\"")
-        .set_font_color(Color::RGB(0x1B5E20));
-    let money_fmt_neg = Format::new()
-        .set_num_format("#,##0.00 \"€\"")
-        .set_font_color(Color::RGB(0xB71C1C));
-    let money_fmt_zero = Format::new()
-        .set_num_format("#,##0.00 \"€\"")
-        .set_font_color(Color::RGB(0x424242));
-    let pick_money_fmt = |v: f64| {
-        if v > 0.0 {
-            &money_fmt_pos
-        } else if v < 0.0 {
-            &money_fmt_neg
-        } else {
-            &money_fmt_zero
-        }
-    };
 
     let generated_at = Local::now().format("%d.%m.%Y %H:%M").to_string();
     let mut current_row: u32 = 0;
@@ -1906,14 +8803,6 @@ for o in open.iter() {
     }
 
     // Autosize helpers
-    fn display_len_amount(v: f64) -> usize {
-        let abs = v.abs();
-        let whole = abs.trunc() as i128;
-        let digits = whole.to_string().len();
-        let groups = if digits > 3 { (digits - 1) / 3 } else { 0 };
-        let sign = if v < 0.0 { 1 } else { 0 };
-        digits + groups + 3 + 2 + sign
-    }
     let header_labels: Vec<&str> = cols
         .iter()
         .map(|k| match k.as_str() {
@@ -1928,7 +8817,7 @@ for o in open.iter() {
     let mut col_widths: Vec<usize> = header_labels.iter().map(|s| s.chars().count()).collect();
 
     // Rows + single TOTAL at end
-    let mut total_outstanding = 0.0f64; // will be <= 0.0
+    let mut total_outstanding = Money::ZERO; // will be <= 0
 
     for (r_idx, row) in rows.iter().enumerate() {
         let rownum = table_start_row + 1 + r_idx as u32;
@@ -1982,11 +8871,11 @@ for o in open.iter() {
                 }
                 "amount" => {
                     let v = row.adj_amount;
-                    let fmt = pick_money_fmt(v);
+                    let fmt = xlsx_money_format(&currency, v);
                     sheet
-                        .write_number_with_format(rownum, c as u16, v, fmt)
+                        .write_number_with_format(rownum, c as u16, v.to_f64(), &fmt)
                         .map_err(|e| e.to_string())?;
-                    col_widths[c] = col_widths[c].max(display_len_amount(v));
+                    col_widths[c] = col_widths[c].max(display_len_amount(v, &currency));
                 }
                 _ => {
                     sheet
@@ -2011,12 +8900,12 @@ for o in open.iter() {
         .write_number_with_format(
             total_row,
             value_col,
-            total_outstanding,
-            pick_money_fmt(total_outstanding),
+            total_outstanding.to_f64(),
+            &xlsx_money_format(&currency, total_outstanding),
         )
         .map_err(|e| e.to_string())?;
     col_widths[value_col as usize] =
-        col_widths[value_col as usize].max(display_len_amount(total_outstanding));
+        col_widths[value_col as usize].max(display_len_amount(total_outstanding, &currency));
 
     // Autosize
     for (c, w) in col_widths.iter().enumerate() {
@@ -2036,8 +8925,9 @@ async fn export_reimbursable_report_pdf(
     state: tauri::State<'_, AppState>,
     filters: TxSearch,
     columns: Option<Vec<String>>,
+    include_receipts: Option<bool>, // one extra page per attachment of the listed expenses
 ) -> Result<String, String> {
-    use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument};
+    use printpdf::{BuiltinFont, ImageTransform, IndirectFontRef, Mm, PdfDocument};
     use std::fs::File;
     use std::io::{BufWriter, Cursor};
     let pool = current_pool(&state).await;
@@ -2045,7 +8935,7 @@ async fn export_reimbursable_report_pdf(
     let acc_id = filters
         .account_id
         .ok_or("Filter to a reimbursable account first")?;
-    let (account_label, _current_balance, carry_at_cut, items_oldest) =
+    let (account_label, currency, _current_balance, carry_at_cut, items_oldest) =
         compute_reimbursable_slice(&pool, acc_id).await?;
 
     // Columns
@@ -2064,42 +8954,42 @@ use std::collections::VecDeque;
 
 struct RowRef<'a> {
     it: &'a TransactionOut,
-    adj_amount: f64,
+    adj_amount: Money,
     desc: String,
 }
 
 struct Open<'a> {
     it: &'a TransactionOut,
-    remaining: f64, // positive outstanding
-    original: f64,  // positive original size
+    remaining: Money, // positive outstanding
+    original: Money,  // positive original size
 }
 
 let mut open: VecDeque<Open<'_>> = VecDeque::new();
-let mut pre = carry_at_cut.max(0.0);
+let mut pre = carry_at_cut.max(Money::ZERO);
 
 for it in &items_oldest {
-    if it.amount < 0.0 {
-        let mut rem = (-it.amount).max(0.0);
-        if pre > 0.0 {
+    if it.amount.is_negative() {
+        let mut rem = -it.amount;
+        if pre.is_positive() {
             let apply = pre.min(rem);
             rem -= apply;
             pre -= apply;
         }
-        if rem > 1e-9 {
+        if rem.is_positive() {
             open.push_back(Open {
                 it,
                 remaining: rem,
-                original: (-it.amount).max(0.0),
+                original: -it.amount,
             });
         }
-    } else if it.amount > 0.0 {
+    } else if it.amount.is_positive() {
         let mut payoff = it.amount;
-        while payoff > 1e-9 {
+        while payoff.is_positive() {
             if let Some(front) = open.front_mut() {
                 let apply = payoff.min(front.remaining);
                 front.remaining -= apply;
                 payoff -= apply;
-                if front.remaining <= 1e-9 {
+                if !front.remaining.is_positive() {
                     open.pop_front();
                 }
             } else {
@@ -2114,11 +9004,11 @@ for it in &items_oldest {
 let mut rows: Vec<RowRef<'_>> = Vec::with_capacity(open.len());
 for o in open.iter() {
     let mut desc = o.it.description.as_deref().unwrap_or("").to_string();
-    if (o.remaining + 1e-9) < o.original {
+    if o.remaining < o.original {
         let note = format!(
-            "(partial: {} € of {} €)",
-            format_amount_eu(o.remaining),
-            format_amount_eu(o.original)
+            "(partial: {} of {})",
+            format_money_eu(o.remaining, &currency),
+            format_money_eu(o.original, &currency)
         );
         desc = if desc.is_empty() { note } else { format!("{desc} {note}") };
     }
@@ -2280,7 +9170,7 @@ for o in open.iter() {
     y -= header_h;
 
     // rows
-    let mut total_outstanding: f64 = 0.0;
+    let mut total_outstanding = Money::ZERO;
 
     for (row_idx, row) in rows.iter().enumerate() {
         if y < m_b.0 + (row_h * 3.0) {
@@ -2323,9 +9213,9 @@ for o in open.iter() {
         for (i, w) in col_w_mm.iter().enumerate() {
             let key = cols[i].as_str();
             if key == "amount" {
-                let s_full = format!("{} €", format_amount_eu(row.adj_amount));
+                let s_full = format_money_eu(row.adj_amount, &currency);
                 let s = clip_by_max_chars(&s_full, *w, fs_cell, pad);
-                let color = if row.adj_amount < 0.0 {
+                let color = if row.adj_amount.is_negative() {
                     expense()
                 } else {
                     income()
@@ -2380,7 +9270,7 @@ for o in open.iter() {
     );
 
     let label = "Total";
-    let value = format!("{} €", format_amount_eu(total_outstanding));
+    let value = format_money_eu(total_outstanding, &currency);
     draw_text(
         &layer_ref,
         &font_bold,
@@ -2391,33 +9281,129 @@ for o in open.iter() {
         black(),
     );
     let rx = text_right_x(m_l.0, content_w, &font_bold, &value, fs_head, pad);
-    let col = if total_outstanding < 0.0 {
+    let col = if total_outstanding.is_negative() {
         expense()
     } else {
         income()
     };
     draw_text(&layer_ref, &font_bold, &value, rx, y, fs_head, col);
 
+    // --- Receipts, in the order of the rows ---
+    if include_receipts.unwrap_or(false) {
+        for row in &rows {
+            let receipts: Vec<(String, String, Vec<u8>)> = sqlx::query_as(
+                "SELECT filename, mime_type, data FROM attachments \
+                 WHERE transaction_id = ?1 ORDER BY id",
+            )
+            .bind(row.it.id)
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+            for (filename, mime_type, data) in receipts {
+                let (np, nl) = doc.add_page(page_w, page_h, "Receipt");
+                layer_ref = doc.get_page(np).get_layer(nl);
+                let mut y = page_h.0 - m_t.0;
+
+                let title = format!("Receipt: {} – {}", iso_to_de(&row.it.date), row.desc);
+                let title = clip_for_width_with_font(&font_bold, &title, content_w, fs_head, 0.0);
+                draw_text(&layer_ref, &font_bold, &title, m_l.0, y, fs_head, black());
+                y -= row_h;
+                let meta = format!(
+                    "{} · {}",
+                    filename,
+                    format_money_eu(row.adj_amount, &currency)
+                );
+                draw_text(&layer_ref, &font_normal, &meta, m_l.0, y, fs_meta, black());
+                y -= row_h + 2.0;
+
+                match jpeg_to_pdf_image(&data) {
+                    Some(image) => {
+                        // fit into the rest of the page, keeping the aspect ratio
+                        let dpi = 300.0;
+                        let w_mm = image.image.width.0 as f64 * 25.4 / dpi;
+                        let h_mm = image.image.height.0 as f64 * 25.4 / dpi;
+                        let scale = (content_w / w_mm).min((y - m_b.0) / h_mm);
+                        image.add_to_layer(
+                            layer_ref.clone(),
+                            ImageTransform {
+                                translate_x: Some(Mm(m_l.0 + (content_w - w_mm * scale) / 2.0)),
+                                translate_y: Some(Mm(y - h_mm * scale)),
+                                scale_x: Some(scale),
+                                scale_y: Some(scale),
+                                dpi: Some(dpi),
+                                ..Default::default()
+                            },
+                        );
+                    }
+                    None => {
+                        let note = format!(
+                            "This file ({mime_type}) can't be shown here; \
+                             it is stored with the transaction in the app."
+                        );
+                        draw_text(&layer_ref, &font_normal, &note, m_l.0, y, fs_meta, black());
+                    }
+                }
+            }
+        }
+    }
+
     let file = File::create(&path).map_err(|e| e.to_string())?;
     doc.save(&mut BufWriter::new(file))
         .map_err(|e| e.to_string())?;
     Ok(path.to_string_lossy().to_string())
 }
 
+// An existing category of the same name is returned as is (its parent is not changed);
+// one in the trash is restored under `parent_id`.
 #[tauri::command]
-async fn add_category(state: State<'_, AppState>, name: String) -> Result<i64, String> {
+async fn add_category(
+    state: State<'_, AppState>,
+    name: String,
+    parent_id: Option<i64>,
+) -> Result<i64, String> {
     let pool = current_pool(&state).await;
 
     let name = name.trim();
     if name.is_empty() {
         return Err("Category name cannot be empty".into());
     }
+    if let Some(pid) = parent_id {
+        check_category_exists(&pool, pid).await?;
+    }
     // Insert (ignore duplicates), then fetch id case-insensitively
-    sqlx::query("INSERT OR IGNORE INTO categories(name) VALUES (?)")
-        .bind(name)
-        .execute(&pool)
-        .await
-        .map_err(|e| e.to_string())?;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let trashed: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM categories WHERE name = ?1 COLLATE NOCASE AND deleted_at IS NOT NULL",
+    )
+    .bind(name)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let changes = if let Some(id) = trashed {
+        let before = audit_before(&mut tx, "category", &[id]).await?;
+        sqlx::query("UPDATE categories SET deleted_at = NULL, parent_id = ?1 WHERE id = ?2")
+            .bind(parent_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        write_audit(&mut tx, "category", before).await?
+    } else {
+        let res = sqlx::query("INSERT OR IGNORE INTO categories(name, parent_id) VALUES (?, ?)")
+            .bind(name)
+            .bind(parent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if res.rows_affected() > 0 {
+            write_audit(&mut tx, "category", vec![(res.last_insert_rowid(), None)]).await?
+        } else {
+            Vec::new()
+        }
+    };
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Add category", changes).await;
 
     let rec =
         sqlx::query_scalar::<_, i64>("SELECT id FROM categories WHERE name = ? COLLATE NOCASE")
@@ -2441,33 +9427,260 @@ async fn update_category(
     if name.is_empty() {
         return Err("Category name cannot be empty".into());
     }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "category", &[id]).await?;
     let res = sqlx::query("UPDATE categories SET name = ? WHERE id = ?")
         .bind(name)
         .bind(id)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
+    let changes = write_audit(&mut tx, "category", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Rename category", changes).await;
     Ok(res.rows_affected() > 0)
 }
 
+// Accounts in the trash don't take new transactions.
+async fn check_account_exists(pool: &SqlitePool, id: i64) -> Result<(), String> {
+    let found: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM accounts WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    found
+        .map(|_| ())
+        .ok_or_else(|| format!("Account #{id} does not exist."))
+}
+
+async fn check_category_exists(pool: &SqlitePool, id: i64) -> Result<(), String> {
+    let found: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM categories WHERE id = ?1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    found
+        .map(|_| ())
+        .ok_or_else(|| format!("Category #{id} does not exist."))
+}
+
+// Moves a category (with its subcategories) under another parent, or to the
+// top level with `parent_id: None`. Transactions keep their category id.
 #[tauri::command]
-async fn delete_category(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+async fn move_category(
+    state: State<'_, AppState>,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<bool, String> {
     let pool = current_pool(&state).await;
-
-    // Only allow delete when not referenced by transactions
-    let cnt: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE category_id = ?")
+    if let Some(pid) = parent_id {
+        check_category_exists(&pool, pid).await?;
+        let tree = CategoryTree::load(&pool).await.map_err(|e| e.to_string())?;
+        if tree.chain(pid).contains(&id) {
+            return Err(
+                "A category cannot be moved into itself or one of its subcategories.".into(),
+            );
+        }
+    }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "category", &[id]).await?;
+    let res = sqlx::query("UPDATE categories SET parent_id = ?1 WHERE id = ?2")
+        .bind(parent_id)
         .bind(id)
-        .fetch_one(&pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
-    if cnt > 0 {
-        return Err("Category is in use by one or more transactions.".into());
+    let changes = write_audit(&mut tx, "category", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Move category", changes).await;
+    Ok(res.rows_affected() > 0)
+}
+
+// Folds the source categories into `target_id`: transactions, split lines, rules,
+// recurring transactions and subcategories move over, then the sources are deleted.
+// A source budget is kept only if the target has none; the same goes for the envelope.
+// Returns the number of transactions that changed category.
+#[tauri::command]
+async fn merge_categories(
+    state: State<'_, AppState>,
+    source_ids: Vec<i64>,
+    target_id: i64,
+) -> Result<i64, String> {
+    let pool = current_pool(&state).await;
+    let mut sources = source_ids;
+    sources.sort_unstable();
+    sources.dedup();
+    sources.retain(|id| *id != target_id);
+    if sources.is_empty() {
+        return Err("Choose at least one category to merge into the target.".into());
     }
-    let res = sqlx::query("DELETE FROM categories WHERE id = ?")
-        .bind(id)
-        .execute(&pool)
+    check_category_exists(&pool, target_id).await?;
+    for id in &sources {
+        check_category_exists(&pool, *id).await?;
+    }
+
+    // the target may sit below a source: its remaining ancestors keep their order
+    let tree = CategoryTree::load(&pool).await.map_err(|e| e.to_string())?;
+    let mut target_chain: Vec<i64> = tree.chain(target_id);
+    target_chain.retain(|id| !sources.contains(id));
+
+    let placeholders = vec!["?"; sources.len()].join(", ");
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let affected_sql = format!(
+        "SELECT t.id FROM transactions t \
+         WHERE t.category_id IN ({placeholders}) \
+            OR EXISTS (SELECT 1 FROM transaction_splits s \
+                       WHERE s.transaction_id = t.id AND s.category_id IN ({placeholders}))"
+    );
+    let mut q = sqlx::query_scalar::<_, i64>(&affected_sql);
+    for id in sources.iter().chain(sources.iter()) {
+        q = q.bind(*id);
+    }
+    let affected_ids = q.fetch_all(&mut *tx).await.map_err(|e| e.to_string())?;
+    // subcategories and the target's ancestors may be re-linked too
+    let category_ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM categories")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tx_before = audit_before(&mut tx, "transaction", &affected_ids).await?;
+    let cat_before = audit_before(&mut tx, "category", &category_ids).await?;
+
+    // keep at most one budget (unique per category) and the target's envelope
+    let has_budget: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM budgets WHERE category_id = ?1")
+            .bind(target_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    if has_budget.is_none() {
+        let sql = format!(
+            "UPDATE budgets SET category_id = ? WHERE id = \
+             (SELECT MIN(id) FROM budgets WHERE category_id IN ({placeholders}))"
+        );
+        let mut q = sqlx::query(&sql).bind(target_id);
+        for id in &sources {
+            q = q.bind(*id);
+        }
+        q.execute(&mut *tx).await.map_err(|e| e.to_string())?;
+    }
+    let sql = format!(
+        "UPDATE categories SET envelope_id = \
+           (SELECT envelope_id FROM categories \
+            WHERE id IN ({placeholders}) AND envelope_id IS NOT NULL ORDER BY id LIMIT 1) \
+         WHERE id = ? AND envelope_id IS NULL"
+    );
+    let mut q = sqlx::query(&sql);
+    for id in &sources {
+        q = q.bind(*id);
+    }
+    q.bind(target_id)
+        .execute(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
+
+    for (table, column) in [
+        ("transactions", "category_id"),
+        ("transaction_splits", "category_id"),
+        ("rules", "category_id"),
+        ("recurring_transactions", "category_id"),
+        ("recurring_occurrences", "category_id"),
+        ("categories", "parent_id"),
+    ] {
+        let sql = format!("UPDATE {table} SET {column} = ? WHERE {column} IN ({placeholders})");
+        let mut q = sqlx::query(&sql).bind(target_id);
+        for id in &sources {
+            q = q.bind(*id);
+        }
+        q.execute(&mut *tx).await.map_err(|e| e.to_string())?;
+    }
+    if target_chain.len() < tree.chain(target_id).len() {
+        let mut parent: Option<i64> = None;
+        for id in &target_chain {
+            sqlx::query("UPDATE categories SET parent_id = ?1 WHERE id = ?2")
+                .bind(parent)
+                .bind(*id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            parent = Some(*id);
+        }
+    }
+    for (table, column) in [("budgets", "category_id"), ("categories", "id")] {
+        let sql = format!("DELETE FROM {table} WHERE {column} IN ({placeholders})");
+        let mut q = sqlx::query(&sql);
+        for id in &sources {
+            q = q.bind(*id);
+        }
+        q.execute(&mut *tx).await.map_err(|e| e.to_string())?;
+    }
+    let mut changes = write_audit(&mut tx, "transaction", tx_before).await?;
+    changes.extend(write_audit(&mut tx, "category", cat_before).await?);
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Merge categories", changes).await;
+    Ok(affected_ids.len() as i64)
+}
+
+// Subcategories of a deleted category move up one level.
+#[tauri::command]
+async fn delete_category(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let pool = current_pool(&state).await;
+
+    // Only allow delete when not referenced by transactions (trashed ones don't count)
+    let cnt: i64 = sqlx::query_scalar(
+        "SELECT (SELECT COUNT(*) FROM transactions WHERE category_id = ?1 AND deleted_at IS NULL) \
+              + (SELECT COUNT(*) FROM transaction_splits s \
+                 JOIN transactions t ON t.id = s.transaction_id \
+                 WHERE s.category_id = ?1 AND t.deleted_at IS NULL)",
+    )
+    .bind(id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if cnt > 0 {
+        return Err("Category is in use by one or more transactions.".into());
+    }
+    let other_uses: i64 = sqlx::query_scalar(
+        "SELECT (SELECT COUNT(*) FROM rules WHERE category_id = ?1) \
+              + (SELECT COUNT(*) FROM recurring_transactions WHERE category_id = ?1)",
+    )
+    .bind(id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if other_uses > 0 {
+        return Err("Category is used by one or more rules or recurring transactions.".into());
+    }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let touched: Vec<i64> =
+        sqlx::query_scalar("SELECT id FROM categories WHERE id = ?1 OR parent_id = ?1")
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    let before = audit_before(&mut tx, "category", &touched).await?;
+    // budgets stay until the trash is purged
+    sqlx::query(
+        "UPDATE categories SET parent_id = (SELECT parent_id FROM categories WHERE id = ?1) \
+         WHERE parent_id = ?1",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let res = sqlx::query(
+        "UPDATE categories SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    let changes = write_audit(&mut tx, "category", before).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    record_undo(&state, "Delete category", changes).await;
     Ok(res.rows_affected() > 0)
 }
 
@@ -2476,7 +9689,7 @@ async fn delete_category(state: State<'_, AppState>, id: i64) -> Result<bool, St
 struct TxMini {
     account_id: i64,
     date: String, // YYYY-MM-DD
-    amount: f64,
+    amount: Money,
 }
 
 #[tauri::command]
@@ -2486,6 +9699,7 @@ async fn list_transactions_all(state: tauri::State<'_, AppState>) -> Result<Vec<
         r#"
     SELECT t.account_id, t.date, t.amount
     FROM transactions t
+    WHERE t.deleted_at IS NULL
     ORDER BY DATE(t.date) ASC, t.id ASC
     "#,
     )
@@ -2494,16 +9708,231 @@ async fn list_transactions_all(state: tauri::State<'_, AppState>) -> Result<Vec<
     .map_err(|e| e.to_string())
 }
 
-//---------------------------------------
+//---------------------------------------
+
+// add:
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// replace your current AppState with:
+#[derive(Clone)]
+struct AppState {
+    pool: Arc<RwLock<SqlitePool>>,
+    journal: Arc<tokio::sync::Mutex<Journal>>, // undo/redo for the open database
+}
+
+/* ---------- Cipher settings ---------- */
+// SQLCipher parameters besides the key. Unset fields are the SQLCipher 4 defaults;
+// a database with other settings keeps them in a `<db>.cipher` sidecar (JSON, no
+// secrets) so they can be applied before the first read.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct CipherSettings {
+    kdf_iter: Option<u32>,          // PBKDF2 iterations, default 256000
+    page_size: Option<u32>,         // cipher_page_size, default 4096
+    hmac_algorithm: Option<String>, // HMAC_SHA1 | HMAC_SHA256 | HMAC_SHA512 (default)
+}
+
+// SQLCipher 3's default; anything lower is weaker than what any current release writes.
+const MIN_KDF_ITER: u32 = 64_000;
+
+impl CipherSettings {
+    // Checks the values and drops the ones that equal the defaults.
+    fn validated(self) -> Result<Self, String> {
+        let kdf_iter = match self.kdf_iter {
+            Some(n) if n < MIN_KDF_ITER => {
+                return Err(format!("Use at least {MIN_KDF_ITER} KDF iterations."))
+            }
+            n => n.filter(|n| *n != 256_000),
+        };
+        let page_size = match self.page_size {
+            Some(n) if !(512..=65_536).contains(&n) || !n.is_power_of_two() => {
+                return Err(format!(
+                    "Page size must be a power of two between 512 and 65536, not {n}."
+                ))
+            }
+            n => n.filter(|n| *n != 4096),
+        };
+        let hmac_algorithm = match trimmed(self.hmac_algorithm) {
+            Some(a) => {
+                let a = a.to_ascii_uppercase();
+                let a = if a.starts_with("HMAC_") { a } else { format!("HMAC_{a}") };
+                if !["HMAC_SHA1", "HMAC_SHA256", "HMAC_SHA512"].contains(&a.as_str()) {
+                    return Err(format!("Unknown HMAC algorithm '{a}'"));
+                }
+                Some(a).filter(|a| a != "HMAC_SHA512")
+            }
+            None => None,
+        };
+        Ok(CipherSettings {
+            kdf_iter,
+            page_size,
+            hmac_algorithm,
+        })
+    }
+
+    // For a connection keyed with `key`.
+    fn apply(&self, opts: SqliteConnectOptions) -> SqliteConnectOptions {
+        let mut opts = match self.kdf_iter {
+            // sqlx sends kdf_iter before cipher_compatibility, which would reset it
+            Some(n) => opts.pragma("kdf_iter", n.to_string()),
+            None => opts.pragma("cipher_compatibility", "4"), // DB Browser defaults
+        };
+        if let Some(n) = self.page_size {
+            opts = opts.pragma("cipher_page_size", n.to_string());
+        }
+        if let Some(a) = &self.hmac_algorithm {
+            opts = opts.pragma("cipher_hmac_algorithm", a.clone());
+        }
+        opts
+    }
+
+    // For a database attached as `schema` (before anything is written to it).
+    fn pragmas(&self, schema: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(n) = self.kdf_iter {
+            out.push(format!("PRAGMA {schema}.kdf_iter = {n}"));
+        }
+        if let Some(n) = self.page_size {
+            out.push(format!("PRAGMA {schema}.cipher_page_size = {n}"));
+        }
+        if let Some(a) = &self.hmac_algorithm {
+            out.push(format!("PRAGMA {schema}.cipher_hmac_algorithm = {a}"));
+        }
+        out
+    }
+}
+
+fn cipher_sidecar_path(db_path: &str) -> String {
+    format!("{db_path}.cipher")
+}
+
+fn read_cipher_settings(db_path: &str) -> Result<CipherSettings, String> {
+    match std::fs::read_to_string(cipher_sidecar_path(db_path)) {
+        Ok(s) => serde_json::from_str(&s).map_err(|e| format!("Unreadable cipher settings: {e}")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(CipherSettings::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Default settings need no sidecar, so a stale one is removed.
+fn write_cipher_settings(db_path: &str, cipher: &CipherSettings) -> Result<(), String> {
+    let path = cipher_sidecar_path(db_path);
+    if *cipher == CipherSettings::default() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        };
+    }
+    let json = serde_json::to_string_pretty(cipher).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Writing {path} failed: {e}"))
+}
+
+#[tauri::command]
+async fn get_cipher_settings(state: State<'_, AppState>) -> Result<CipherSettings, String> {
+    let pool = current_pool(&state).await;
+    read_cipher_settings(&open_database_path(&pool).await?)
+}
 
-// add:
-use std::sync::Arc;
-use tokio::sync::RwLock;
+// Renames a database file together with its `.cipher` sidecar; a sidecar left at
+// `to` from before is removed so it can't describe the wrong file.
+fn move_with_sidecar(from: &str, to: &str) -> std::io::Result<()> {
+    std::fs::rename(from, to)?;
+    let (from_sidecar, to_sidecar) = (cipher_sidecar_path(from), cipher_sidecar_path(to));
+    if Path::new(&from_sidecar).exists() {
+        std::fs::rename(&from_sidecar, &to_sidecar)
+    } else {
+        match std::fs::remove_file(&to_sidecar) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
 
-// replace your current AppState with:
-#[derive(Clone)]
-struct AppState {
-    pool: Arc<RwLock<SqlitePool>>,
+// Opens `db_path` with its sidecar settings and checks the key before handing the pool out.
+async fn reopen_pool(db_path: &str, passphrase: &str) -> Result<SqlitePool, String> {
+    let cipher = read_cipher_settings(db_path)?;
+    let pool = build_encrypted_pool(db_path, passphrase, &cipher)
+        .await
+        .map_err(|e| map_notadb(&e.to_string(), db_path))?;
+    if let Err(e) = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM sqlite_master;")
+        .fetch_one(&pool)
+        .await
+    {
+        pool.close().await;
+        return Err(map_notadb(&e.to_string(), db_path));
+    }
+    let _ = sqlx::query("PRAGMA foreign_keys = ON;")
+        .execute(&pool)
+        .await;
+    let _ = sqlx::query("PRAGMA journal_mode = WAL;")
+        .execute(&pool)
+        .await;
+    Ok(pool)
+}
+
+// Rewrites the open database with new cipher settings (same passphrase): exports
+// it next to the original with its own sidecar, then swaps both in. The original
+// is kept as `<db>.bak` until the new file has opened, and put back otherwise.
+// The undo journal stays valid.
+#[tauri::command]
+async fn reencrypt_database(
+    state: State<'_, AppState>,
+    passphrase: String,
+    cipher: CipherSettings,
+) -> Result<(), String> {
+    let cipher = cipher.validated()?;
+    // no other command gets the pool until the new one is in place
+    let mut slot = state.pool.write().await;
+    let db_path = open_database_path(&slot).await?;
+    check_passphrase(&db_path, &passphrase).await?;
+
+    let tmp = format!("{db_path}.reencrypt");
+    let backup = format!("{db_path}.bak");
+    let discard = |path: &str| {
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(cipher_sidecar_path(path));
+    };
+    discard(&tmp);
+    sqlcipher_export(&slot, &tmp, &passphrase, &cipher).await?;
+    let prepared = match write_cipher_settings(&tmp, &cipher) {
+        Ok(()) => check_passphrase(&tmp, &passphrase).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = prepared {
+        discard(&tmp);
+        return Err(format!("Re-encrypting failed: {e}"));
+    }
+
+    // closing checkpoints the WAL into the old file
+    slot.close().await;
+    discard(&backup);
+    if let Err(e) = move_with_sidecar(&db_path, &backup) {
+        discard(&tmp);
+        *slot = reopen_pool(&db_path, &passphrase).await?;
+        return Err(format!("Replacing the database failed: {e}"));
+    }
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{db_path}{suffix}"));
+    }
+    let opened = match move_with_sidecar(&tmp, &db_path) {
+        Ok(()) => reopen_pool(&db_path, &passphrase).await,
+        Err(e) => Err(format!("Replacing the database failed: {e}")),
+    };
+    match opened {
+        Ok(pool) => {
+            discard(&backup);
+            *slot = pool;
+            Ok(())
+        }
+        Err(e) => {
+            discard(&tmp);
+            discard(&db_path);
+            move_with_sidecar(&backup, &db_path)
+                .map_err(|r| format!("{e}; restoring {backup} failed: {r}"))?;
+            *slot = reopen_pool(&db_path, &passphrase).await?;
+            Err(e)
+        }
+    }
 }
 
 // helper: clone the current pool inside any command
@@ -2511,13 +9940,22 @@ async fn current_pool(state: &State<'_, AppState>) -> SqlitePool {
     state.pool.read().await.clone()
 }
 
-async fn build_encrypted_pool(db_path: &str, passphrase: &str) -> Result<SqlitePool, sqlx::Error> {
-    let pass_owned = passphrase.to_owned(); // must be owned
+// Passphrases go into key pragmas, which can't take bound parameters.
+fn sql_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+async fn build_encrypted_pool(
+    db_path: &str,
+    passphrase: &str,
+    cipher: &CipherSettings,
+) -> Result<SqlitePool, sqlx::Error> {
+    let pass_owned = sql_literal(passphrase); // must be owned
     let opts = SqliteConnectOptions::new()
         .filename(db_path)
         .create_if_missing(false)
-        .pragma("key", pass_owned) // FIRST thing that runs
-        .pragma("cipher_compatibility", "4"); // DB Browser defaults
+        .pragma("key", pass_owned); // FIRST thing that runs
+    let opts = cipher.apply(opts);
 
     SqlitePoolOptions::new()
         .max_connections(5)
@@ -2530,14 +9968,16 @@ async fn create_database(
     state: State<'_, AppState>,
     db_path: String,
     passphrase: String,
+    cipher: Option<CipherSettings>,
 ) -> Result<(), String> {
+    let cipher = cipher.unwrap_or_default().validated()?;
     // Create + key
-    let pass_owned = passphrase.clone();
+    let pass_owned = sql_literal(&passphrase);
     let opts = SqliteConnectOptions::new()
         .filename(&db_path)
         .create_if_missing(true)
-        .pragma("key", pass_owned)
-        .pragma("cipher_compatibility", "4");
+        .pragma("key", pass_owned);
+    let opts = cipher.apply(opts);
 
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
@@ -2558,7 +9998,9 @@ async fn create_database(
         .run(&pool)
         .await
         .map_err(|e| e.to_string())?;
+    write_cipher_settings(&db_path, &cipher)?;
     *state.pool.write().await = pool;
+    *state.journal.lock().await = Journal::default();
     Ok(())
 }
 
@@ -2600,7 +10042,8 @@ async fn open_database(
     }
 
     // Connect with key first
-    let pool = match build_encrypted_pool(&db_path, &passphrase).await {
+    let cipher = read_cipher_settings(&db_path)?;
+    let pool = match build_encrypted_pool(&db_path, &passphrase, &cipher).await {
         Ok(p) => p,
         Err(e) => return Err(map_notadb(&e.to_string(), &db_path)),
     };
@@ -2625,7 +10068,15 @@ async fn open_database(
     if let Err(e) = sqlx::migrate!("./migrations").run(&pool).await {
         return Err(e.to_string());
     }
+    backfill_fingerprints(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    post_due_recurring(&pool, today()).await?;
+    if let Some(days) = trash_retention(&pool).await? {
+        purge_trashed(&pool, Some(days)).await?;
+    }
     *state.pool.write().await = pool;
+    *state.journal.lock().await = Journal::default();
     Ok(())
 }
 
@@ -2642,6 +10093,186 @@ async fn close_database(state: State<'_, AppState>) -> Result<(), String> {
         .await
         .map_err(|e| e.to_string())?;
     *state.pool.write().await = pool;
+    *state.journal.lock().await = Journal::default();
+    Ok(())
+}
+
+// File behind the open pool; the placeholder pool has none.
+async fn open_database_path(pool: &SqlitePool) -> Result<String, String> {
+    let rows: Vec<(i64, String, String)> = sqlx::query_as("PRAGMA database_list")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    rows.into_iter()
+        .find(|(_, name, file)| name == "main" && !file.is_empty())
+        .map(|(_, _, file)| file)
+        .ok_or_else(|| "No database is open.".to_string())
+}
+
+// The passphrase has to unlock the file on its own, not just match the open pool.
+async fn check_passphrase(db_path: &str, passphrase: &str) -> Result<(), String> {
+    let cipher = read_cipher_settings(db_path)?;
+    let check = build_encrypted_pool(db_path, passphrase, &cipher)
+        .await
+        .map_err(|e| map_notadb(&e.to_string(), db_path))?;
+    let unlocked = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM sqlite_master;")
+        .fetch_one(&check)
+        .await;
+    check.close().await;
+    unlocked
+        .map(|_| ())
+        .map_err(|e| map_notadb(&e.to_string(), db_path))
+}
+
+// Re-encrypts the open database under a new passphrase. The undo journal
+// survives: it's still the same database.
+#[tauri::command]
+async fn change_passphrase(
+    state: State<'_, AppState>,
+    old_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    if new_passphrase.is_empty() {
+        return Err("The new passphrase cannot be empty.".into());
+    }
+    // no other command gets the pool until the new one is in place
+    let mut slot = state.pool.write().await;
+    let db_path = open_database_path(&slot).await?;
+
+    check_passphrase(&db_path, &old_passphrase).await?;
+
+    let pool = {
+        let mut conn = slot.acquire().await.map_err(|e| e.to_string())?;
+        let rekey = |pass: &str| format!("PRAGMA rekey = {};", sql_literal(pass));
+        sqlx::query(&rekey(&new_passphrase))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Changing the passphrase failed: {e}"))?;
+        // the new pool has to open with the new key before the old one is given up
+        // (rekey keeps the cipher settings); until then the old pool stays usable
+        match reopen_pool(&db_path, &new_passphrase).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                sqlx::query(&rekey(&old_passphrase))
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| format!("Changing the passphrase failed: {e}"))?;
+                return Err(format!("Changing the passphrase failed: {e}"));
+            }
+        }
+    };
+    // the other connections still hold the old key
+    slot.close().await;
+    *slot = pool;
+    Ok(())
+}
+
+// Writes an encrypted copy of a plain SQLite file to `dest` and opens it like
+// open_database (migrations included). The source file is left as it is.
+#[tauri::command]
+async fn encrypt_existing_database(
+    state: State<'_, AppState>,
+    src: String,
+    dest: String,
+    passphrase: String,
+    cipher: Option<CipherSettings>,
+) -> Result<(), String> {
+    let cipher = cipher.unwrap_or_default().validated()?;
+    if !Path::new(&src).exists() {
+        return Err("The selected file does not exist.".into());
+    }
+    if !looks_like_plain_sqlite(&src) {
+        return Err("This file is not an unencrypted SQLite database.".into());
+    }
+    if Path::new(&dest).exists() {
+        return Err("The target file already exists.".into());
+    }
+    if passphrase.is_empty() {
+        return Err("The passphrase cannot be empty.".into());
+    }
+
+    let opts = SqliteConnectOptions::new()
+        .filename(&src)
+        .create_if_missing(false);
+    let plain = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(opts)
+        .await
+        .map_err(|e| format!("Open failed: {e}"))?;
+    let exported = sqlcipher_export(&plain, &dest, &passphrase, &cipher).await;
+    plain.close().await;
+    exported?;
+    write_cipher_settings(&dest, &cipher)?;
+
+    if let Err(e) = open_database(state, dest.clone(), passphrase).await {
+        for suffix in ["", "-wal", "-shm", ".cipher"] {
+            let _ = std::fs::remove_file(format!("{dest}{suffix}"));
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+// Writes an unencrypted copy of the open database to `dest`, e.g. for
+// DB Browser or scripts. Asks for the passphrase again since the copy is readable by anyone.
+#[tauri::command]
+async fn export_plain_database(
+    state: State<'_, AppState>,
+    dest: String,
+    passphrase: String,
+) -> Result<String, String> {
+    if Path::new(&dest).exists() {
+        return Err("The target file already exists.".into());
+    }
+    let pool = current_pool(&state).await;
+    let db_path = open_database_path(&pool).await?;
+    check_passphrase(&db_path, &passphrase).await?;
+    // an empty key attaches the target unencrypted
+    sqlcipher_export(&pool, &dest, "", &CipherSettings::default()).await?;
+    Ok(dest)
+}
+
+// Copies schema and data of `pool` into a new file at `dest`, keyed with `key`.
+async fn sqlcipher_export(
+    pool: &SqlitePool,
+    dest: &str,
+    key: &str,
+    cipher: &CipherSettings,
+) -> Result<(), String> {
+    // ATTACH can't create the file if the pool was opened without create_if_missing,
+    // but an empty file is an empty database
+    File::create(dest).map_err(|e| format!("Creating {dest} failed: {e}"))?;
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let attached = sqlx::query("ATTACH DATABASE ?1 AS export KEY ?2")
+        .bind(dest)
+        .bind(key)
+        .execute(&mut *conn)
+        .await;
+    let exported = match attached {
+        Ok(_) => {
+            // the cipher settings have to be in place before the first write
+            let mut statements = cipher.pragmas("export");
+            statements.push("SELECT sqlcipher_export('export')".to_string());
+            let mut exported = Ok(());
+            for sql in &statements {
+                if let Err(e) = sqlx::query(sql).execute(&mut *conn).await {
+                    exported = Err(e);
+                    break;
+                }
+            }
+            // detach either way, the connection goes back to the pool
+            sqlx::query("DETACH DATABASE export")
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| e.to_string())?;
+            exported
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = exported {
+        let _ = std::fs::remove_file(dest);
+        return Err(format!("Export failed: {e}"));
+    }
     Ok(())
 }
 
@@ -2751,16 +10382,40 @@ pub fn run() {
                 });
             }
 
-            app.manage(AppState { pool: Arc::new(RwLock::new(pool)) });
+            app.manage(AppState {
+                pool: Arc::new(RwLock::new(pool)),
+                journal: Default::default(),
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            open_database, create_database, close_database,
+            open_database, create_database, close_database, change_passphrase,
+            encrypt_existing_database, export_plain_database,
+            get_cipher_settings, reencrypt_database,
             add_account, list_accounts, list_transactions,
-            add_transaction, update_transaction, delete_transaction,
+            add_transaction, update_transaction, delete_transaction, add_transfer,
             delete_account, update_account,
-            list_categories, add_category, update_category, delete_category,
-            search_transactions, export_transactions_xlsx, export_transactions_pdf,
+            list_categories, add_category, update_category, delete_category, move_category,
+            merge_categories,
+            list_exchange_rates, set_exchange_rate, delete_exchange_rate,
+            import_exchange_rates_csv,
+            import_csv, import_camt, import_mt940, import_ofx, import_qif,
+            list_tags, add_tag, rename_tag, delete_tag, set_transaction_tags,
+            set_transaction_status, reconcile_account, list_reconciliations, undo_reconciliation,
+            add_attachment, list_attachments, extract_attachment, delete_attachment,
+            list_audit_log, undo, redo, journal_status,
+            list_trash, restore_from_trash, purge_trash, get_trash_retention, set_trash_retention,
+            list_payees, add_payee, update_payee, delete_payee, assign_payees, payee_summary,
+            list_rules, add_rule, update_rule, delete_rule, apply_rules,
+            list_recurring, add_recurring, update_recurring, delete_recurring,
+            upcoming_occurrences, skip_occurrence, edit_occurrence, confirm_occurrence,
+            list_budgets, set_budget, delete_budget, budget_report,
+            export_budget_report_xlsx, export_budget_report_pdf,
+            get_budget_mode, set_budget_mode,
+            list_envelopes, add_envelope, update_envelope, delete_envelope,
+            assign_to_envelope, move_between_envelopes, envelope_month,
+            search_transactions, category_totals,
+            export_transactions_xlsx, export_transactions_pdf,
             export_reimbursable_report_xlsx, export_reimbursable_report_pdf,
             list_transactions_all, is_database_open, system_prefers_dark
         ])
@@ -2769,3 +10424,866 @@ pub fn run() {
 }
 
 fn main() { run(); }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(cents: i64) -> Money {
+        Money(cents)
+    }
+
+    #[test]
+    fn money_parse() {
+        assert_eq!(Money::parse("-1234.5"), Some(m(-123450)));
+        assert_eq!(Money::parse(" +3 "), Some(m(300)));
+        assert_eq!(Money::parse(".5"), Some(m(50)));
+        assert_eq!(Money::parse("12.345"), Some(m(1235)));
+        assert_eq!(Money::parse("-0.004"), Some(m(0)));
+        for bad in ["", "-", ".", "1,5", "1.2.3", "12a", "--1"] {
+            assert_eq!(Money::parse(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn statement_amounts() {
+        assert_eq!(parse_statement_amount("1.234,56", ','), Some(m(123456)));
+        assert_eq!(parse_statement_amount("12,30-", ','), Some(m(-1230)));
+        assert_eq!(parse_statement_amount("(5.00)", '.'), Some(m(-500)));
+        assert_eq!(parse_statement_amount("€ 3,50", ','), Some(m(350)));
+        assert_eq!(
+            parse_statement_amount("EUR -1'000.00", '.'),
+            Some(m(-100000))
+        );
+        assert_eq!(parse_statement_amount("abc", ','), None);
+
+        assert_eq!(parse_guessed_amount("1,234.56"), Some(m(123456)));
+        assert_eq!(parse_guessed_amount("1.234,56"), Some(m(123456)));
+        assert_eq!(parse_guessed_amount("1.234"), Some(m(123400)));
+        assert_eq!(parse_guessed_amount("-12,5"), Some(m(-1250)));
+    }
+
+    #[test]
+    fn fingerprint_normalization() {
+        assert_eq!(
+            normalize_description(Some("  REWE  Markt-GmbH, Berlin ")),
+            "rewe markt gmbh berlin"
+        );
+        assert_eq!(
+            normalize_description(Some("Bäcker/Konditor")),
+            "bäcker konditor"
+        );
+        assert_eq!(normalize_description(None), "");
+
+        let fp = fingerprint("2024-02-01", m(-350), Some("REWE Markt-GmbH"), None);
+        assert_eq!(fp.len(), 64);
+        assert_eq!(
+            fp,
+            fingerprint(
+                "2024-02-01T10:00:00",
+                m(-350),
+                Some("rewe markt gmbh"),
+                None
+            )
+        );
+        assert_ne!(
+            fp,
+            fingerprint("2024-02-02", m(-350), Some("REWE Markt-GmbH"), None)
+        );
+        assert_ne!(
+            fp,
+            fingerprint("2024-02-01", m(-351), Some("REWE Markt-GmbH"), None)
+        );
+        assert_ne!(
+            fp,
+            fingerprint("2024-02-01", m(-350), Some("REWE Markt-GmbH"), Some("R1"))
+        );
+    }
+
+    #[test]
+    fn rrule() {
+        use chrono::{NaiveDate, Weekday};
+        let s = parse_rrule("RRULE:FREQ=monthly;INTERVAL=2;BYMONTHDAY=-1").unwrap();
+        assert_eq!(
+            (s.freq, s.interval, s.by_month_day),
+            (Freq::Monthly, 2, Some(-1))
+        );
+
+        let s = parse_rrule("FREQ=WEEKLY;BYDAY=FR,MO,FR;UNTIL=20261231T000000Z;COUNT=3").unwrap();
+        assert_eq!(s.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!(s.until, NaiveDate::from_ymd_opt(2026, 12, 31));
+        assert_eq!(s.count, Some(3));
+
+        for bad in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=-1",
+            "FREQ=DAILY;UNTIL=2026-13-01",
+            "FREQ=DAILY;BYMONTHDAY=1",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=DAILY;FOO=1",
+            "FREQ",
+        ] {
+            assert!(parse_rrule(bad).is_err(), "{bad:?}");
+        }
+
+        // the 31st falls on the last day of shorter months
+        let s = parse_rrule("FREQ=MONTHLY").unwrap();
+        let d = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert_eq!(
+            schedule_dates(&s, d(1, 31), d(4, 30)),
+            vec![d(1, 31), d(2, 29), d(3, 31), d(4, 30)]
+        );
+    }
+
+    fn csv_opts(mapping: serde_json::Value) -> CsvImport {
+        serde_json::from_value(serde_json::json!({
+            "path": "",
+            "account_id": 1,
+            "mapping": mapping,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn csv_statement() {
+        let text = "Buchungstag;Text;Soll;Haben\n\
+                    01.02.2024;Bäcker;3,50;\n\
+                    02.02.2024;Gehalt;;1.234,56\n\
+                    ;;;\n\
+                    03.02.24;Kiosk;1,00;\n\
+                    xx;bad;1;\n\
+                    05.02.2024;odd;abc;\n";
+        let opts =
+            csv_opts(serde_json::json!({"date": 0, "description": 1, "debit": 2, "credit": 3}));
+        let out = parse_csv_statement(text, &opts).unwrap();
+        assert_eq!(out.headers, vec!["Buchungstag", "Text", "Soll", "Haben"]);
+        let rows = &out.result.rows;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            (rows[0].line, rows[0].date.as_str(), rows[0].amount),
+            (2, "2024-02-01", m(-350))
+        );
+        assert_eq!(rows[0].description.as_deref(), Some("Bäcker"));
+        assert_eq!(
+            (rows[1].date.as_str(), rows[1].amount),
+            ("2024-02-02", m(123456))
+        );
+        assert_eq!(
+            (rows[2].date.as_str(), rows[2].amount),
+            ("2024-02-03", m(-100))
+        );
+        let errors: Vec<_> = out
+            .result
+            .errors
+            .iter()
+            .map(|e| (e.line, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![(6, "invalid date 'xx'"), (7, "invalid amount 'abc'")]
+        );
+
+        // signed amounts with '.' decimals and an explicit date format
+        let mut opts = csv_opts(serde_json::json!({"date": 0, "amount": 1}));
+        opts.has_header = Some(false);
+        opts.delimiter = Some(",".into());
+        opts.decimal_separator = Some(".".into());
+        opts.date_format = Some("%m/%d/%Y".into());
+        let out = parse_csv_statement("02/03/2024,\"-1,234.50\"\n", &opts).unwrap();
+        assert_eq!(out.result.rows[0].date, "2024-02-03");
+        assert_eq!(out.result.rows[0].amount, m(-123450));
+
+        assert!(parse_csv_statement(text, &csv_opts(serde_json::json!({"date": 0}))).is_err());
+        let mut opts = csv_opts(serde_json::json!({"date": 0, "amount": 1}));
+        opts.delimiter = Some("||".into());
+        assert!(parse_csv_statement(text, &opts).is_err());
+        opts.delimiter = None;
+        opts.decimal_separator = Some("x".into());
+        assert!(parse_csv_statement(text, &opts).is_err());
+    }
+
+    #[test]
+    fn camt_statement() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02"><BkToCstmrStmt><Stmt>
+<Ntry><Amt Ccy="EUR">12.50</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts>
+  <BookgDt><Dt>2024-03-01</Dt></BookgDt><AcctSvcrRef>REF1</AcctSvcrRef>
+  <NtryDtls><TxDtls><Refs><EndToEndId>E2E-1</EndToEndId></Refs>
+    <RltdPties><Cdtr><Nm>Bakery</Nm></Cdtr><CdtrAcct><Id><IBAN>DE02100100100006820101</IBAN></Id></CdtrAcct></RltdPties>
+    <RmtInf><Ustrd>Bread</Ustrd><Ustrd>and rolls</Ustrd></RmtInf></TxDtls></NtryDtls></Ntry>
+<Ntry><Amt Ccy="EUR">100</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts><Cd>BOOK</Cd></Sts>
+  <ValDt><DtTm>2024-03-02T10:00:00</DtTm></ValDt>
+  <NtryDtls><TxDtls><Refs><EndToEndId>E2E-2</EndToEndId></Refs>
+    <RltdPties><Dbtr><Pty><Nm>Employer</Nm></Pty></Dbtr></RltdPties></TxDtls></NtryDtls></Ntry>
+<Ntry><Amt Ccy="EUR">1</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>PDNG</Sts><BookgDt><Dt>2024-03-03</Dt></BookgDt></Ntry>
+<Ntry><Amt Ccy="EUR">1</Amt><CdtDbtInd>XX</CdtDbtInd><BookgDt><Dt>2024-03-03</Dt></BookgDt></Ntry>
+<Ntry><Amt Ccy="EUR">1,00</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>2024-03-03</Dt></BookgDt></Ntry>
+<Ntry><Amt Ccy="EUR">1</Amt><CdtDbtInd>DBIT</CdtDbtInd><BookgDt><Dt>03.03.2024</Dt></BookgDt></Ntry>
+</Stmt></BkToCstmrStmt></Document>"#;
+        let out = parse_camt(text).unwrap();
+        assert_eq!(out.rows.len(), 2);
+        let r = &out.rows[0];
+        assert_eq!((r.date.as_str(), r.amount), ("2024-03-01", m(-1250)));
+        assert_eq!(r.description.as_deref(), Some("Bread and rolls"));
+        assert_eq!(r.counterparty.as_deref(), Some("Bakery"));
+        assert_eq!(
+            r.counterparty_iban.as_deref(),
+            Some("DE02100100100006820101")
+        );
+        assert_eq!(r.bank_ref.as_deref(), Some("REF1"));
+        assert_eq!(r.currency.as_deref(), Some("EUR"));
+        let r = &out.rows[1];
+        assert_eq!((r.date.as_str(), r.amount), ("2024-03-02", m(10000)));
+        assert_eq!(r.counterparty.as_deref(), Some("Employer"));
+        // the payer's EndToEndId is not a bank reference
+        assert_eq!(r.bank_ref, None);
+
+        let errors: Vec<_> = out.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "entry not booked yet (status PDNG), skipped",
+                "invalid credit/debit indicator Some(\"XX\")",
+                "invalid amount '1,00'",
+                "entry has no booking date",
+            ]
+        );
+
+        assert!(parse_camt("not xml").is_err());
+        assert!(parse_camt("<Document><Other/></Document>").is_err());
+    }
+
+    #[test]
+    fn mt940_statement() {
+        let text = "\
+:20:STARTUMS
+:25:10020030/1234567
+:28C:00001/001
+:60F:C240301EUR1000,00
+:61:2403010301D12,50NTRFNONREF//BANKREF1
+:86:166?00SEPA-UEBERWEISUNG?20Rent Ma
+rch 2024?31DE02100100100006820101?32Land
+lord
+:61:2403020302C1000,00NTRFNONREF
+:86:Salary
+ March
+:61:240304RD5,00NTRF//R2
+:61:24030X
+:61:240305X5,00
+:61:2401011231D1,00NTRF//NONREF
+:62F:C240302EUR1987,50
+-";
+        let out = parse_mt940(text);
+        let rows: Vec<_> = out
+            .rows
+            .iter()
+            .map(|r| (r.line, r.date.as_str(), r.amount, r.bank_ref.as_deref()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (5, "2024-03-01", m(-1250), Some("BANKREF1")),
+                (9, "2024-03-02", m(100000), None),
+                (12, "2024-03-04", m(500), Some("R2")),
+                (15, "2023-12-31", m(-100), None),
+            ]
+        );
+        let rent = &out.rows[0];
+        assert_eq!(rent.description.as_deref(), Some("Rent March 2024"));
+        assert_eq!(rent.counterparty.as_deref(), Some("Landlord"));
+        assert_eq!(
+            rent.counterparty_iban.as_deref(),
+            Some("DE02100100100006820101")
+        );
+        assert_eq!(rent.currency.as_deref(), Some("EUR"));
+        assert_eq!(out.rows[1].description.as_deref(), Some("Salary March"));
+        assert_eq!(out.rows[2].description, None);
+
+        let errors: Vec<_> = out
+            .errors
+            .iter()
+            .map(|e| (e.line, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (13, "invalid value date in :61:24030X"),
+                (14, "missing debit/credit mark in :61:240305X5,00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ofx_statement() {
+        let text = "OFXHEADER:100\nDATA:OFXSGML\n\n<OFX><BANKMSGSRSV1>
+<STMTTRNRS><STMTRS><CURDEF>EUR<BANKACCTFROM><ACCTID>Giro</BANKACCTFROM><BANKTRANLIST>
+<STMTTRN><DTPOSTED>20240105120000[0:GMT]<TRNAMT>-12.34<FITID>F1<NAME>Bakery &amp; Co<MEMO>Bread</STMTTRN>
+<STMTTRN><DTPOSTED>20240106<TRNAMT>100,00<FITID>F2<NAME>Refund</STMTTRN>
+<STMTTRN><DTPOSTED>2024<TRNAMT>1<FITID>F3</STMTTRN>
+</BANKTRANLIST></STMTRS></STMTTRNRS>
+<CCSTMTTRNRS><CCSTMTRS><CURDEF>usd<CCACCTFROM><ACCTID>Card</CCACCTFROM><BANKTRANLIST>
+<STMTTRN><DTPOSTED>20240107<TRNAMT>abc<FITID>F4</STMTTRN>
+<STMTTRN><DTPOSTED>20240108<TRNAMT>-5<FITID>F5<PAYEE>Shop</STMTTRN>
+</BANKTRANLIST></CCSTMTRS></CCSTMTTRNRS></BANKMSGSRSV1></OFX>";
+        let out = parse_ofx(text).unwrap();
+        assert_eq!(out.len(), 2);
+
+        let (name, currency, giro) = &out[0];
+        assert_eq!(
+            (name.as_deref(), currency.as_deref()),
+            (Some("Giro"), Some("EUR"))
+        );
+        assert_eq!(giro.rows.len(), 2);
+        let r = &giro.rows[0];
+        assert_eq!(
+            (r.line, r.date.as_str(), r.amount),
+            (6, "2024-01-05", m(-1234))
+        );
+        assert_eq!(r.description.as_deref(), Some("Bread"));
+        assert_eq!(r.counterparty.as_deref(), Some("Bakery & Co"));
+        assert_eq!(r.bank_ref.as_deref(), Some("F1"));
+        assert_eq!(giro.rows[1].amount, m(10000));
+        assert_eq!(giro.rows[1].description.as_deref(), Some("Refund"));
+        assert_eq!(giro.errors[0].message, "invalid date '2024'");
+
+        let (name, currency, card) = &out[1];
+        assert_eq!(
+            (name.as_deref(), currency.as_deref()),
+            (Some("Card"), Some("USD"))
+        );
+        assert_eq!(card.rows.len(), 1);
+        assert_eq!(card.rows[0].counterparty.as_deref(), Some("Shop"));
+        assert_eq!(card.rows[0].currency.as_deref(), Some("USD"));
+        assert_eq!(card.errors[0].message, "invalid amount 'abc'");
+
+        assert!(parse_ofx("OFXHEADER:100\n<BANK>").is_err());
+    }
+
+    #[test]
+    fn qif_statement() {
+        let text = "\
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D1/ 5'24
+T1,000.00
+POpening Balance
+L[Checking]
+^
+D01/06/2024
+T-85.40
+PSupermarket
+MWeekly shop
+SFood/Trip
+EVeg
+$-60.40
+SHousehold
+$-25.00
+^
+D01/07/2024
+T-10.00
+SFood
+$-5.00
+^
+Dxx
+T1
+^
+!Account
+NSavings
+TBank
+^
+!type:bank
+D01/08/2024
+T500.00
+L[Checking]/Moves
+^
+";
+        let out = parse_qif(text, None).unwrap();
+        assert_eq!(out.len(), 2);
+
+        let (name, _, checking) = &out[0];
+        assert_eq!(name.as_deref(), Some("Checking"));
+        let opening = &checking.rows[0];
+        assert_eq!(
+            (opening.date.as_str(), opening.amount),
+            ("2024-01-05", m(100000))
+        );
+        assert_eq!(opening.category.as_deref(), Some("Init"));
+        let shop = &checking.rows[1];
+        assert_eq!(shop.description.as_deref(), Some("Weekly shop"));
+        assert_eq!(shop.counterparty.as_deref(), Some("Supermarket"));
+        assert_eq!(shop.category, None);
+        let splits: Vec<_> = shop
+            .splits
+            .iter()
+            .map(|s| (s.category.as_deref(), s.amount, s.memo.as_deref()))
+            .collect();
+        assert_eq!(
+            splits,
+            vec![
+                (Some("Food"), m(-6040), Some("Veg")),
+                (Some("Household"), m(-2500), None)
+            ]
+        );
+        let errors: Vec<_> = checking
+            .errors
+            .iter()
+            .map(|e| (e.line, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (21, "split lines add up to -5.00, not -10.00"),
+                (26, "invalid date 'xx'"),
+            ]
+        );
+
+        // a transfer to another account carries no category
+        let (name, _, savings) = &out[1];
+        assert_eq!(name.as_deref(), Some("Savings"));
+        assert_eq!(savings.rows[0].amount, m(50000));
+        assert_eq!(savings.rows[0].category, None);
+
+        let out = parse_qif("!Type:Bank\nD05.01.2024\nT-1\n^\n", Some("%d.%m.%Y")).unwrap();
+        assert_eq!(out[0].0, None);
+        assert_eq!(out[0].2.rows[0].date, "2024-01-05");
+
+        let out = parse_qif(
+            "!TYPE:BANK\nD01/05/2024\nT-1\nLFood:Groceries/Vacation\n^\n",
+            None,
+        );
+        assert_eq!(
+            out.unwrap()[0].2.rows[0].category.as_deref(),
+            Some("Food:Groceries")
+        );
+
+        let out = parse_qif("!Type:Invst\nD01/05/2024\nT1\n^\n", None).unwrap();
+        assert!(out[0].2.rows.is_empty());
+        assert_eq!(
+            out[0].2.errors[0].message,
+            "section !Type:invst is not supported, skipped"
+        );
+    }
+
+    /* ---------- Database ---------- */
+    type TestApp = tauri::App<tauri::test::MockRuntime>;
+
+    // A fresh in-memory database with all migrations, managed by a mock app.
+    async fn test_app() -> TestApp {
+        let opts = SqliteConnectOptions::new()
+            .filename(":memory:")
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(opts)
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let app = tauri::test::mock_app();
+        app.manage(AppState {
+            pool: Arc::new(RwLock::new(pool)),
+            journal: Default::default(),
+        });
+        app
+    }
+
+    fn input<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    async fn account(app: &TestApp, name: &str) -> i64 {
+        let new = input(serde_json::json!({ "name": name, "account_type": "standard" }));
+        add_account(app.state(), new).await.unwrap()
+    }
+
+    // (account, date, amount) of the live legs, sending side first
+    async fn transfer_legs(app: &TestApp, transfer_id: i64) -> Vec<(i64, String, Money)> {
+        let pool = current_pool(&app.state()).await;
+        sqlx::query_as(
+            "SELECT account_id, date, amount FROM transactions \
+             WHERE transfer_id = ?1 AND deleted_at IS NULL ORDER BY amount",
+        )
+        .bind(transfer_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn transfer_legs_stay_in_sync() {
+        let app = test_app().await;
+        let x = account(&app, "Checking").await;
+        let y = account(&app, "Savings").await;
+        let same = serde_json::json!({
+            "from_account_id": x, "to_account_id": x, "date": "2024-01-02", "amount": 50
+        });
+        assert!(add_transfer(app.state(), input(same)).await.is_err());
+
+        let new = serde_json::json!({
+            "from_account_id": x, "to_account_id": y, "date": "2024-01-02", "amount": 50
+        });
+        let tid = add_transfer(app.state(), input(new)).await.unwrap();
+        let legs = transfer_legs(&app, tid).await;
+        assert_eq!(
+            legs,
+            vec![
+                (x, "2024-01-02".to_string(), m(-5000)),
+                (y, "2024-01-02".to_string(), m(5000)),
+            ]
+        );
+
+        // editing one leg moves the other along
+        let pool = current_pool(&app.state()).await;
+        let leg: i64 = sqlx::query_scalar(
+            "SELECT id FROM transactions WHERE transfer_id = ?1 AND account_id = ?2",
+        )
+        .bind(tid)
+        .bind(x)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let edit = serde_json::json!({ "id": leg, "amount": -70, "date": "2024-01-05" });
+        assert!(update_transaction(app.state(), input(edit)).await.unwrap());
+        assert_eq!(
+            transfer_legs(&app, tid).await,
+            vec![
+                (x, "2024-01-05".to_string(), m(-7000)),
+                (y, "2024-01-05".to_string(), m(7000)),
+            ]
+        );
+        let edit = serde_json::json!({ "id": leg, "account_id": y });
+        assert!(update_transaction(app.state(), input(edit)).await.is_err());
+
+        // deleting one leg trashes both
+        assert!(delete_transaction(app.state(), leg).await.unwrap());
+        assert!(transfer_legs(&app, tid).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn split_lines_match_the_total() {
+        let app = test_app().await;
+        let x = account(&app, "Checking").await;
+        let short = serde_json::json!({
+            "account_id": x, "date": "2024-01-01", "amount": -100,
+            "splits": [{ "category": "Food", "amount": -60 }, { "category": "Home", "amount": -30 }]
+        });
+        assert!(add_transaction(app.state(), input(short)).await.is_err());
+        let both = serde_json::json!({
+            "account_id": x, "date": "2024-01-01", "amount": -100, "category": "Shop",
+            "splits": [{ "category": "Food", "amount": -60 }, { "category": "Home", "amount": -40 }]
+        });
+        assert!(add_transaction(app.state(), input(both)).await.is_err());
+
+        let new = serde_json::json!({
+            "account_id": x, "date": "2024-01-01", "amount": -100,
+            "splits": [{ "category": "Food", "amount": -60 }, { "category": "Home", "amount": -40 }]
+        });
+        let id = add_transaction(app.state(), input(new)).await.unwrap().id;
+        let pool = current_pool(&app.state()).await;
+        let lines = |pool: SqlitePool| async move {
+            sqlx::query_as::<_, (String, Money)>(
+                "SELECT c.name, s.amount FROM transaction_splits s \
+                 JOIN categories c ON c.id = s.category_id \
+                 WHERE s.transaction_id = ?1 ORDER BY s.id",
+            )
+            .bind(id)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+        assert_eq!(
+            lines(pool.clone()).await,
+            vec![
+                ("Food".to_string(), m(-6000)),
+                ("Home".to_string(), m(-4000))
+            ]
+        );
+        let category: Option<i64> =
+            sqlx::query_scalar("SELECT category_id FROM transactions WHERE id = ?1")
+                .bind(id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(category, None);
+
+        // a new amount needs lines that add up to it
+        let edit = serde_json::json!({ "id": id, "amount": -90 });
+        assert!(update_transaction(app.state(), input(edit)).await.is_err());
+        let edit = serde_json::json!({
+            "id": id, "amount": -90, "splits": [{ "category": "Food", "amount": -90 }]
+        });
+        assert!(update_transaction(app.state(), input(edit)).await.unwrap());
+        assert_eq!(lines(pool).await, vec![("Food".to_string(), m(-9000))]);
+    }
+
+    #[tokio::test]
+    async fn merge_moves_everything_to_the_target() {
+        let app = test_app().await;
+        let x = account(&app, "Checking").await;
+        let food = add_category(app.state(), "Food".into(), None)
+            .await
+            .unwrap();
+        let grocery = add_category(app.state(), "Grocery".into(), None)
+            .await
+            .unwrap();
+        let organic = add_category(app.state(), "Organic".into(), Some(grocery))
+            .await
+            .unwrap();
+        for new in [
+            serde_json::json!({
+                "account_id": x, "date": "2024-01-01", "amount": -10, "category": "Grocery"
+            }),
+            serde_json::json!({
+                "account_id": x, "date": "2024-01-02", "amount": -30, "category": "Rent"
+            }),
+            serde_json::json!({
+                "account_id": x, "date": "2024-01-03", "amount": -50,
+                "splits": [
+                    { "category": "Grocery", "amount": -5 },
+                    { "category": "Rent", "amount": -45 }
+                ]
+            }),
+        ] {
+            add_transaction(app.state(), input(new)).await.unwrap();
+        }
+        let rule = serde_json::json!({
+            "name": "Aldi", "description_contains": "aldi", "category": "Grocery"
+        });
+        add_rule(app.state(), input(rule)).await.unwrap();
+        let budget = serde_json::json!({ "category": "Grocery", "amount": 100 });
+        set_budget(app.state(), input(budget)).await.unwrap();
+
+        assert!(merge_categories(app.state(), vec![food], food)
+            .await
+            .is_err());
+        assert!(merge_categories(app.state(), vec![999], food)
+            .await
+            .is_err());
+        // the plain booking and the split line, counted once each
+        let moved = merge_categories(app.state(), vec![grocery, grocery], food)
+            .await
+            .unwrap();
+        assert_eq!(moved, 2);
+
+        let pool = current_pool(&app.state()).await;
+        let count = |sql: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(sql)
+                    .bind(food)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(
+            count("SELECT COUNT(*) FROM transactions WHERE category_id = ?1").await,
+            1
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM transaction_splits WHERE category_id = ?1").await,
+            1
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM rules WHERE category_id = ?1").await,
+            1
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM budgets WHERE category_id = ?1").await,
+            1
+        );
+        assert_eq!(
+            count("SELECT COUNT(*) FROM categories WHERE parent_id = ?1").await,
+            1
+        );
+        let cats = list_categories(app.state()).await.unwrap();
+        assert!(cats.iter().all(|c| c.id != grocery));
+        let organic = cats.iter().find(|c| c.id == organic).unwrap();
+        assert_eq!(organic.path, "Food > Organic");
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_round_trip() {
+        let app = test_app().await;
+        let x = account(&app, "Checking").await;
+        let new = serde_json::json!({
+            "account_id": x, "date": "2024-01-05", "description": "Hotel", "amount": -80,
+            "tags": ["trip"]
+        });
+        let id = add_transaction(app.state(), input(new)).await.unwrap().id;
+        let edit = serde_json::json!({ "id": id, "description": "Hotel Berlin" });
+        assert!(update_transaction(app.state(), input(edit)).await.unwrap());
+        assert!(delete_transaction(app.state(), id).await.unwrap());
+        let status = journal_status(app.state()).await.unwrap();
+        assert_eq!(
+            status.undo[..3],
+            ["Delete transaction", "Edit transaction", "Add transaction"]
+        );
+
+        let pool = current_pool(&app.state()).await;
+        // (description, trashed, tags) of the transaction, None once it is gone
+        let row = || {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_as::<_, (Option<String>, bool, Option<String>)>(
+                    "SELECT description, deleted_at IS NOT NULL, \
+                       (SELECT group_concat(g.name) FROM transaction_tags tt \
+                        JOIN tags g ON g.id = tt.tag_id WHERE tt.transaction_id = t.id) \
+                     FROM transactions t WHERE id = ?1",
+                )
+                .bind(id)
+                .fetch_optional(&pool)
+                .await
+                .unwrap()
+            }
+        };
+        let trip = Some("trip".to_string());
+        assert_eq!(
+            undo(app.state(), None).await.unwrap(),
+            ["Delete transaction"]
+        );
+        assert_eq!(
+            row().await,
+            Some((Some("Hotel Berlin".into()), false, trip.clone()))
+        );
+        assert_eq!(
+            undo(app.state(), Some(1)).await.unwrap(),
+            ["Edit transaction"]
+        );
+        assert_eq!(
+            row().await,
+            Some((Some("Hotel".into()), false, trip.clone()))
+        );
+        assert_eq!(
+            redo(app.state(), Some(5)).await.unwrap(),
+            ["Edit transaction", "Delete transaction"]
+        );
+        assert_eq!(row().await, Some((Some("Hotel Berlin".into()), true, trip)));
+        assert_eq!(undo(app.state(), Some(3)).await.unwrap().len(), 3);
+        assert_eq!(row().await, None);
+        assert_eq!(journal_status(app.state()).await.unwrap().redo.len(), 3);
+
+        // a new edit drops the redo history
+        let new = serde_json::json!({
+            "account_id": x, "date": "2024-01-06", "description": "Taxi", "amount": -20
+        });
+        let taxi = add_transaction(app.state(), input(new)).await.unwrap().id;
+        assert!(journal_status(app.state()).await.unwrap().redo.is_empty());
+        assert!(redo(app.state(), None).await.unwrap().is_empty());
+
+        // a row changed outside the journal is left alone
+        sqlx::query("UPDATE transactions SET description = 'Cab' WHERE id = ?1")
+            .bind(taxi)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(undo(app.state(), None).await.is_err());
+        let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE id = ?1")
+            .bind(taxi)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(left, 1);
+    }
+
+    #[tokio::test]
+    async fn trash_restores_and_purges() {
+        let app = test_app().await;
+        let x = account(&app, "Checking").await;
+        let new = serde_json::json!({
+            "account_id": x, "date": "2024-01-05", "amount": -80, "category": "Travel"
+        });
+        let id = add_transaction(app.state(), input(new)).await.unwrap().id;
+        let cats = list_categories(app.state()).await.unwrap();
+        let travel = cats.iter().find(|c| c.name == "Travel").unwrap().id;
+        assert!(delete_transaction(app.state(), id).await.unwrap());
+        assert!(delete_category(app.state(), travel).await.unwrap());
+        assert!(delete_account(app.state(), x).await.unwrap());
+        assert_eq!(list_trash(app.state()).await.unwrap().len(), 3);
+
+        // the transaction brings back its account and category
+        assert!(restore_from_trash(app.state(), "transaction".into(), id)
+            .await
+            .unwrap());
+        assert!(!restore_from_trash(app.state(), "transaction".into(), id)
+            .await
+            .unwrap());
+        assert!(list_trash(app.state()).await.unwrap().is_empty());
+        let accounts = list_accounts(app.state(), None).await.unwrap();
+        assert!(accounts.iter().any(|a| a.id == x));
+        let cats = list_categories(app.state()).await.unwrap();
+        assert!(cats.iter().any(|c| c.id == travel));
+
+        // purging keeps rows younger than the cut-off
+        assert!(delete_transaction(app.state(), id).await.unwrap());
+        assert_eq!(purge_trash(app.state(), Some(7)).await.unwrap(), 0);
+        let pool = current_pool(&app.state()).await;
+        sqlx::query(
+            "UPDATE transactions SET deleted_at = datetime('now', '-10 days') WHERE id = ?1",
+        )
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(purge_trash(app.state(), Some(7)).await.unwrap(), 1);
+        let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE id = ?1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(left, 0);
+
+        // without a cut-off everything goes
+        assert!(delete_category(app.state(), travel).await.unwrap());
+        assert_eq!(purge_trash(app.state(), None).await.unwrap(), 1);
+        assert!(list_trash(app.state()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reconciled_rows_are_locked() {
+        let app = test_app().await;
+        let x = account(&app, "Checking").await;
+        let mut ids = Vec::new();
+        for (date, amount) in [("2024-01-01", 100), ("2024-01-05", -30), ("2024-01-20", -5)] {
+            let new = serde_json::json!({ "account_id": x, "date": date, "amount": amount });
+            ids.push(add_transaction(app.state(), input(new)).await.unwrap().id);
+        }
+        let cleared = set_transaction_status(app.state(), ids[..2].to_vec(), "cleared".into())
+            .await
+            .unwrap();
+        assert_eq!(cleared, 2);
+
+        let statement = |balance: i64| {
+            input(serde_json::json!({
+                "account_id": x, "statement_date": "2024-01-31",
+                "statement_balance": balance, "finish": true
+            }))
+        };
+        assert!(reconcile_account(app.state(), statement(65)).await.is_err());
+        let done = reconcile_account(app.state(), statement(70)).await.unwrap();
+        assert_eq!((done.cleared_count, done.uncleared_count), (2, 1));
+        let rid = done.reconciliation_id.unwrap();
+
+        // amount, date and deletion are locked; the description is not
+        let edit = serde_json::json!({ "id": ids[0], "amount": 1 });
+        assert!(update_transaction(app.state(), input(edit)).await.is_err());
+        let edit = serde_json::json!({ "id": ids[0], "description": "Salary" });
+        assert!(update_transaction(app.state(), input(edit)).await.unwrap());
+        assert!(delete_transaction(app.state(), ids[1]).await.is_err());
+        let reopened = set_transaction_status(app.state(), ids.clone(), "uncleared".into())
+            .await
+            .unwrap();
+        assert_eq!(reopened, 1);
+
+        // undoing the reconciliation unlocks them again
+        assert!(undo_reconciliation(app.state(), rid).await.unwrap());
+        assert!(delete_transaction(app.state(), ids[1]).await.unwrap());
+    }
+}
@@ -1,8 +1,17 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
   Account, NewAccount, UpdateAccount,
-  Transaction, NewTransaction, UpdateTransaction,
-  TransactionSearch, TransactionSearchResult,
+  Transaction, NewTransaction, AddedTransaction, UpdateTransaction, NewTransfer,
+  TransactionSearch, TransactionSearchResult, CategoryTotal, Category,
+  ExchangeRate, NewExchangeRate,
+  CsvImport, CsvImportResult, StatementImport, ImportResult, MultiAccountImport, AccountImport,
+  Rule, RuleInput, ApplyRules, ApplyRulesResult,
+  Recurring, RecurringInput, Occurrence, OccurrenceEdit,
+  Budget, BudgetInput, BudgetReport, BudgetMode, Envelope, EnvelopeMonth,
+  Tag, Payee, PayeeInput, PayeeSummary,
+  ReconcileInput, ReconcileResult, Reconciliation, Attachment,
+  AuditEntry, AuditFilter, JournalStatus, TrashEntity, TrashItem,
+  CipherSettings,
 } from '../types';
 
 export async function deleteAsset(id: number): Promise<boolean> {
@@ -10,15 +19,15 @@ export async function deleteAsset(id: number): Promise<boolean> {
 }
 
 /* accounts */
-export async function listAccounts(): Promise<Account[]> {
-  return invoke<Account[]>('list_accounts');
+export async function listAccounts(baseCurrency?: string): Promise<Account[]> {
+  return invoke<Account[]>('list_accounts', { baseCurrency });
 }
 export async function addAccount(input: NewAccount): Promise<number> {
   return invoke<number>('add_account', { input });
 }
 export async function updateAccount(input: UpdateAccount): Promise<boolean> {
-  const { id, name, color } = input;
-  return invoke<boolean>('update_account', { id, name, color: color ?? null });
+  const { id, name, color, currency } = input;
+  return invoke<boolean>('update_account', { id, name, color: color ?? null, currency: currency ?? null });
 }
 export async function deleteAccount(id: number): Promise<boolean> {
   return invoke<boolean>('delete_account', { id });
@@ -28,8 +37,8 @@ export async function deleteAccount(id: number): Promise<boolean> {
 export async function listTransactions(limit?: number): Promise<Transaction[]> {
   return invoke<Transaction[]>('list_transactions', { limit });
 }
-export async function addTransaction(input: NewTransaction): Promise<number> {
-  return invoke<number>('add_transaction', { input });
+export async function addTransaction(input: NewTransaction): Promise<AddedTransaction> {
+  return invoke<AddedTransaction>('add_transaction', { input });
 }
 export async function updateTransaction(input: UpdateTransaction): Promise<boolean> {
   return invoke<boolean>('update_transaction', { input });
@@ -37,11 +46,18 @@ export async function updateTransaction(input: UpdateTransaction): Promise<boole
 export async function deleteTransaction(id: number): Promise<boolean> {
   return invoke<boolean>('delete_transaction', { id });
 }
+export async function addTransfer(input: NewTransfer): Promise<number> {
+  return invoke<number>('add_transfer', { input });
+}
 
 /* transactions (search + export) */
 export async function searchTransactions(filters: TransactionSearch): Promise<TransactionSearchResult> {
   return invoke<TransactionSearchResult>('search_transactions', { filters });
 }
+// level: roll subcategories up to this depth (0 = top level); omit to keep categories as booked
+export async function categoryTotals(filters: TransactionSearch, level?: number): Promise<CategoryTotal[]> {
+  return invoke<CategoryTotal[]>('category_totals', { filters, level });
+}
 export async function exportTransactionsXlsx(filters: TransactionSearch, columns?: string[]): Promise<string> {
   return invoke<string>('export_transactions_xlsx', { filters, columns });
 }
@@ -53,25 +69,30 @@ export async function exportTransactionsPdf(filters: TransactionSearch, columns?
 export async function exportReimbursableReportXlsx(filters: TransactionSearch, columns?: string[]): Promise<string> {
   return invoke<string>('export_reimbursable_report_xlsx', { filters, columns });
 }
-export async function exportReimbursableReportPdf(filters: TransactionSearch, columns?: string[]): Promise<string> {
-  return invoke<string>('export_reimbursable_report_pdf', { filters, columns });
+export async function exportReimbursableReportPdf(filters: TransactionSearch, columns?: string[], includeReceipts = false): Promise<string> {
+  return invoke<string>('export_reimbursable_report_pdf', { filters, columns, includeReceipts });
 }
 
 // categories
-import type { Category } from '../types';
-
 export async function listCategories(): Promise<Category[]> {
   return invoke<Category[]>('list_categories');
 }
 
-export async function addCategory(name: string): Promise<number> {
-  return invoke<number>('add_category', { name });
+export async function addCategory(name: string, parentId?: number | null): Promise<number> {
+  return invoke<number>('add_category', { name, parentId: parentId ?? null });
 }
 
 export async function renameCategory(id: number, name: string): Promise<boolean> {
   return invoke<boolean>('update_category', { id, name });
 }
 
+export async function moveCategory(id: number, parentId: number | null): Promise<boolean> {
+  return invoke<boolean>('move_category', { id, parentId });
+}
+// moves every reference into the target and deletes the sources; returns the number of changed transactions
+export async function mergeCategories(sourceIds: number[], targetId: number): Promise<number> {
+  return invoke<number>('merge_categories', { sourceIds, targetId });
+}
 export async function deleteCategory(id: number): Promise<boolean> {
   return invoke<boolean>('delete_category', { id });
 }
@@ -83,4 +104,250 @@ export type TxMini = { account_id: number; date: string; amount: number };
 
 export async function listTransactionsAll(): Promise<TxMini[]> {
   return invoke<TxMini[]>('list_transactions_all');
-}
\ No newline at end of file
+}
+
+/* exchange rates */
+export async function listExchangeRates(): Promise<ExchangeRate[]> {
+  return invoke<ExchangeRate[]>('list_exchange_rates');
+}
+export async function setExchangeRate(input: NewExchangeRate): Promise<void> {
+  return invoke<void>('set_exchange_rate', { input });
+}
+export async function deleteExchangeRate(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_exchange_rate', { id });
+}
+export async function importExchangeRatesCsv(path: string): Promise<number> {
+  return invoke<number>('import_exchange_rates_csv', { path });
+}
+
+/* statement import */
+export async function importCsv(input: CsvImport): Promise<CsvImportResult> {
+  return invoke<CsvImportResult>('import_csv', { input });
+}
+export async function importCamt(input: StatementImport): Promise<ImportResult> {
+  return invoke<ImportResult>('import_camt', { input });
+}
+export async function importMt940(input: StatementImport): Promise<ImportResult> {
+  return invoke<ImportResult>('import_mt940', { input });
+}
+export async function importOfx(input: MultiAccountImport): Promise<AccountImport[]> {
+  return invoke<AccountImport[]>('import_ofx', { input });
+}
+export async function importQif(input: MultiAccountImport): Promise<AccountImport[]> {
+  return invoke<AccountImport[]>('import_qif', { input });
+}
+
+/* auto-categorization rules */
+export async function listRules(): Promise<Rule[]> {
+  return invoke<Rule[]>('list_rules');
+}
+export async function addRule(input: RuleInput): Promise<number> {
+  return invoke<number>('add_rule', { input });
+}
+export async function updateRule(id: number, input: RuleInput): Promise<boolean> {
+  return invoke<boolean>('update_rule', { id, input });
+}
+export async function deleteRule(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_rule', { id });
+}
+export async function applyRules(input: ApplyRules): Promise<ApplyRulesResult> {
+  return invoke<ApplyRulesResult>('apply_rules', { input });
+}
+
+/* recurring transactions */
+export async function listRecurring(): Promise<Recurring[]> {
+  return invoke<Recurring[]>('list_recurring');
+}
+export async function addRecurring(input: RecurringInput): Promise<number> {
+  return invoke<number>('add_recurring', { input });
+}
+export async function updateRecurring(id: number, input: RecurringInput): Promise<boolean> {
+  return invoke<boolean>('update_recurring', { id, input });
+}
+export async function deleteRecurring(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_recurring', { id });
+}
+export async function upcomingOccurrences(days?: number): Promise<Occurrence[]> {
+  return invoke<Occurrence[]>('upcoming_occurrences', { days });
+}
+export async function skipOccurrence(recurringId: number, date: string): Promise<boolean> {
+  return invoke<boolean>('skip_occurrence', { recurringId, date });
+}
+export async function editOccurrence(input: OccurrenceEdit): Promise<boolean> {
+  return invoke<boolean>('edit_occurrence', { input });
+}
+export async function confirmOccurrence(recurringId: number, date: string): Promise<number> {
+  return invoke<number>('confirm_occurrence', { recurringId, date });
+}
+
+/* budgets (the report uses the same filters as searchTransactions; default range: this month) */
+export async function listBudgets(): Promise<Budget[]> {
+  return invoke<Budget[]>('list_budgets');
+}
+export async function setBudget(input: BudgetInput): Promise<number> {
+  return invoke<number>('set_budget', { input });
+}
+export async function deleteBudget(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_budget', { id });
+}
+export async function budgetReport(filters: TransactionSearch): Promise<BudgetReport> {
+  return invoke<BudgetReport>('budget_report', { filters });
+}
+export async function exportBudgetReportXlsx(filters: TransactionSearch): Promise<string> {
+  return invoke<string>('export_budget_report_xlsx', { filters });
+}
+export async function exportBudgetReportPdf(filters: TransactionSearch): Promise<string> {
+  return invoke<string>('export_budget_report_pdf', { filters });
+}
+
+/* envelope budgeting; months are YYYY-MM */
+export async function getBudgetMode(): Promise<BudgetMode> {
+  return invoke<BudgetMode>('get_budget_mode');
+}
+export async function setBudgetMode(mode: BudgetMode): Promise<void> {
+  return invoke<void>('set_budget_mode', { mode });
+}
+export async function listEnvelopes(): Promise<Envelope[]> {
+  return invoke<Envelope[]>('list_envelopes');
+}
+export async function addEnvelope(name: string, categories: string[] = []): Promise<number> {
+  return invoke<number>('add_envelope', { name, categories });
+}
+export async function updateEnvelope(id: number, name: string, categories: string[]): Promise<boolean> {
+  return invoke<boolean>('update_envelope', { id, name, categories });
+}
+export async function deleteEnvelope(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_envelope', { id });
+}
+export async function assignToEnvelope(envelopeId: number, month: string, amount: number): Promise<void> {
+  return invoke<void>('assign_to_envelope', { envelopeId, month, amount });
+}
+export async function moveBetweenEnvelopes(
+  fromEnvelopeId: number, toEnvelopeId: number, month: string, amount: number,
+): Promise<number> {
+  return invoke<number>('move_between_envelopes', { fromEnvelopeId, toEnvelopeId, month, amount });
+}
+export async function envelopeMonth(month: string, baseCurrency?: string): Promise<EnvelopeMonth> {
+  return invoke<EnvelopeMonth>('envelope_month', { month, baseCurrency });
+}
+
+/* tags (searchTransactions filters by `tags` + `tag_match`) */
+export async function listTags(): Promise<Tag[]> {
+  return invoke<Tag[]>('list_tags');
+}
+export async function addTag(name: string): Promise<number> {
+  return invoke<number>('add_tag', { name });
+}
+export async function renameTag(id: number, name: string): Promise<boolean> {
+  return invoke<boolean>('rename_tag', { id, name });
+}
+export async function deleteTag(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_tag', { id });
+}
+export async function setTransactionTags(transactionId: number, tags: string[]): Promise<boolean> {
+  return invoke<boolean>('set_transaction_tags', { transactionId, tags });
+}
+
+/* payees (adding or editing a payee links matching transactions that have none) */
+export async function listPayees(): Promise<Payee[]> {
+  return invoke<Payee[]>('list_payees');
+}
+export async function addPayee(input: PayeeInput): Promise<number> {
+  return invoke<number>('add_payee', { input });
+}
+export async function updatePayee(id: number, input: PayeeInput): Promise<boolean> {
+  return invoke<boolean>('update_payee', { id, input });
+}
+export async function deletePayee(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_payee', { id });
+}
+export async function assignPayees(overwrite = false): Promise<number> {
+  return invoke<number>('assign_payees', { overwrite });
+}
+export async function payeeSummary(filters: TransactionSearch): Promise<PayeeSummary[]> {
+  return invoke<PayeeSummary[]>('payee_summary', { filters });
+}
+
+/* reconciliation (only cleared/uncleared can be set by hand) */
+export async function setTransactionStatus(ids: number[], status: 'cleared' | 'uncleared'): Promise<number> {
+  return invoke<number>('set_transaction_status', { ids, status });
+}
+export async function reconcileAccount(input: ReconcileInput): Promise<ReconcileResult> {
+  return invoke<ReconcileResult>('reconcile_account', { input });
+}
+export async function listReconciliations(accountId: number): Promise<Reconciliation[]> {
+  return invoke<Reconciliation[]>('list_reconciliations', { accountId });
+}
+export async function undoReconciliation(id: number): Promise<boolean> {
+  return invoke<boolean>('undo_reconciliation', { id });
+}
+
+/* attachments (receipts); the files are stored encrypted in the database */
+export async function addAttachment(transactionId: number, path: string): Promise<Attachment> {
+  return invoke<Attachment>('add_attachment', { transactionId, path });
+}
+export async function listAttachments(transactionId: number): Promise<Attachment[]> {
+  return invoke<Attachment[]>('list_attachments', { transactionId });
+}
+export async function extractAttachment(id: number, path?: string): Promise<string> {
+  return invoke<string>('extract_attachment', { id, path });
+}
+export async function deleteAttachment(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_attachment', { id });
+}
+
+/* audit log (newest first) */
+export async function listAuditLog(filters?: AuditFilter): Promise<AuditEntry[]> {
+  return invoke<AuditEntry[]>('list_audit_log', { filters });
+}
+
+/* undo/redo of transaction, account and category edits; returns the labels of the steps taken */
+export async function undo(steps = 1): Promise<string[]> {
+  return invoke<string[]>('undo', { steps });
+}
+export async function redo(steps = 1): Promise<string[]> {
+  return invoke<string[]>('redo', { steps });
+}
+export async function journalStatus(): Promise<JournalStatus> {
+  return invoke<JournalStatus>('journal_status');
+}
+
+/* trash; retention is in days, null = keep until purged by hand */
+export async function listTrash(): Promise<TrashItem[]> {
+  return invoke<TrashItem[]>('list_trash');
+}
+export async function restoreFromTrash(entity: TrashEntity, id: number): Promise<boolean> {
+  return invoke<boolean>('restore_from_trash', { entity, id });
+}
+export async function purgeTrash(olderThanDays?: number | null): Promise<number> {
+  return invoke<number>('purge_trash', { olderThanDays });
+}
+export async function getTrashRetention(): Promise<number | null> {
+  return invoke<number | null>('get_trash_retention');
+}
+export async function setTrashRetention(days: number | null): Promise<void> {
+  return invoke<void>('set_trash_retention', { days });
+}
+
+/* re-encrypts the open database; the old passphrase is checked first */
+export async function changePassphrase(oldPassphrase: string, newPassphrase: string): Promise<void> {
+  return invoke<void>('change_passphrase', { oldPassphrase, newPassphrase });
+}
+
+/* plain SQLite <-> encrypted database; the target file must not exist yet */
+export async function encryptExistingDatabase(
+  src: string, dest: string, passphrase: string, cipher?: CipherSettings | null,
+): Promise<void> {
+  return invoke<void>('encrypt_existing_database', { src, dest, passphrase, cipher });
+}
+export async function exportPlainDatabase(dest: string, passphrase: string): Promise<string> {
+  return invoke<string>('export_plain_database', { dest, passphrase });
+}
+
+/* cipher settings of the open database; re-encrypting keeps the passphrase */
+export async function getCipherSettings(): Promise<CipherSettings> {
+  return invoke<CipherSettings>('get_cipher_settings');
+}
+export async function reencryptDatabase(passphrase: string, cipher: CipherSettings): Promise<void> {
+  return invoke<void>('reencrypt_database', { passphrase, cipher });
+}
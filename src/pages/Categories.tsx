@@ -1,6 +1,6 @@
 import { useEffect, useMemo, useRef, useState, type ReactNode } from 'react';
 import ConfirmDialog from '../components/ConfirmDialog';
-import { listCategories, addCategory, renameCategory, deleteCategory } from '../lib/api';
+import { listCategories, addCategory, renameCategory, moveCategory, deleteCategory } from '../lib/api';
 import type { Category } from '../types';
 
 export default function Categories() {
@@ -10,6 +10,7 @@ export default function Categories() {
   // create
   const [creating, setCreating] = useState(false);
   const [newName, setNewName] = useState('');
+  const [newParent, setNewParent] = useState<number | null>(null);
 
   // edit
   const [editId, setEditId] = useState<number | null>(null);
@@ -39,6 +40,7 @@ export default function Categories() {
   const beginCreate = () => {
     setCreating(true);
     setNewName('');
+    setNewParent(null);
     setTimeout(() => inputRef.current?.focus(), 0);
   };
 
@@ -46,7 +48,7 @@ export default function Categories() {
     const name = newName.trim();
     if (!name) return;
     try {
-      await addCategory(name);
+      await addCategory(name, newParent);
       await refresh();
       setNewName('');
       setCreating(false);
@@ -76,6 +78,17 @@ export default function Categories() {
     }
   };
 
+  // transactions keep their category; only the position in the tree changes
+  const saveParent = async (id: number, parentId: number | null) => {
+    try {
+      await moveCategory(id, parentId);
+      await refresh();
+    } catch (e: any) {
+      console.error(e);
+      setErrorMsg(e?.toString() || 'Failed to move category.');
+    }
+  };
+
   const requestDelete = (id: number) => setConfirmDeleteId(id);
 
   const confirmDelete = async () => {
@@ -93,11 +106,16 @@ export default function Categories() {
     }
   };
 
+  // by path, so subcategories follow their parent
   const sorted = useMemo(
-    () => [...items].sort((a, b) => a.name.localeCompare(b.name, undefined, { sensitivity: 'base' })),
+    () => [...items].sort((a, b) => a.path.localeCompare(b.path, undefined, { sensitivity: 'base' })),
     [items]
   );
 
+  // a category can't move below itself
+  const isInSubtree = (c: Category, rootId: number) =>
+    c.id === rootId || c.path.startsWith(`${items.find(x => x.id === rootId)?.path ?? ''} > `);
+
   return (
     <div className="px-3 sm:px-4 md:px-6 pt-4 grid gap-6">
       <div className="card">
@@ -123,6 +141,15 @@ export default function Categories() {
                   if (e.key === 'Escape') setCreating(false);
                 }}
               />
+              <select
+                className="input h-9"
+                value={newParent ?? ''}
+                onChange={(e) => setNewParent(e.target.value ? Number(e.target.value) : null)}
+                title="Parent category"
+              >
+                <option value="">Top level</option>
+                {sorted.map(p => <option key={p.id} value={p.id}>{p.path}</option>)}
+              </select>
               <button className="btn h-9 px-3" onClick={() => setCreating(false)}>Cancel</button>
               <button className="btn btn-primary h-9 px-3" onClick={saveCreate} disabled={!newName.trim()}>
                 Add
@@ -137,6 +164,7 @@ export default function Categories() {
             <thead>
               <tr className="text-left text-xs uppercase tracking-wide text-neutral-500">
                 <th className="px-4 py-2">Name</th>
+                <th className="px-4 py-2">Parent</th>
                 <th className="px-4 py-2 w-28 text-right">Actions</th>
               </tr>
             </thead>
@@ -156,9 +184,22 @@ export default function Categories() {
                         }}
                       />
                     ) : (
-                      <span className="leading-9">{c.name}</span>
+                      <span className="leading-9" style={{ paddingLeft: `${c.depth * 1.25}rem` }}>{c.name}</span>
                     )}
                   </td>
+                  <td className="px-4 py-2">
+                    <select
+                      className="input h-9"
+                      value={c.parent_id ?? ''}
+                      onChange={(e) => saveParent(c.id, e.target.value ? Number(e.target.value) : null)}
+                      title="Parent category"
+                    >
+                      <option value="">Top level</option>
+                      {sorted.filter(p => !isInSubtree(p, c.id)).map(p => (
+                        <option key={p.id} value={p.id}>{p.path}</option>
+                      ))}
+                    </select>
+                  </td>
                   <td className="px-4 py-2">
                     {editId === c.id ? (
                       <div className="flex gap-1 justify-end">
@@ -185,7 +226,7 @@ export default function Categories() {
 
               {sorted.length === 0 && !creating && (
                 <tr>
-                  <td className="px-4 py-6 text-sm text-neutral-500" colSpan={2}>
+                  <td className="px-4 py-6 text-sm text-neutral-500" colSpan={3}>
                     No categories yet.
                   </td>
                 </tr>
@@ -205,7 +246,7 @@ export default function Categories() {
       <ConfirmDialog
         open={confirmDeleteId !== null}
         title="Delete category?"
-        description="This will only work if the category is not used by any transaction."
+        description="This will only work if the category is not used by any transaction. Its subcategories move up one level."
         confirmText="Delete"
         cancelText="Cancel"
         danger
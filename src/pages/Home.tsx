@@ -11,6 +11,7 @@ import type {
   Account,
   Transaction,
   NewTransaction,
+  AddedTransaction,
   UpdateTransaction,
 } from '../types';
 import Amount from '../components/Amount';
@@ -29,6 +30,8 @@ export default function Home() {
 
   // deletion modal state for transactions
   const [confirmTxId, setConfirmTxId] = useState<number | null>(null);
+  // just-added transaction that looks like an existing one
+  const [duplicateOf, setDuplicateOf] = useState<AddedTransaction | null>(null);
 
   async function refresh() {
     setLoading(true);
@@ -62,7 +65,15 @@ export default function Home() {
   }, [accounts]);
 
   const handleAddTx = async (input: NewTransaction) => {
-    await addTransaction(input);
+    const added = await addTransaction(input);
+    if (added.possible_duplicates.length > 0) setDuplicateOf(added);
+    await refresh();
+  };
+
+  const undoDuplicate = async () => {
+    if (duplicateOf == null) return;
+    await deleteTransaction(duplicateOf.id);
+    setDuplicateOf(null);
     await refresh();
   };
 
@@ -160,6 +171,23 @@ export default function Home() {
         onCancel={() => setConfirmTxId(null)}
         onConfirm={confirmDeleteTx}
       />
+
+      <ConfirmDialog
+        open={duplicateOf !== null}
+        title="Possible duplicate"
+        description={
+          duplicateOf
+            ? `Looks like ${duplicateOf.possible_duplicates
+                .map(d => `${d.date} ${d.description ?? ''}`.trim())
+                .join(', ')}. Undo the new transaction?`
+            : undefined
+        }
+        confirmText="Undo"
+        cancelText="Keep"
+        danger
+        onCancel={() => setDuplicateOf(null)}
+        onConfirm={undoDuplicate}
+      />
     </div>
   );
 }
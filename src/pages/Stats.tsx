@@ -4,6 +4,7 @@ import {
   listAccounts,
   listTransactionsAll,
   searchTransactions,
+  categoryTotals,
 } from '../lib/api';
 import type { Account } from '../types';
 import type { TxMini } from '../lib/api';
@@ -29,6 +30,8 @@ export default function Stats() {
   // controls
   const [groupBy, setGroupBy] = useState<'monthly' | 'yearly'>('monthly');
   const [range, setRange] = useState<'12m' | '24m' | 'all'>('12m');
+  // category roll-up: '' = as booked, otherwise the depth to roll up to (0 = top level)
+  const [catLevel, setCatLevel] = useState<'' | '0' | '1' | '2'>('0');
 
   // quick helper (month start/end)
   const today = new Date();
@@ -53,30 +56,15 @@ export default function Stats() {
   useEffect(() => {
   (async () => {
     try {
-      const pageSize = 1000;
-      let offset = 0;
-      const all: TxForCat[] = [];
-      while (true) {
-        const res = await searchTransactions({
-          tx_type: 'expense', // only expenses
-          limit: pageSize,
-          offset,
-          sort_by: 'id',
-          sort_dir: 'asc',
-        });
-        const items = (res.items ?? []) as any[];
-        all.push(...items.map(it => ({ amount: it.amount, category: it.category })));
-        const total: number = (res as any).total ?? items.length;
-        offset += pageSize;
-        if (offset >= total) break;
-      }
-      setTxCatItems(all);
+      // per split line, so split transactions count towards each of their categories
+      const rows = await categoryTotals({ tx_type: 'expense' }, catLevel === '' ? undefined : Number(catLevel));
+      setTxCatItems(rows.map(r => ({ amount: r.total, category: r.path ?? r.category })));
     } catch (e) {
       console.error('fetch categories failed', e);
       setTxCatItems([]);
     }
   })();
-}, []);
+}, [catLevel]);
 
   /* =========================
      Derived values / helpers
@@ -371,7 +359,20 @@ const expensesByCategory = useMemo(() => {
       <section className="card p-5">
         <div className="flex items-center justify-between">
           <h2 className="text-base font-semibold">Expenses by category</h2>
-          <span className="text-xs text-neutral-500">Transfers excluded</span>
+          <div className="flex items-center gap-2">
+            <span className="text-xs text-neutral-500">Transfers excluded</span>
+            <select
+              className="input"
+              value={catLevel}
+              onChange={(e) => setCatLevel(e.target.value as any)}
+              title="Roll up subcategories"
+            >
+              <option value="0">Top level</option>
+              <option value="1">2 levels</option>
+              <option value="2">3 levels</option>
+              <option value="">As booked</option>
+            </select>
+          </div>
         </div>
 
         <div className="mt-3 w-full" style={{ height: 320 }}>
@@ -97,17 +97,19 @@ export default function Transactions() {
     { key: 'date',        label: 'Date' },
     { key: 'account',     label: 'Account' },
     { key: 'category',    label: 'Category' },
+    { key: 'tags',        label: 'Tags' },
     { key: 'description', label: 'Notes' },
     { key: 'amount',      label: 'Value' },
   ] as const;
 
   const [exportCols, setExportCols] = useState<string[]>(
-    ALL_EXPORT_COLS.map(c => c.key) // default: all
+    ALL_EXPORT_COLS.map(c => c.key).filter(k => k !== 'tags') // default: all but tags
   );
   const toggleExportCol = (key: string) =>
     setExportCols(cols => cols.includes(key) ? cols.filter(k => k !== key) : [...cols, key]);
 
   const [exportFmt, setExportFmt] = useState<'xlsx' | 'pdf'>('xlsx');
+  const [includeReceipts, setIncludeReceipts] = useState(false); // reimbursable PDF only
   const [exportOkPath, setExportOkPath] = useState<string | null>(null);
 
   const { hidden } = useOutletContext<OutletCtx>();
@@ -302,7 +304,9 @@ const adjustedGlobal = useMemo(() => {
       alert('Filter to a reimbursable account first.');
       return;
     }
-    if (exportCols.length === 0) {
+    // the reimbursable report has no tag column
+    const reimbCols = exportCols.filter(k => k !== 'tags');
+    if (reimbCols.length === 0) {
       alert('Please choose at least one column to export.');
       return;
     }
@@ -316,8 +320,8 @@ const adjustedGlobal = useMemo(() => {
       };
       const path =
         exportFmt === 'pdf'
-          ? await exportReimbursableReportPdf(common, exportCols)
-          : await exportReimbursableReportXlsx(common, exportCols);
+          ? await exportReimbursableReportPdf(common, reimbCols, includeReceipts)
+          : await exportReimbursableReportXlsx(common, reimbCols);
 
       setExportOkPath(path);
     } catch (e) {
@@ -607,6 +611,16 @@ const adjustedGlobal = useMemo(() => {
         </button>
 
         {/* NEW: reimbursable button */}
+        {exportFmt === 'pdf' && !reimbursableDisabled && (
+          <label className="flex items-center gap-2 mt-3 text-sm">
+            <input
+              type="checkbox"
+              checked={includeReceipts}
+              onChange={(e) => setIncludeReceipts(e.target.checked)}
+            />
+            <span>Append receipts to the reimbursable report</span>
+          </label>
+        )}
         <button
           className="btn w-full mt-2"
           onClick={handleExportReimbursable}
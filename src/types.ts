@@ -9,7 +9,9 @@ export type Account = {
   name: string;
   type: AccountType;
   color?: string | null;
+  currency: string; // ISO 4217, e.g. "EUR"
   balance: number;
+  balance_base?: number | null; // converted into the requested base currency
 };
 
 export type NewAccount = {
@@ -17,6 +19,7 @@ export type NewAccount = {
   account_type?: AccountType;
   color?: string | null;
   initial_balance?: number;
+  currency?: string;
 };
 
 export type UpdateAccount = {
@@ -24,19 +27,51 @@ export type UpdateAccount = {
   name?: string;
   type?: AccountType;
   color?: string | null;
+  currency?: string;
 };
 
-export type Category = { id: ID; name: string };
+export type Category = {
+  id: ID;
+  name: string;
+  parent_id?: ID | null;
+  path: string; // "Food > Groceries"
+  depth: number; // 0 = top level
+};
 
 export type Transaction = {
   id: ID;
   account_id: ID;
   account_name?: string;
   account_color?: string | null;
+  currency?: string;
   date: string; // YYYY-MM-DD
   category?: string | null;
   description?: string | null;
   amount: number;
+  transfer_id?: ID | null; // shared by both legs of a transfer
+  counterparty?: string | null; // from statement imports
+  counterparty_iban?: string | null;
+  splits?: Split[]; // empty unless split across categories
+  tags?: string[];
+  payee_id?: ID | null;
+  payee?: string | null;
+  status?: TxStatus;
+};
+
+export type TxStatus = 'uncleared' | 'cleared' | 'reconciled';
+
+export type Split = {
+  id: ID;
+  transaction_id: ID;
+  category?: string | null;
+  amount: number;
+  memo?: string | null;
+};
+
+export type SplitInput = {
+  category?: string | null;
+  amount: number;
+  memo?: string | null;
 };
 
 export type NewTransaction = {
@@ -45,6 +80,24 @@ export type NewTransaction = {
   amount: number;
   description?: string | null;
   category?: string | null;
+  splits?: SplitInput[] | null; // must add up to amount; replaces category
+  tags?: string[]; // added to the tags from rules
+  payee?: string | null; // by name, created if new (default: matched by alias)
+};
+
+export type AddedTransaction = {
+  id: ID;
+  possible_duplicates: Transaction[]; // same amount within a few days (saved anyway)
+};
+
+export type NewTransfer = {
+  from_account_id: ID;
+  to_account_id: ID;
+  date: string; // YYYY-MM-DD
+  amount: number; // positive
+  to_amount?: number | null; // when the accounts use different currencies
+  description?: string | null;
+  category?: string | null; // defaults to "Transfer"
 };
 
 export type UpdateTransaction = {
@@ -54,10 +107,13 @@ export type UpdateTransaction = {
   amount?: number;
   description?: string | null;
   category?: string | null;
+  splits?: SplitInput[] | null; // [] removes the split
+  tags?: string[] | null; // replaces the tags; [] removes all
+  payee?: string | null; // by name, created if new; '' removes the payee
 };
 
-export type TxTypeFilter = 'all' | 'income' | 'expense';
-export type TxSortBy = 'date' | 'category' | 'description' | 'amount' | 'account' | 'id';
+export type TxTypeFilter = 'all' | 'income' | 'expense' | 'transfer';
+export type TxSortBy = 'date' | 'category' | 'description' | 'amount' | 'account' | 'payee' | 'id';
 export type TxSortDir = 'asc' | 'desc';
 
 export type TransactionSearch = {
@@ -70,8 +126,16 @@ export type TransactionSearch = {
   offset?: number; // use -1 to request last page from server
   sort_by?: TxSortBy;
   sort_dir?: TxSortDir;
+  base_currency?: string;
+  tags?: string[];
+  tag_match?: TagMatch; // default 'any'
+  category_id?: number | null; // includes subcategories
+  payee_id?: number | null;
+  status?: TxStatus | null;
 };
 
+export type TagMatch = 'any' | 'all';
+
 export type TransactionSearchResult = {
   items: Transaction[];
   total: number;
@@ -84,4 +148,394 @@ export type TransactionSearchResult = {
   sum_income_reimb?: number;
   sum_expense_reimb?: number;
   sum_init?: number;
+  // sums above are converted into base_currency
+  base_currency?: string;
+  by_currency?: CurrencyTotals[];
+  missing_rates?: string[];
+};
+
+export type CategoryTotal = {
+  category_id?: ID | null;
+  category?: string | null;
+  path?: string | null; // "Food > Groceries"
+  total: number; // in the requested base currency
+};
+
+export type CurrencyTotals = {
+  currency: string;
+  sum_income: number;
+  sum_expense: number;
+  sum_init: number;
+};
+
+export type ExchangeRate = {
+  id: ID;
+  date: string; // YYYY-MM-DD
+  currency: string;
+  base: string;
+  rate: number; // 1 currency = rate base
+};
+
+export type NewExchangeRate = Omit<ExchangeRate, 'id'>;
+
+/* statement import (dry_run defaults to true: preview only) */
+export type ImportRow = {
+  line: number;
+  date: string; // YYYY-MM-DD
+  description?: string | null;
+  amount: number;
+  category?: string | null;
+  counterparty?: string | null;
+  counterparty_iban?: string | null;
+  bank_ref?: string | null;
+  currency?: string | null;
+  already_imported: boolean; // skipped on commit
+  splits?: SplitInput[]; // QIF split lines
+  possible_duplicates: ID[]; // same amount within a few days; review before committing
+  excluded: boolean; // listed in skip_lines
+  tags: string[]; // added by rules
+  payee_id?: ID | null; // matched by payee alias
+  payee?: string | null;
+};
+
+export type ImportIssue = { line: number; message: string };
+
+export type ImportResult = {
+  rows: ImportRow[];
+  errors: ImportIssue[];
+  imported: number; // 0 for a dry run
+  skipped: number; // already imported or excluded
+};
+
+export type StatementImport = {
+  path: string;
+  account_id: ID;
+  dry_run?: boolean;
+  skip_lines?: number[] | null; // rows excluded after the preview
+};
+
+export type CsvColumnMapping = {
+  // 0-based column indexes; map `amount` or `debit`/`credit`
+  date: number;
+  description?: number | null;
+  amount?: number | null;
+  debit?: number | null;
+  credit?: number | null;
+  category?: number | null;
+};
+
+export type CsvImport = {
+  path: string;
+  account_id: ID;
+  mapping: CsvColumnMapping;
+  delimiter?: string | null; // ';' | ',' | 'tab'
+  encoding?: string | null; // 'utf-8' | 'windows-1252' | ...
+  date_format?: string | null; // chrono format, e.g. '%d.%m.%Y'
+  decimal_separator?: ',' | '.' | null;
+  skip_rows?: number | null;
+  has_header?: boolean | null;
+  dry_run?: boolean;
+  skip_lines?: number[] | null;
+};
+
+export type CsvImportResult = ImportResult & { headers: string[] };
+
+/* OFX/QFX and QIF: accounts are matched by name or created on commit */
+export type MultiAccountImport = {
+  path: string;
+  account_id?: ID | null; // when the file doesn't name its account
+  date_format?: string | null; // QIF only
+  dry_run?: boolean;
+  skip_lines?: number[] | null;
+};
+
+export type AccountImport = ImportResult & {
+  name: string;
+  account_id?: ID | null; // null = created on commit
+  created: boolean;
+};
+
+/* auto-categorization rules: set conditions must all match, null = any */
+export type Rule = {
+  id: ID;
+  name: string;
+  position: number; // evaluation order; first rule setting a category/description wins
+  enabled: boolean;
+  description_contains?: string | null; // case-insensitive, also checks the counterparty
+  description_regex?: string | null;
+  amount_min?: number | null; // signed, inclusive
+  amount_max?: number | null;
+  account_id?: ID | null;
+  category?: string | null;
+  set_description?: string | null;
+  add_tag?: string | null;
+};
+
+export type RuleInput = Omit<Rule, 'id' | 'position' | 'enabled'> & {
+  position?: number | null;
+  enabled?: boolean | null;
+};
+
+export type ApplyRules = {
+  account_id?: ID | null;
+  date_from?: string | null;
+  date_to?: string | null;
+  overwrite?: boolean; // also re-categorize transactions that have a category
+  dry_run?: boolean;
+};
+
+export type RuleChange = {
+  transaction_id: ID;
+  date: string;
+  rule_ids: ID[];
+  description?: string | null;
+  new_description?: string | null; // null = unchanged
+  category?: string | null;
+  new_category?: string | null; // null = unchanged
+  new_tags: string[];
+};
+
+export type ApplyRulesResult = {
+  changes: RuleChange[];
+  applied: number; // 0 for a dry run
+};
+
+/* recurring transactions; rrule supports FREQ, INTERVAL, COUNT, UNTIL, BYMONTHDAY, BYDAY */
+export type Recurring = {
+  id: ID;
+  account_id: ID;
+  account_name: string;
+  currency: string;
+  category?: string | null;
+  description?: string | null;
+  amount: number;
+  rrule: string; // e.g. 'FREQ=MONTHLY;BYMONTHDAY=1'
+  start_date: string; // YYYY-MM-DD
+  auto_post: boolean; // false = due occurrences wait for confirmation
+  handled_through?: string | null;
+  next_date?: string | null; // null = schedule has ended (or can't be read)
+  schedule_error?: string | null; // set when rrule / start_date can't be read; never posted
+};
+
+export type RecurringInput = {
+  account_id: ID;
+  category?: string | null;
+  description?: string | null;
+  amount: number;
+  rrule: string;
+  start_date: string;
+  auto_post?: boolean;
+};
+
+export type Occurrence = {
+  recurring_id: ID;
+  date: string; // scheduled date; identifies the occurrence
+  post_date: string; // booking date (differs when moved)
+  account_id: ID;
+  amount: number;
+  description?: string | null;
+  category?: string | null;
+  status: 'planned' | 'pending' | 'skipped'; // pending = due, waiting for confirmation
+};
+
+export type OccurrenceEdit = {
+  recurring_id: ID;
+  date: string;
+  post_date?: string | null;
+  amount?: number | null;
+  description?: string | null;
+  category?: string | null;
+};
+
+/* budgets: one per category; amounts are spending limits per period */
+export type BudgetPeriod = 'monthly' | 'quarterly' | 'yearly';
+
+export type Budget = {
+  id: ID;
+  category_id: ID;
+  category: string;
+  period: BudgetPeriod;
+  amount: number;
+  currency: string;
+  rollover: boolean; // unspent money carries into the next period
+  start_date: string; // YYYY-MM-DD
+};
+
+export type BudgetInput = {
+  category: string;
+  period?: BudgetPeriod;
+  amount: number;
+  currency?: string | null;
+  rollover?: boolean;
+  start_date?: string | null; // default: first day of the current month
+};
+
+export type BudgetLine = {
+  budget_id: ID;
+  category_id: ID;
+  category: string;
+  period: BudgetPeriod;
+  budgeted: number; // amount × periods in the range
+  carried: number; // rolled over from before the range
+  actual: number; // net spending in the range
+  remaining: number; // negative = overspent
+  overspent: boolean;
+};
+
+export type BudgetReport = {
+  date_from: string;
+  date_to: string;
+  base_currency: string;
+  lines: BudgetLine[];
+  total_budgeted: number;
+  total_actual: number;
+  total_remaining: number;
+  missing_rates: string[];
+};
+
+/* envelope budgeting (zero-based): income is assigned to envelopes month by month */
+export type BudgetMode = 'categories' | 'envelopes';
+
+export type Envelope = {
+  id: ID;
+  name: string;
+  position: number;
+  categories: string[]; // spending in these categories draws the envelope down
+};
+
+export type EnvelopeStatus = {
+  id: ID;
+  name: string;
+  carried: number; // balance at the end of the previous month (negative = overspent)
+  assigned: number;
+  moved: number; // net moves in (+) / out (-)
+  activity: number; // spending (negative) and refunds
+  available: number;
+};
+
+export type EnvelopeMonth = {
+  month: string; // YYYY-MM
+  currency: string;
+  income: number;
+  available_to_budget: number; // all income so far minus everything assigned so far
+  envelopes: EnvelopeStatus[];
+  unassigned_activity: number; // spending in categories without an envelope
+  missing_rates: string[];
+};
+
+/* tags: free-form labels, many per transaction */
+export type Tag = {
+  id: ID;
+  name: string;
+  usage: number; // number of tagged transactions
+};
+
+/* payees: one per merchant; aliases map the bank's spellings to it */
+export type Payee = {
+  id: ID;
+  name: string;
+  transactions: number;
+  aliases: string[];
+};
+
+export type PayeeInput = {
+  name: string;
+  aliases?: string[]; // replaces the aliases on update
+};
+
+export type PayeeSummary = {
+  payee_id?: ID | null; // null = transactions without a payee
+  payee?: string | null;
+  transactions: number;
+  spent: number; // negative, in the requested base currency
+  received: number;
+  last_date: string;
+};
+
+/* reconciliation against a bank statement; reconciled transactions are locked */
+export type ReconcileInput = {
+  account_id: ID;
+  statement_date: string; // YYYY-MM-DD, inclusive
+  statement_balance: number;
+  finish?: boolean; // lock the cleared transactions if the balance matches
+};
+
+export type ReconcileResult = {
+  account_id: ID;
+  statement_date: string;
+  statement_balance: number;
+  cleared_balance: number;
+  difference: number; // 0 = balanced
+  cleared_count: number;
+  uncleared_count: number;
+  uncleared_amount: number;
+  reconciliation_id?: ID | null; // set once finished
+};
+
+export type Reconciliation = {
+  id: ID;
+  account_id: ID;
+  statement_date: string;
+  statement_balance: number;
+  created_at: string;
+  transactions: number;
+};
+
+/* a file kept with a transaction (content is only returned by extractAttachment) */
+export type Attachment = {
+  id: ID;
+  transaction_id: ID;
+  filename: string;
+  mime_type: string;
+  size: number; // bytes
+  sha256: string;
+  created_at: string;
+};
+
+/* audit log: full rows as JSON before/after each change (amounts in cents) */
+export type TrashEntity = 'transaction' | 'account' | 'category';
+export type AuditEntity = TrashEntity | 'payee' | 'tag' | 'reconciliation';
+
+export type AuditFilter = {
+  entity?: AuditEntity | null;
+  entity_id?: ID | null;
+  date_from?: string | null; // YYYY-MM-DD, UTC
+  date_to?: string | null;
+  limit?: number; // default 200
+  offset?: number;
+};
+
+export type AuditEntry = {
+  id: ID;
+  at: string; // 'YYYY-MM-DD HH:MM:SS', UTC
+  entity: AuditEntity;
+  entity_id: ID;
+  action: 'insert' | 'update' | 'trash' | 'restore' | 'delete';
+  before?: Record<string, unknown> | null;
+  after?: Record<string, unknown> | null;
+};
+
+/* undo/redo journal (kept until the database is closed) */
+export type JournalStatus = {
+  undo: string[]; // labels like 'Delete transaction', next one first
+  redo: string[];
+};
+
+/* trash: deleted rows until restored or purged */
+export type TrashItem = {
+  entity: TrashEntity;
+  id: ID;
+  name?: string | null; // description for transactions
+  date?: string | null; // transactions only
+  amount?: number | null;
+  currency?: string | null;
+  account_name?: string | null;
+  deleted_at: string; // 'YYYY-MM-DD HH:MM:SS', UTC
+};
+
+/* SQLCipher settings besides the key; unset = SQLCipher 4 defaults */
+export type CipherSettings = {
+  kdf_iter?: number | null; // default 256000, at least 64000
+  page_size?: number | null; // default 4096, power of two 512..65536
+  hmac_algorithm?: 'HMAC_SHA1' | 'HMAC_SHA256' | 'HMAC_SHA512' | null; // default HMAC_SHA512
 };